@@ -0,0 +1,92 @@
+//! Deadlines as unix timestamps
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Unix timestamp, in seconds, after which `ValidateDeadline` fails
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Deadline(pub i64);
+
+impl Deadline {
+    /// Deadline that never expires
+    pub const NEVER: Self = Self(0);
+
+    /// Returns the deadline as a unix timestamp
+    pub fn unix_timestamp(self) -> i64 {
+        self.0
+    }
+}
+
+impl From<i64> for Deadline {
+    fn from(unix_timestamp: i64) -> Self {
+        Self(unix_timestamp)
+    }
+}
+
+impl From<Deadline> for i64 {
+    fn from(deadline: Deadline) -> Self {
+        deadline.0
+    }
+}
+
+impl From<SystemTime> for Deadline {
+    /// Rounds down to the second, so the deadline never ends up later than
+    /// `time`, except in the first second after the epoch, which maps to 1
+    /// rather than to `Deadline::NEVER`. Times outside the i64 range
+    /// saturate.
+    fn from(time: SystemTime) -> Self {
+        let seconds = match time.duration_since(UNIX_EPOCH) {
+            Ok(elapsed) => i64::try_from(elapsed.as_secs()).unwrap_or(i64::MAX),
+            Err(error) => {
+                let before = error.duration();
+                let seconds = i64::try_from(before.as_secs()).unwrap_or(i64::MAX);
+                // Round towards the past
+                let seconds = if before.subsec_nanos() > 0 {
+                    seconds.saturating_add(1)
+                } else {
+                    seconds
+                };
+                seconds.saturating_neg()
+            }
+        };
+        // 0 would never expire
+        Self(if seconds == 0 { 1 } else { seconds })
+    }
+}
+
+/// Returns the deadline `duration` from now, according to the local clock
+///
+/// The program compares against the cluster clock, which may differ from
+/// the local clock by a few seconds.
+pub fn deadline_in(duration: Duration) -> Deadline {
+    let now = Deadline::from(SystemTime::now()).0;
+    let seconds = i64::try_from(duration.as_secs()).unwrap_or(i64::MAX);
+    Deadline(now.saturating_add(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_system_time() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_700_000_000_999);
+        assert_eq!(Deadline::from(time), Deadline(1700000000));
+        assert_eq!(Deadline::from(UNIX_EPOCH), Deadline(1));
+        let time = UNIX_EPOCH + Duration::from_millis(999);
+        assert_eq!(Deadline::from(time), Deadline(1));
+        assert_ne!(Deadline::from(UNIX_EPOCH), Deadline::NEVER);
+
+        let before = UNIX_EPOCH - Duration::from_millis(1_500);
+        assert_eq!(Deadline::from(before), Deadline(-2));
+        let before = UNIX_EPOCH - Duration::from_secs(1);
+        assert_eq!(Deadline::from(before), Deadline(-1));
+    }
+
+    #[test]
+    fn test_deadline_in() {
+        let now = Deadline::from(SystemTime::now());
+        let deadline = deadline_in(Duration::from_secs(300));
+        assert!(deadline.0 - now.0 >= 300 && deadline.0 - now.0 <= 301);
+        assert_eq!(deadline_in(Duration::MAX), Deadline(i64::MAX));
+    }
+}
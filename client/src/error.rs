@@ -0,0 +1,299 @@
+//! Decoding of program errors from failed transactions
+//!
+//! The program fails with `InstructionError::Custom(code)`. Codes start at
+//! [`ERROR_CODE_BASE`], except for `ValidateDeadline` with the legacy
+//! discriminator 0, which returns the small legacy codes that countless
+//! other programs use too, for instance the system program that the
+//! cooldown and session instructions invoke. [`program_failure`] uses the
+//! failed instruction's program ID and discriminator to tell them apart.
+
+use crate::ID;
+use solana_instruction::{error::InstructionError, Instruction};
+use solana_transaction_error::TransactionError;
+use std::fmt;
+
+/// Base of the custom error codes, "DEAD" in hex
+pub const ERROR_CODE_BASE: u32 = 0xDEAD_0000;
+
+/// Errors returned by the deadline validator program, mirroring the
+/// program's `DeadlineError`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DeadlineError {
+    /// The deadline has expired (current time > deadline)
+    DeadlineExpired,
+    /// The instruction data is invalid or malformed
+    InvalidInstructionData,
+    /// The cluster clock differs from the reference time by more than allowed
+    ClockDrift,
+    /// The oracle update is older than the allowed staleness
+    OracleStale,
+    /// The oracle account has the wrong owner or an invalid layout
+    InvalidOracleAccount,
+    /// No token transfer in the transaction pays the current auction price
+    InsufficientPayment,
+    /// The current time is outside the allowed weekly schedule
+    OutsideSchedule,
+    /// The time-predicate expression evaluated to false
+    ExpressionFalse,
+    /// The minimum interval since the last action has not elapsed
+    CooldownActive,
+    /// The cooldown account is not initialized, malformed or misplaced
+    InvalidCooldown,
+    /// The signer is not the authority of the account
+    Unauthorized,
+    /// The session has expired (current time > expires_at)
+    SessionExpired,
+    /// The owner has revoked the session
+    SessionRevoked,
+    /// The amount would exceed the session's spend cap
+    SpendCapExceeded,
+    /// The session account is not initialized, malformed or misplaced
+    InvalidSession,
+    /// The current time is before the envelope's not_before
+    NotYetValid,
+    /// The current slot is outside the envelope's slot range
+    SlotOutOfRange,
+    /// The session amount does not match the delegate's token transfers
+    SessionTransferMismatch,
+}
+
+impl DeadlineError {
+    /// All errors, ordered by code
+    pub const ALL: [DeadlineError; 18] = [
+        DeadlineError::DeadlineExpired,
+        DeadlineError::InvalidInstructionData,
+        DeadlineError::ClockDrift,
+        DeadlineError::OracleStale,
+        DeadlineError::InvalidOracleAccount,
+        DeadlineError::InsufficientPayment,
+        DeadlineError::OutsideSchedule,
+        DeadlineError::ExpressionFalse,
+        DeadlineError::CooldownActive,
+        DeadlineError::InvalidCooldown,
+        DeadlineError::Unauthorized,
+        DeadlineError::SessionExpired,
+        DeadlineError::SessionRevoked,
+        DeadlineError::SpendCapExceeded,
+        DeadlineError::InvalidSession,
+        DeadlineError::NotYetValid,
+        DeadlineError::SlotOutOfRange,
+        DeadlineError::SessionTransferMismatch,
+    ];
+
+    /// Returns the `InstructionError::Custom` code
+    pub fn code(self) -> u32 {
+        ERROR_CODE_BASE + self as u32
+    }
+
+    /// Returns the `InstructionError::Custom` code of `ValidateDeadline`
+    /// with the legacy discriminator 0
+    pub fn legacy_code(self) -> u32 {
+        self as u32
+    }
+}
+
+impl TryFrom<u32> for DeadlineError {
+    /// The unknown code
+    type Error = u32;
+
+    /// Accepts both codes and legacy codes
+    fn try_from(code: u32) -> Result<Self, u32> {
+        let index = code.checked_sub(ERROR_CODE_BASE).unwrap_or(code);
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| Self::ALL.get(index))
+            .copied()
+            .ok_or(code)
+    }
+}
+
+impl fmt::Display for DeadlineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::DeadlineExpired => "Deadline has expired",
+            Self::InvalidInstructionData => "Invalid instruction data",
+            Self::ClockDrift => "Clock drift exceeds tolerance",
+            Self::OracleStale => "Oracle update is stale",
+            Self::InvalidOracleAccount => "Invalid oracle account",
+            Self::InsufficientPayment => "Insufficient payment",
+            Self::OutsideSchedule => "Outside allowed schedule",
+            Self::ExpressionFalse => "Expression not satisfied",
+            Self::CooldownActive => "Cooldown still active",
+            Self::InvalidCooldown => "Invalid cooldown account",
+            Self::Unauthorized => "Unauthorized signer",
+            Self::SessionExpired => "Session has expired",
+            Self::SessionRevoked => "Session has been revoked",
+            Self::SpendCapExceeded => "Session spend cap exceeded",
+            Self::InvalidSession => "Invalid session account",
+            Self::NotYetValid => "Not yet valid",
+            Self::SlotOutOfRange => "Slot out of range",
+            Self::SessionTransferMismatch => "Session transfer mismatch",
+        })
+    }
+}
+
+impl std::error::Error for DeadlineError {}
+
+/// A transaction failure caused by an instruction of the deadline
+/// validator program
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProgramFailure {
+    /// Index of the failed instruction in the transaction
+    pub index: u8,
+    /// Error returned by the program
+    pub error: InstructionError,
+    /// Whether the instruction has the legacy discriminator 0, so small
+    /// codes are legacy codes
+    pub legacy: bool,
+}
+
+impl ProgramFailure {
+    /// Returns the program-specific error, if the program failed with one
+    ///
+    /// Returns `None` for generic errors, such as a missing signature, and
+    /// for small codes of instructions without the legacy discriminator,
+    /// which come from the programs they invoke.
+    pub fn deadline_error(&self) -> Option<DeadlineError> {
+        match self.error {
+            InstructionError::Custom(code) if code >= ERROR_CODE_BASE || self.legacy => {
+                DeadlineError::try_from(code).ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ProgramFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.deadline_error() {
+            Some(error) => write!(f, "Deadline validator instruction {}: {error}", self.index),
+            None => write!(
+                f,
+                "Deadline validator instruction {}: {}",
+                self.index, self.error
+            ),
+        }
+    }
+}
+
+/// Returns the failure if `error` was raised by a top-level instruction of
+/// the deadline validator program
+///
+/// `instructions` are the transaction's instructions, in order. Returns
+/// `None` for failures of other programs, including a deadline validator
+/// invoked through CPI, which the runtime attributes to the calling
+/// instruction.
+pub fn program_failure(
+    error: &TransactionError,
+    instructions: &[Instruction],
+) -> Option<ProgramFailure> {
+    let TransactionError::InstructionError(index, error) = error else {
+        return None;
+    };
+    let instruction = instructions.get(usize::from(*index))?;
+    (instruction.program_id == ID).then(|| ProgramFailure {
+        index: *index,
+        error: error.clone(),
+        legacy: instruction.data.first() == Some(&0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction::{initialize_cooldown, validate_deadline, validate_session};
+    use cascade_protocol_deadline_validator::error as program;
+    use solana_pubkey::Pubkey;
+
+    #[test]
+    fn test_errors_match_program() {
+        assert_eq!(DeadlineError::ALL.len(), program::DeadlineError::ALL.len());
+        for (error, expected) in DeadlineError::ALL
+            .into_iter()
+            .zip(program::DeadlineError::ALL)
+        {
+            assert_eq!(error.code(), expected.code());
+            assert_eq!(error.legacy_code(), expected.legacy_code());
+            assert_eq!(format!("{error:?}"), format!("{expected:?}"));
+            assert_eq!(error.to_string(), expected.to_string());
+        }
+    }
+
+    #[test]
+    fn test_try_from_code() {
+        for error in DeadlineError::ALL {
+            assert_eq!(DeadlineError::try_from(error.code()), Ok(error));
+            assert_eq!(DeadlineError::try_from(error.legacy_code()), Ok(error));
+        }
+        assert_eq!(DeadlineError::try_from(18), Err(18));
+        assert_eq!(
+            DeadlineError::try_from(ERROR_CODE_BASE + 18),
+            Err(ERROR_CODE_BASE + 18)
+        );
+    }
+
+    #[test]
+    fn test_program_failure() {
+        let other = Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![]);
+        let account = Pubkey::new_unique();
+        let mut legacy_data = vec![0];
+        legacy_data.extend_from_slice(&1700000000i64.to_le_bytes());
+        let instructions = [
+            other,
+            validate_deadline(1700000000),
+            validate_session(&account, &account, 1),
+            Instruction::new_with_bytes(ID, &legacy_data, vec![]),
+            initialize_cooldown(&account, &account, 60, 0),
+        ];
+
+        let error = TransactionError::InstructionError(1, InstructionError::Custom(0xDEAD_0000));
+        let failure = program_failure(&error, &instructions).unwrap();
+        assert_eq!(failure.index, 1);
+        assert_eq!(
+            failure.deadline_error(),
+            Some(DeadlineError::DeadlineExpired)
+        );
+        assert_eq!(
+            failure.to_string(),
+            "Deadline validator instruction 1: Deadline has expired"
+        );
+
+        // Legacy code, from the legacy discriminator
+        let error = TransactionError::InstructionError(3, InstructionError::Custom(0));
+        let failure = program_failure(&error, &instructions).unwrap();
+        assert!(failure.legacy);
+        assert_eq!(
+            failure.deadline_error(),
+            Some(DeadlineError::DeadlineExpired)
+        );
+
+        // Small codes of other discriminators come from invoked programs,
+        // here the system program's AccountAlreadyInUse
+        for index in [1, 4] {
+            let error = TransactionError::InstructionError(index, InstructionError::Custom(0));
+            let failure = program_failure(&error, &instructions).unwrap();
+            assert!(!failure.legacy);
+            assert_eq!(failure.deadline_error(), None);
+        }
+
+        // Same code, other program
+        let error = TransactionError::InstructionError(0, InstructionError::Custom(0));
+        assert_eq!(program_failure(&error, &instructions), None);
+
+        let error =
+            TransactionError::InstructionError(2, InstructionError::MissingRequiredSignature);
+        let failure = program_failure(&error, &instructions).unwrap();
+        assert_eq!(failure.deadline_error(), None);
+
+        let error = TransactionError::InstructionError(2, InstructionError::Custom(1000));
+        let failure = program_failure(&error, &instructions).unwrap();
+        assert_eq!(failure.deadline_error(), None);
+
+        let error = TransactionError::InstructionError(5, InstructionError::Custom(0));
+        assert_eq!(program_failure(&error, &instructions), None);
+        assert_eq!(
+            program_failure(&TransactionError::BlockhashNotFound, &instructions),
+            None
+        );
+    }
+}
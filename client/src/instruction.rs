@@ -0,0 +1,445 @@
+//! Instruction builders
+//!
+//! Instruction data is packed by `cascade_protocol_deadline_codec`, the
+//! same codec the program unpacks with. Instructions built here can also
+//! be wrapped in a versioned envelope with [`versioned`].
+
+use crate::{deadline::Deadline, ID};
+use cascade_protocol_deadline_codec::{DeadlineInstruction, Envelope, Expression};
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::{pubkey, Pubkey};
+
+pub use cascade_protocol_deadline_codec::{Constraints, Op};
+
+/// Clock sysvar address
+pub const CLOCK_SYSVAR_ID: Pubkey = pubkey!("SysvarC1ock11111111111111111111111111111111");
+
+/// Instructions sysvar address
+pub const INSTRUCTIONS_SYSVAR_ID: Pubkey = pubkey!("Sysvar1nstructions1111111111111111111111111");
+
+/// System program address
+pub const SYSTEM_PROGRAM_ID: Pubkey = pubkey!("11111111111111111111111111111111");
+
+/// Seed prefix for cooldown PDAs
+pub const COOLDOWN_SEED: &[u8] = b"cooldown";
+
+/// Seed prefix for session PDAs
+pub const SESSION_SEED: &[u8] = b"session";
+
+/// Size of a weekly schedule bitmap, one bit per hour of the week
+pub const SCHEDULE_LEN: usize = 21;
+
+/// Returns the cooldown address for an authority and seed
+pub fn cooldown_address(authority: &Pubkey, seed: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[COOLDOWN_SEED, authority.as_ref(), &seed.to_le_bytes()],
+        &ID,
+    )
+    .0
+}
+
+/// Returns the session address for an owner and seed
+pub fn session_address(owner: &Pubkey, seed: u64) -> Pubkey {
+    Pubkey::find_program_address(&[SESSION_SEED, owner.as_ref(), &seed.to_le_bytes()], &ID).0
+}
+
+/// Packs instruction data into a buffer of exactly its encoded length
+fn pack(envelope: Envelope) -> Vec<u8> {
+    let mut data = vec![0; envelope.packed_len()];
+    envelope.pack_into(&mut data);
+    data
+}
+
+/// Creates an instruction with the packed data and no constraints
+fn build(instruction: DeadlineInstruction, accounts: Vec<AccountMeta>) -> Instruction {
+    Instruction::new_with_bytes(ID, &pack(instruction.into()), accounts)
+}
+
+/// Creates a `ValidateDeadline` instruction
+///
+/// Uses the shortest of the three encodings: 5 bytes for current
+/// deadlines, 2 bytes for `Deadline::NEVER`.
+pub fn validate_deadline(deadline: impl Into<Deadline>) -> Instruction {
+    let deadline = deadline.into().unix_timestamp();
+    build(DeadlineInstruction::ValidateDeadline { deadline }, vec![])
+}
+
+/// Creates a `ValidateOracleClock` instruction
+///
+/// `price_update` must hold the `feed_id` price feed, published no earlier
+/// than `min_publish_time`.
+pub fn validate_oracle_clock(
+    price_update: &Pubkey,
+    max_drift: u64,
+    max_staleness_slots: u64,
+    feed_id: &[u8; 32],
+    min_publish_time: i64,
+) -> Instruction {
+    build(
+        DeadlineInstruction::ValidateOracleClock {
+            max_drift,
+            max_staleness_slots,
+            feed_id: *feed_id,
+            min_publish_time,
+        },
+        vec![AccountMeta::new_readonly(*price_update, false)],
+    )
+}
+
+/// Creates a `ValidateClockSanity` instruction
+pub fn validate_clock_sanity(max_drift: u64) -> Instruction {
+    build(
+        DeadlineInstruction::ValidateClockSanity { max_drift },
+        vec![],
+    )
+}
+
+/// Creates a `ValidateDutchAuction` instruction
+///
+/// Only `TransferChecked` instructions of `mint` to the `destination` token
+/// account count towards the price.
+pub fn validate_dutch_auction(
+    mint: &Pubkey,
+    destination: &Pubkey,
+    start_price: u64,
+    floor_price: u64,
+    start_time: i64,
+    end_time: i64,
+) -> Instruction {
+    build(
+        DeadlineInstruction::ValidateDutchAuction {
+            start_price,
+            floor_price,
+            start_time,
+            end_time,
+            mint: mint.to_bytes(),
+            destination: destination.to_bytes(),
+        },
+        vec![AccountMeta::new_readonly(INSTRUCTIONS_SYSVAR_ID, false)],
+    )
+}
+
+/// Creates a `ValidateSchedule` instruction
+///
+/// `schedule` has one bit per UTC hour of the week, starting Monday 00:00,
+/// least significant bit first.
+pub fn validate_schedule(schedule: &[u8; SCHEDULE_LEN]) -> Instruction {
+    let schedule = *schedule;
+    build(DeadlineInstruction::ValidateSchedule { schedule }, vec![])
+}
+
+/// Creates a `ValidateExpression` instruction
+///
+/// The program rejects expressions over 32 ops, deeper than 8 stack
+/// entries, or not leaving exactly one value on the stack.
+pub fn validate_expression(ops: &[Op]) -> Instruction {
+    let ops = Expression::from_ops(ops);
+    build(DeadlineInstruction::ValidateExpression { ops }, vec![])
+}
+
+/// Creates an `InitializeCooldown` instruction
+pub fn initialize_cooldown(
+    payer: &Pubkey,
+    authority: &Pubkey,
+    min_interval: u64,
+    seed: u64,
+) -> Instruction {
+    build(
+        DeadlineInstruction::InitializeCooldown { min_interval, seed },
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(cooldown_address(authority, seed), false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+    )
+}
+
+/// Creates a `CheckAndBumpCooldown` instruction
+pub fn check_and_bump_cooldown(cooldown: &Pubkey, authority: &Pubkey) -> Instruction {
+    build(
+        DeadlineInstruction::CheckAndBumpCooldown,
+        vec![
+            AccountMeta::new(*cooldown, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Creates a `CreateSession` instruction
+pub fn create_session(
+    payer: &Pubkey,
+    owner: &Pubkey,
+    delegate: &Pubkey,
+    expires_at: impl Into<Deadline>,
+    spend_cap: Option<u64>,
+    seed: u64,
+) -> Instruction {
+    build(
+        DeadlineInstruction::CreateSession {
+            delegate: delegate.to_bytes(),
+            expires_at: expires_at.into().unix_timestamp(),
+            spend_cap,
+            seed,
+        },
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new(session_address(owner, seed), false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+    )
+}
+
+/// Creates a `RevokeSession` instruction
+pub fn revoke_session(session: &Pubkey, owner: &Pubkey) -> Instruction {
+    build(
+        DeadlineInstruction::RevokeSession,
+        vec![
+            AccountMeta::new(*session, false),
+            AccountMeta::new_readonly(*owner, true),
+        ],
+    )
+}
+
+/// Creates a `ValidateSession` instruction
+///
+/// `amount` must be the total of the `TransferChecked` instructions the
+/// delegate signs in the same transaction.
+pub fn validate_session(session: &Pubkey, delegate: &Pubkey, amount: u64) -> Instruction {
+    build(
+        DeadlineInstruction::ValidateSession { amount },
+        vec![
+            AccountMeta::new(*session, false),
+            AccountMeta::new_readonly(*delegate, true),
+            AccountMeta::new_readonly(INSTRUCTIONS_SYSVAR_ID, false),
+        ],
+    )
+}
+
+/// Wraps an instruction built by this module in a versioned envelope
+///
+/// Returns the instruction unchanged when no constraint is set, like
+/// `DeadlineInstruction::pack`. The constraints replace those of an
+/// instruction that is already versioned.
+///
+/// Panics if the instruction data is not a valid instruction of the
+/// program, which the program would reject anyway.
+pub fn versioned(mut instruction: Instruction, constraints: &Constraints) -> Instruction {
+    if constraints.is_empty() {
+        return instruction;
+    }
+    let envelope = Envelope::unpack(&instruction.data).expect("valid instruction data");
+    instruction.data = pack(Envelope {
+        instruction: envelope.instruction,
+        constraints: *constraints,
+    });
+    instruction
+}
+
+/// Passes the Clock sysvar as an account, for SVM forks without the clock
+/// syscall
+///
+/// Every instruction, plain or versioned, reads the clock from this
+/// account when it is passed. `ValidateClockSanity` also reads the
+/// EpochSchedule sysvar, which can be appended the same way.
+pub fn with_clock_sysvar(mut instruction: Instruction) -> Instruction {
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(CLOCK_SYSVAR_ID, false));
+    instruction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cascade_protocol_deadline_validator::{
+        expression, id,
+        instruction::{
+            self as program, DeadlineInstruction, InnerInstruction, COMPACT_DEADLINE_BASE,
+        },
+        schedule::WeeklySchedule,
+        solana_program::sysvar,
+        state,
+    };
+
+    #[test]
+    fn test_program_id() {
+        assert_eq!(ID, id());
+    }
+
+    #[test]
+    fn test_addresses_match_program() {
+        assert_eq!(CLOCK_SYSVAR_ID, sysvar::clock::ID);
+        assert_eq!(INSTRUCTIONS_SYSVAR_ID, sysvar::instructions::ID);
+        assert_eq!(COOLDOWN_SEED, state::COOLDOWN_SEED);
+        assert_eq!(SESSION_SEED, state::SESSION_SEED);
+        assert_eq!(SCHEDULE_LEN, WeeklySchedule::LEN);
+    }
+
+    #[test]
+    fn test_validate_deadline_matches_pack() {
+        for deadline in [
+            0,
+            -1,
+            1700000000,
+            COMPACT_DEADLINE_BASE,
+            COMPACT_DEADLINE_BASE + u32::MAX as i64,
+            COMPACT_DEADLINE_BASE + u32::MAX as i64 + 1,
+            i64::MIN,
+            i64::MAX,
+        ] {
+            assert_eq!(
+                validate_deadline(deadline).data,
+                DeadlineInstruction::ValidateDeadline { deadline }.pack(),
+                "{deadline}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_builders_match_pack() {
+        let oracle = Pubkey::new_from_array([1; 32]);
+        let mint = Pubkey::new_from_array([4; 32]);
+        let destination = Pubkey::new_from_array([5; 32]);
+        let cases = [
+            (
+                validate_oracle_clock(&oracle, 30, 25, &[3; 32], 1700000000),
+                DeadlineInstruction::ValidateOracleClock {
+                    max_drift: 30,
+                    max_staleness_slots: 25,
+                    feed_id: [3; 32],
+                    min_publish_time: 1700000000,
+                },
+            ),
+            (
+                validate_clock_sanity(60),
+                DeadlineInstruction::ValidateClockSanity { max_drift: 60 },
+            ),
+            (
+                validate_dutch_auction(&mint, &destination, 10, 1, 1700000000, 1700000900),
+                DeadlineInstruction::ValidateDutchAuction {
+                    start_price: 10,
+                    floor_price: 1,
+                    start_time: 1700000000,
+                    end_time: 1700000900,
+                    mint,
+                    destination,
+                },
+            ),
+            (
+                validate_schedule(&[0xaa; SCHEDULE_LEN]),
+                DeadlineInstruction::ValidateSchedule {
+                    schedule: WeeklySchedule([0xaa; SCHEDULE_LEN]),
+                },
+            ),
+            (
+                validate_expression(&[
+                    Op::TimeLe(1),
+                    Op::TimeGe(-2),
+                    Op::SlotLe(3),
+                    Op::SlotGe(4),
+                    Op::EpochLe(5),
+                    Op::EpochGe(6),
+                    Op::And,
+                    Op::Or,
+                    Op::Not,
+                ]),
+                DeadlineInstruction::ValidateExpression {
+                    ops: vec![
+                        expression::Op::TimeLe(1),
+                        expression::Op::TimeGe(-2),
+                        expression::Op::SlotLe(3),
+                        expression::Op::SlotGe(4),
+                        expression::Op::EpochLe(5),
+                        expression::Op::EpochGe(6),
+                        expression::Op::And,
+                        expression::Op::Or,
+                        expression::Op::Not,
+                    ],
+                },
+            ),
+        ];
+        for (instruction, expected) in cases {
+            assert_eq!(instruction.program_id, ID);
+            assert_eq!(instruction.data, expected.pack(), "{expected:?}");
+        }
+        assert_eq!(
+            validate_oracle_clock(&oracle, 30, 25, &[3; 32], 1700000000).accounts,
+            vec![AccountMeta::new_readonly(oracle, false)]
+        );
+        assert_eq!(
+            validate_dutch_auction(&mint, &destination, 10, 1, 0, 1).accounts,
+            vec![AccountMeta::new_readonly(INSTRUCTIONS_SYSVAR_ID, false)]
+        );
+    }
+
+    #[test]
+    fn test_account_builders_match_program() {
+        let payer = Pubkey::new_from_array([1; 32]);
+        let owner = Pubkey::new_from_array([2; 32]);
+        let delegate = Pubkey::new_from_array([3; 32]);
+        let account = Pubkey::new_from_array([4; 32]);
+
+        assert_eq!(
+            initialize_cooldown(&payer, &owner, 86_400, 7),
+            program::initialize_cooldown(&ID, &payer, &owner, 86_400, 7)
+        );
+        assert_eq!(
+            check_and_bump_cooldown(&account, &owner),
+            program::check_and_bump_cooldown(&ID, &account, &owner)
+        );
+        for spend_cap in [None, Some(5)] {
+            assert_eq!(
+                create_session(&payer, &owner, &delegate, 1800000000, spend_cap, 7),
+                program::create_session(&ID, &payer, &owner, &delegate, 1800000000, spend_cap, 7)
+            );
+        }
+        assert_eq!(
+            revoke_session(&account, &owner),
+            program::revoke_session(&ID, &account, &owner)
+        );
+        assert_eq!(
+            validate_session(&account, &delegate, 11),
+            program::validate_session(&ID, &account, &delegate, 11)
+        );
+    }
+
+    #[test]
+    fn test_versioned_matches_pack() {
+        let constraints = Constraints {
+            not_before: Some(-5),
+            min_slot: None,
+            max_slot: Some(1000),
+        };
+        let instruction = versioned(validate_deadline(1700000000), &constraints);
+        let expected = DeadlineInstruction::Versioned {
+            instruction: InnerInstruction::try_from(DeadlineInstruction::ValidateDeadline {
+                deadline: 1700000000,
+            })
+            .unwrap(),
+            constraints: program::Constraints {
+                not_before: Some(-5),
+                min_slot: None,
+                max_slot: Some(1000),
+            },
+        };
+        assert_eq!(instruction.data, expected.pack());
+        assert_eq!(
+            DeadlineInstruction::unpack(&instruction.data).unwrap(),
+            expected
+        );
+
+        let plain = validate_deadline(0);
+        assert_eq!(versioned(plain.clone(), &Constraints::default()), plain);
+    }
+
+    #[test]
+    fn test_with_clock_sysvar() {
+        let instruction = with_clock_sysvar(validate_deadline(0));
+        assert_eq!(
+            instruction.accounts,
+            vec![AccountMeta::new_readonly(CLOCK_SYSVAR_ID, false)]
+        );
+    }
+}
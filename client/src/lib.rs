@@ -0,0 +1,41 @@
+//! Client for the deadline validator program
+//!
+//! Typed builders for every instruction of the program, depending only on
+//! the Solana SDK crates for instructions and public keys and on the
+//! instruction codec. Add the returned instructions to a transaction next to
+//! the instructions they guard.
+//!
+//! Optional features add the rest, with their dependencies:
+//!
+//! - `scan`: `scan_message` reads the time constraints of a transaction
+//! - `parse`: `parse_instruction` renders an instruction as JSON
+//! - `error`: `program_failure` tells whether a failed transaction was
+//!   rejected by the program
+//!
+//! ```
+//! use cascade_protocol_deadline_validator_client::{deadline_in, instruction};
+//! use std::time::Duration;
+//!
+//! // Fails the transaction if it lands more than 5 minutes from now
+//! let ix = instruction::validate_deadline(deadline_in(Duration::from_secs(300)));
+//! ```
+
+pub mod deadline;
+#[cfg(feature = "error")]
+pub mod error;
+pub mod instruction;
+#[cfg(feature = "parse")]
+pub mod parse;
+#[cfg(feature = "scan")]
+pub mod scan;
+
+pub use deadline::{deadline_in, Deadline};
+#[cfg(feature = "error")]
+pub use error::{program_failure, DeadlineError, ProgramFailure};
+#[cfg(feature = "parse")]
+pub use parse::{parse_instruction, ParseError, ParsedInstruction, PARSED_VERSION};
+#[cfg(feature = "scan")]
+pub use scan::{scan_message, ScanError, TimeConstraints};
+
+// Program ID - vanity address starting with "DEAD" (deadline validator)
+solana_pubkey::declare_id!("DEADaT1auZ8JjUMWUhhPWjQqFk9HSgHBkt5KaGMVnp1H");
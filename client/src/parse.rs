@@ -0,0 +1,683 @@
+//! Explorer-style JSON for instructions of the program
+//!
+//! [`parse_instruction`] turns an instruction into the `{ type, info }`
+//! shape block explorers use for parsed instructions, e.g.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "type": "validateDeadline",
+//!   "info": { "deadline": 1800000000, "deadlineIso": "2027-01-15T08:00:00Z" }
+//! }
+//! ```
+//!
+//! Field names are camelCase. Accounts appear in `info` by role, and
+//! timestamps come with an `...Iso` companion in RFC 3339, `null` for years
+//! outside 0000–9999 and for a deadline or session expiry of 0 ("never
+//! expires"). Oracle feed IDs are `0x`-prefixed hex, as Pyth publishes
+//! them, and schedules list the allowed UTC hour ranges of each day, e.g.
+//! `{ "monday": ["08:00-20:00"], ..., "sunday": [] }`. A versioned envelope
+//! adds `constraints`. The layout is stable within a [`PARSED_VERSION`];
+//! fields may be added, but renaming or removing one bumps the version.
+
+use crate::instruction::{CLOCK_SYSVAR_ID, SCHEDULE_LEN};
+use cascade_protocol_deadline_codec::{Constraints, DeadlineInstruction, Envelope, Op};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use solana_pubkey::Pubkey;
+use std::fmt;
+
+/// Version of the JSON layout
+pub const PARSED_VERSION: u8 = 1;
+
+/// A parsed instruction
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ParsedInstruction {
+    /// Version of the layout, [`PARSED_VERSION`] for this crate
+    pub version: u8,
+    /// camelCase instruction name, e.g. `validateDeadline`
+    #[serde(rename = "type")]
+    pub instruction_type: String,
+    /// Instruction fields and accounts
+    pub info: Value,
+    /// Constraints of a versioned envelope, if any is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub constraints: Option<Value>,
+}
+
+/// Errors that may be returned when parsing an instruction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The data is not an instruction the program accepts
+    InvalidInstructionData,
+    /// The instruction has fewer accounts than it requires
+    NotEnoughAccounts,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::InvalidInstructionData => "Invalid instruction data",
+            Self::NotEnoughAccounts => "Not enough accounts",
+        })
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses an instruction of the program
+///
+/// `accounts` are the instruction's account keys, in order. Accepts every
+/// encoding the program accepts, including the compact deadline forms,
+/// Anchor aliases and the versioned envelope.
+pub fn parse_instruction(
+    data: &[u8],
+    accounts: &[Pubkey],
+) -> Result<ParsedInstruction, ParseError> {
+    let envelope = Envelope::unpack(data).map_err(|_| ParseError::InvalidInstructionData)?;
+    let (instruction_type, info) = parse_info(&envelope.instruction, accounts)?;
+    Ok(ParsedInstruction {
+        version: PARSED_VERSION,
+        instruction_type: instruction_type.to_string(),
+        info,
+        constraints: (!envelope.constraints.is_empty())
+            .then(|| parse_constraints(&envelope.constraints)),
+    })
+}
+
+/// Returns the instruction name and its fields and accounts
+fn parse_info(
+    instruction: &DeadlineInstruction,
+    accounts: &[Pubkey],
+) -> Result<(&'static str, Value), ParseError> {
+    let parsed = match *instruction {
+        DeadlineInstruction::ValidateDeadline { deadline } => {
+            let mut info = json!({
+                "deadline": deadline,
+                "deadlineIso": expiry_iso8601(deadline),
+            });
+            add_clock_sysvar(&mut info, accounts);
+            ("validateDeadline", info)
+        }
+        DeadlineInstruction::ValidateOracleClock {
+            max_drift,
+            max_staleness_slots,
+            feed_id,
+            min_publish_time,
+        } => {
+            check_num_accounts(accounts, 1)?;
+            (
+                "validateOracleClock",
+                json!({
+                    "priceUpdate": accounts[0].to_string(),
+                    "maxDrift": max_drift,
+                    "maxStalenessSlots": max_staleness_slots,
+                    "feedId": hex(&feed_id),
+                    "minPublishTime": min_publish_time,
+                }),
+            )
+        }
+        DeadlineInstruction::ValidateClockSanity { max_drift } => {
+            ("validateClockSanity", json!({ "maxDrift": max_drift }))
+        }
+        DeadlineInstruction::ValidateDutchAuction {
+            start_price,
+            floor_price,
+            start_time,
+            end_time,
+            mint,
+            destination,
+        } => {
+            check_num_accounts(accounts, 1)?;
+            (
+                "validateDutchAuction",
+                json!({
+                    "instructionsSysvar": accounts[0].to_string(),
+                    "startPrice": start_price,
+                    "floorPrice": floor_price,
+                    "startTime": start_time,
+                    "startTimeIso": iso8601(start_time),
+                    "endTime": end_time,
+                    "endTimeIso": iso8601(end_time),
+                    "mint": Pubkey::new_from_array(mint).to_string(),
+                    "destination": Pubkey::new_from_array(destination).to_string(),
+                }),
+            )
+        }
+        DeadlineInstruction::ValidateSchedule { schedule } => {
+            let mut info = json!({ "schedule": parse_schedule(&schedule) });
+            add_clock_sysvar(&mut info, accounts);
+            ("validateSchedule", info)
+        }
+        DeadlineInstruction::ValidateExpression { ops } => {
+            let mut info = json!({ "ops": ops.ops().map(parse_op).collect::<Vec<_>>() });
+            add_clock_sysvar(&mut info, accounts);
+            ("validateExpression", info)
+        }
+        DeadlineInstruction::InitializeCooldown { min_interval, seed } => {
+            check_num_accounts(accounts, 4)?;
+            (
+                "initializeCooldown",
+                json!({
+                    "payer": accounts[0].to_string(),
+                    "authority": accounts[1].to_string(),
+                    "cooldown": accounts[2].to_string(),
+                    "systemProgram": accounts[3].to_string(),
+                    "minInterval": min_interval,
+                    "seed": seed,
+                }),
+            )
+        }
+        DeadlineInstruction::CheckAndBumpCooldown => {
+            check_num_accounts(accounts, 2)?;
+            (
+                "checkAndBumpCooldown",
+                json!({
+                    "cooldown": accounts[0].to_string(),
+                    "authority": accounts[1].to_string(),
+                }),
+            )
+        }
+        DeadlineInstruction::CreateSession {
+            delegate,
+            expires_at,
+            spend_cap,
+            seed,
+        } => {
+            check_num_accounts(accounts, 4)?;
+            (
+                "createSession",
+                json!({
+                    "payer": accounts[0].to_string(),
+                    "owner": accounts[1].to_string(),
+                    "session": accounts[2].to_string(),
+                    "systemProgram": accounts[3].to_string(),
+                    "delegate": Pubkey::new_from_array(delegate).to_string(),
+                    "expiresAt": expires_at,
+                    "expiresAtIso": expiry_iso8601(expires_at),
+                    "spendCap": spend_cap,
+                    "seed": seed,
+                }),
+            )
+        }
+        DeadlineInstruction::RevokeSession => {
+            check_num_accounts(accounts, 2)?;
+            (
+                "revokeSession",
+                json!({
+                    "session": accounts[0].to_string(),
+                    "owner": accounts[1].to_string(),
+                }),
+            )
+        }
+        DeadlineInstruction::ValidateSession { amount } => {
+            check_num_accounts(accounts, 3)?;
+            (
+                "validateSession",
+                json!({
+                    "session": accounts[0].to_string(),
+                    "delegate": accounts[1].to_string(),
+                    "instructionsSysvar": accounts[2].to_string(),
+                    "amount": amount,
+                }),
+            )
+        }
+    };
+    Ok(parsed)
+}
+
+fn check_num_accounts(accounts: &[Pubkey], num: usize) -> Result<(), ParseError> {
+    if accounts.len() < num {
+        return Err(ParseError::NotEnoughAccounts);
+    }
+    Ok(())
+}
+
+/// Adds the optional Clock sysvar account of clock-only instructions
+///
+/// The program finds the Clock sysvar at any position and ignores other
+/// accounts, so only the sysvar itself is shown.
+fn add_clock_sysvar(info: &mut Value, accounts: &[Pubkey]) {
+    let clock = accounts.iter().find(|account| **account == CLOCK_SYSVAR_ID);
+    if let (Some(clock), Value::Object(info)) = (clock, info) {
+        info.insert("clockSysvar".to_string(), clock.to_string().into());
+    }
+}
+
+/// Formats bytes as `0x`-prefixed lowercase hex
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold("0x".to_string(), |mut hex, byte| {
+        hex.push_str(&format!("{byte:02x}"));
+        hex
+    })
+}
+
+/// Days of the week in schedule order, which starts on Monday
+const WEEKDAYS: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+/// Returns the allowed hours of each day as `"HH:00-HH:00"` ranges in UTC,
+/// end exclusive
+fn parse_schedule(schedule: &[u8; SCHEDULE_LEN]) -> Value {
+    let allowed = |hour: usize| schedule[hour / 8] & (1 << (hour % 8)) != 0;
+    let mut days = Map::new();
+    for (day, name) in WEEKDAYS.into_iter().enumerate() {
+        let mut ranges = vec![];
+        let mut start = None;
+        for hour in 0..=24 {
+            match (hour < 24 && allowed(day * 24 + hour), start) {
+                (true, None) => start = Some(hour),
+                (false, Some(first)) => {
+                    ranges.push(format!("{first:02}:00-{hour:02}:00"));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        days.insert(name.to_string(), ranges.into());
+    }
+    days.into()
+}
+
+/// Returns `{ op, operand }`, without `operand` for logical ops
+fn parse_op(op: Op) -> Value {
+    let (name, operand) = match op {
+        Op::TimeLe(t) => ("timeLe", Some(json!(t))),
+        Op::TimeGe(t) => ("timeGe", Some(json!(t))),
+        Op::SlotLe(s) => ("slotLe", Some(json!(s))),
+        Op::SlotGe(s) => ("slotGe", Some(json!(s))),
+        Op::EpochLe(e) => ("epochLe", Some(json!(e))),
+        Op::EpochGe(e) => ("epochGe", Some(json!(e))),
+        Op::And => ("and", None),
+        Op::Or => ("or", None),
+        Op::Not => ("not", None),
+    };
+    let mut value = Map::new();
+    value.insert("op".to_string(), name.into());
+    if let Some(operand) = operand {
+        value.insert("operand".to_string(), operand);
+    }
+    value.into()
+}
+
+/// Returns the set constraints as `{ notBefore?, notBeforeIso?, minSlot?, maxSlot? }`
+fn parse_constraints(constraints: &Constraints) -> Value {
+    let mut value = Map::new();
+    if let Some(not_before) = constraints.not_before {
+        value.insert("notBefore".to_string(), not_before.into());
+        value.insert("notBeforeIso".to_string(), iso8601(not_before).into());
+    }
+    if let Some(min_slot) = constraints.min_slot {
+        value.insert("minSlot".to_string(), min_slot.into());
+    }
+    if let Some(max_slot) = constraints.max_slot {
+        value.insert("maxSlot".to_string(), max_slot.into());
+    }
+    value.into()
+}
+
+/// Formats a deadline or session expiry like [`iso8601`], returning
+/// `None` for 0, which the program treats as "never expires"
+fn expiry_iso8601(timestamp: i64) -> Option<String> {
+    (timestamp != 0).then(|| iso8601(timestamp)).flatten()
+}
+
+/// Formats a unix timestamp as RFC 3339 in UTC
+///
+/// Returns `None` for years outside 0000–9999, which RFC 3339 cannot
+/// represent.
+fn iso8601(timestamp: i64) -> Option<String> {
+    let days = timestamp.div_euclid(86_400);
+    let seconds = timestamp.rem_euclid(86_400);
+
+    // Days to proleptic Gregorian date, after Howard Hinnant's
+    // `civil_from_days`, with eras of 400 years starting on March 1st
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = era * 400 + year_of_era + i64::from(month <= 2);
+
+    (0..=9999).contains(&year).then(|| {
+        format!(
+            "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+            seconds / 3_600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction::{self, versioned};
+    use solana_instruction::Instruction;
+
+    fn parse(instruction: &Instruction) -> Result<ParsedInstruction, ParseError> {
+        let accounts: Vec<_> = instruction
+            .accounts
+            .iter()
+            .map(|meta| meta.pubkey)
+            .collect();
+        parse_instruction(&instruction.data, &accounts)
+    }
+
+    #[test]
+    fn test_iso8601() {
+        assert_eq!(iso8601(0).as_deref(), Some("1970-01-01T00:00:00Z"));
+        assert_eq!(expiry_iso8601(0), None);
+        assert_eq!(expiry_iso8601(1).as_deref(), Some("1970-01-01T00:00:01Z"));
+        assert_eq!(iso8601(1).as_deref(), Some("1970-01-01T00:00:01Z"));
+        assert_eq!(iso8601(-1).as_deref(), Some("1969-12-31T23:59:59Z"));
+        assert_eq!(
+            iso8601(951_782_400).as_deref(),
+            Some("2000-02-29T00:00:00Z")
+        );
+        assert_eq!(
+            iso8601(1_800_000_000).as_deref(),
+            Some("2027-01-15T08:00:00Z")
+        );
+        assert_eq!(
+            iso8601(253_402_300_799).as_deref(),
+            Some("9999-12-31T23:59:59Z")
+        );
+        assert_eq!(iso8601(253_402_300_800), None);
+        assert_eq!(
+            iso8601(-62_167_219_200).as_deref(),
+            Some("0000-01-01T00:00:00Z")
+        );
+        assert_eq!(iso8601(-62_167_219_201), None);
+        assert_eq!(iso8601(i64::MIN), None);
+        assert_eq!(iso8601(i64::MAX), None);
+    }
+
+    #[test]
+    fn test_parse_validate_deadline() {
+        let parsed = parse(&instruction::validate_deadline(1800000000)).unwrap();
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap(),
+            json!({
+                "version": 1,
+                "type": "validateDeadline",
+                "info": { "deadline": 1800000000, "deadlineIso": "2027-01-15T08:00:00Z" },
+            })
+        );
+
+        let parsed = parse(&instruction::with_clock_sysvar(
+            instruction::validate_deadline(0),
+        ));
+        assert_eq!(
+            parsed.unwrap().info,
+            json!({
+                "deadline": 0,
+                "deadlineIso": null,
+                "clockSysvar": instruction::CLOCK_SYSVAR_ID.to_string(),
+            })
+        );
+
+        // Other accounts are ignored, before or instead of the clock
+        let other = Pubkey::new_unique();
+        let data = instruction::validate_deadline(0).data;
+        let parsed = parse_instruction(&data, &[other]).unwrap();
+        assert_eq!(parsed.info.get("clockSysvar"), None);
+        let parsed = parse_instruction(&data, &[other, instruction::CLOCK_SYSVAR_ID]).unwrap();
+        assert_eq!(
+            parsed.info["clockSysvar"],
+            instruction::CLOCK_SYSVAR_ID.to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_every_instruction() {
+        let payer = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let delegate = Pubkey::new_unique();
+        let oracle = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let destination = Pubkey::new_unique();
+        let cooldown = instruction::cooldown_address(&owner, 3);
+        let session = instruction::session_address(&owner, 4);
+        let system_program = instruction::SYSTEM_PROGRAM_ID.to_string();
+
+        let cases = [
+            (
+                instruction::validate_oracle_clock(&oracle, 30, 25, &[3; 32], 1700000000),
+                json!({
+                    "type": "validateOracleClock",
+                    "info": {
+                        "priceUpdate": oracle.to_string(),
+                        "maxDrift": 30,
+                        "maxStalenessSlots": 25,
+                        "feedId": format!("0x{}", "03".repeat(32)),
+                        "minPublishTime": 1700000000,
+                    },
+                }),
+            ),
+            (
+                instruction::validate_clock_sanity(30),
+                json!({ "type": "validateClockSanity", "info": { "maxDrift": 30 } }),
+            ),
+            (
+                instruction::validate_dutch_auction(
+                    &mint,
+                    &destination,
+                    1_000,
+                    100,
+                    1800000000,
+                    1800003600,
+                ),
+                json!({
+                    "type": "validateDutchAuction",
+                    "info": {
+                        "instructionsSysvar": instruction::INSTRUCTIONS_SYSVAR_ID.to_string(),
+                        "startPrice": 1_000,
+                        "floorPrice": 100,
+                        "startTime": 1800000000,
+                        "startTimeIso": "2027-01-15T08:00:00Z",
+                        "endTime": 1800003600,
+                        "endTimeIso": "2027-01-15T09:00:00Z",
+                        "mint": mint.to_string(),
+                        "destination": destination.to_string(),
+                    },
+                }),
+            ),
+            (
+                instruction::validate_schedule(&[0xFF; 21]),
+                json!({
+                    "type": "validateSchedule",
+                    "info": {
+                        "schedule": {
+                            "monday": ["00:00-24:00"],
+                            "tuesday": ["00:00-24:00"],
+                            "wednesday": ["00:00-24:00"],
+                            "thursday": ["00:00-24:00"],
+                            "friday": ["00:00-24:00"],
+                            "saturday": ["00:00-24:00"],
+                            "sunday": ["00:00-24:00"],
+                        },
+                    },
+                }),
+            ),
+            (
+                instruction::validate_expression(&[Op::TimeLe(5), Op::SlotGe(7), Op::And]),
+                json!({
+                    "type": "validateExpression",
+                    "info": {
+                        "ops": [
+                            { "op": "timeLe", "operand": 5 },
+                            { "op": "slotGe", "operand": 7 },
+                            { "op": "and" },
+                        ],
+                    },
+                }),
+            ),
+            (
+                instruction::initialize_cooldown(&payer, &owner, 60, 3),
+                json!({
+                    "type": "initializeCooldown",
+                    "info": {
+                        "payer": payer.to_string(),
+                        "authority": owner.to_string(),
+                        "cooldown": cooldown.to_string(),
+                        "systemProgram": system_program,
+                        "minInterval": 60,
+                        "seed": 3,
+                    },
+                }),
+            ),
+            (
+                instruction::check_and_bump_cooldown(&cooldown, &owner),
+                json!({
+                    "type": "checkAndBumpCooldown",
+                    "info": { "cooldown": cooldown.to_string(), "authority": owner.to_string() },
+                }),
+            ),
+            (
+                instruction::create_session(&payer, &owner, &delegate, 1800000000, None, 4),
+                json!({
+                    "type": "createSession",
+                    "info": {
+                        "payer": payer.to_string(),
+                        "owner": owner.to_string(),
+                        "session": session.to_string(),
+                        "systemProgram": system_program,
+                        "delegate": delegate.to_string(),
+                        "expiresAt": 1800000000,
+                        "expiresAtIso": "2027-01-15T08:00:00Z",
+                        "spendCap": null,
+                        "seed": 4,
+                    },
+                }),
+            ),
+            (
+                instruction::revoke_session(&session, &owner),
+                json!({
+                    "type": "revokeSession",
+                    "info": { "session": session.to_string(), "owner": owner.to_string() },
+                }),
+            ),
+            (
+                instruction::validate_session(&session, &delegate, 500),
+                json!({
+                    "type": "validateSession",
+                    "info": {
+                        "session": session.to_string(),
+                        "delegate": delegate.to_string(),
+                        "instructionsSysvar": instruction::INSTRUCTIONS_SYSVAR_ID.to_string(),
+                        "amount": 500,
+                    },
+                }),
+            ),
+        ];
+        for (instruction, mut expected) in cases {
+            expected["version"] = json!(PARSED_VERSION);
+            let parsed = parse(&instruction).unwrap();
+            assert_eq!(serde_json::to_value(&parsed).unwrap(), expected);
+
+            let round_trip: ParsedInstruction = serde_json::from_value(expected).unwrap();
+            assert_eq!(round_trip, parsed);
+        }
+    }
+
+    #[test]
+    fn test_parse_schedule() {
+        // Weekdays 08:00-20:00, Tuesday 12:00-13:00 blocked, Sunday 23:00
+        let mut schedule = [0; SCHEDULE_LEN];
+        let mut allow = |hour: usize| schedule[hour / 8] |= 1 << (hour % 8);
+        for day in 0..5 {
+            (8..20)
+                .filter(|&hour| day != 1 || hour != 12)
+                .for_each(|hour| allow(day * 24 + hour));
+        }
+        allow(6 * 24 + 23);
+
+        assert_eq!(
+            parse_schedule(&schedule),
+            json!({
+                "monday": ["08:00-20:00"],
+                "tuesday": ["08:00-12:00", "13:00-20:00"],
+                "wednesday": ["08:00-20:00"],
+                "thursday": ["08:00-20:00"],
+                "friday": ["08:00-20:00"],
+                "saturday": [],
+                "sunday": ["23:00-24:00"],
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_versioned() {
+        let instruction = versioned(
+            instruction::validate_clock_sanity(30),
+            &Constraints {
+                not_before: Some(1800000000),
+                min_slot: None,
+                max_slot: Some(1_000),
+            },
+        );
+        assert_eq!(
+            serde_json::to_value(parse(&instruction).unwrap()).unwrap(),
+            json!({
+                "version": 1,
+                "type": "validateClockSanity",
+                "info": { "maxDrift": 30 },
+                "constraints": {
+                    "notBefore": 1800000000,
+                    "notBeforeIso": "2027-01-15T08:00:00Z",
+                    "maxSlot": 1_000,
+                },
+            })
+        );
+
+        // 0 is a plain timestamp outside deadlines
+        let instruction = versioned(
+            instruction::validate_clock_sanity(30),
+            &Constraints {
+                not_before: Some(0),
+                ..Constraints::default()
+            },
+        );
+        assert_eq!(
+            parse(&instruction).unwrap().constraints,
+            Some(json!({ "notBefore": 0, "notBeforeIso": "1970-01-01T00:00:00Z" }))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_instruction(&[], &[]),
+            Err(ParseError::InvalidInstructionData)
+        );
+        assert_eq!(
+            parse_instruction(&[0, 1, 2], &[]),
+            Err(ParseError::InvalidInstructionData)
+        );
+
+        let mut instruction =
+            instruction::revoke_session(&Pubkey::new_unique(), &Pubkey::new_unique());
+        instruction.accounts.pop();
+        assert_eq!(parse(&instruction), Err(ParseError::NotEnoughAccounts));
+        assert_eq!(
+            ParseError::NotEnoughAccounts.to_string(),
+            "Not enough accounts"
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Time constraints carried by a transaction
+//!
+//! [`scan_message`] finds every top-level instruction of the deadline
+//! validator program in a legacy or v0 message, decodes it with the
+//! program's codec and combines the time constraints into one
+//! [`TimeConstraints`]. Facilitators can use it to decide how long a
+//! signed transaction stays valid, and wallets to show it.
+//!
+//! Only deadlines and envelope bounds are combined. Schedules,
+//! expressions, auction times, cooldowns and session expiries also depend
+//! on the current time, but do not reduce to one window; such
+//! instructions are listed in [`TimeConstraints::uncombined`], and the
+//! transaction may fail inside the combined window when there are any.
+
+use crate::ID;
+use cascade_protocol_deadline_codec::{DeadlineInstruction, Envelope};
+use solana_message::{v0::LoadedAddresses, AccountKeys, VersionedMessage};
+use std::fmt;
+
+/// Combined time constraints of all instructions of the program
+///
+/// Every constraint must hold for the transaction to succeed, so the
+/// combination is the intersection: the earliest deadline, the latest
+/// not-before, the highest minimum slot and the lowest maximum slot. The
+/// window is exact only when `uncombined` is empty.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TimeConstraints {
+    /// Earliest `ValidateDeadline` deadline, ignoring deadline 0, which
+    /// never expires
+    pub deadline: Option<i64>,
+    /// Latest envelope `not_before`
+    pub not_before: Option<i64>,
+    /// Highest envelope `min_slot`
+    pub min_slot: Option<u64>,
+    /// Lowest envelope `max_slot`
+    pub max_slot: Option<u64>,
+    /// Indices of the instructions whose time-dependent checks are not
+    /// reflected in the bounds above
+    pub uncombined: Vec<usize>,
+}
+
+impl TimeConstraints {
+    /// Returns whether the transaction carries no time constraint
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Errors that may be returned when scanning a message
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScanError {
+    /// An instruction's program ID index is outside the account keys
+    InvalidProgramIdIndex {
+        /// Index of the instruction in the message
+        index: usize,
+    },
+    /// An instruction of the program has data the program rejects
+    InvalidInstructionData {
+        /// Index of the instruction in the message
+        index: usize,
+    },
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidProgramIdIndex { index } => {
+                write!(f, "Instruction {index}: invalid program ID index")
+            }
+            Self::InvalidInstructionData { index } => {
+                write!(f, "Instruction {index}: invalid deadline validator data")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScanError {}
+
+/// Returns the combined time constraints of a message
+///
+/// `loaded_addresses` are the keys a v0 message loads from address lookup
+/// tables, if already resolved. Program IDs are looked up in them too,
+/// although the runtime only accepts static program IDs. Only top-level
+/// instructions are scanned; instructions invoked through CPI are not
+/// visible in the message.
+///
+/// The returned window can be wider than the one the program enforces; see
+/// [`TimeConstraints::uncombined`].
+pub fn scan_message(
+    message: &VersionedMessage,
+    loaded_addresses: Option<&LoadedAddresses>,
+) -> Result<TimeConstraints, ScanError> {
+    let keys = AccountKeys::new(message.static_account_keys(), loaded_addresses);
+    let mut constraints = TimeConstraints::default();
+
+    for (index, instruction) in message.instructions().iter().enumerate() {
+        let program_id = keys
+            .get(usize::from(instruction.program_id_index))
+            .ok_or(ScanError::InvalidProgramIdIndex { index })?;
+        if *program_id != ID {
+            continue;
+        }
+        let envelope = Envelope::unpack(&instruction.data)
+            .map_err(|_| ScanError::InvalidInstructionData { index })?;
+
+        if let DeadlineInstruction::ValidateDeadline { deadline } = envelope.instruction {
+            if deadline != 0 {
+                constraints.deadline = combine(constraints.deadline, Some(deadline), i64::min);
+            }
+        }
+        if is_uncombined(&envelope.instruction) {
+            constraints.uncombined.push(index);
+        }
+        let envelope = envelope.constraints;
+        constraints.not_before = combine(constraints.not_before, envelope.not_before, i64::max);
+        constraints.min_slot = combine(constraints.min_slot, envelope.min_slot, u64::max);
+        constraints.max_slot = combine(constraints.max_slot, envelope.max_slot, u64::min);
+    }
+    Ok(constraints)
+}
+
+/// Returns whether an instruction depends on the current time in a way the
+/// combined bounds cannot express
+///
+/// The oracle and slot progression checks compare clock sources with each
+/// other rather than bounding the time a transaction lands, so they count
+/// as combined.
+fn is_uncombined(instruction: &DeadlineInstruction) -> bool {
+    match instruction {
+        DeadlineInstruction::ValidateSchedule { .. }
+        | DeadlineInstruction::ValidateExpression { .. }
+        | DeadlineInstruction::ValidateDutchAuction { .. }
+        | DeadlineInstruction::CheckAndBumpCooldown
+        | DeadlineInstruction::ValidateSession { .. } => true,
+        DeadlineInstruction::ValidateDeadline { .. }
+        | DeadlineInstruction::ValidateOracleClock { .. }
+        | DeadlineInstruction::ValidateClockSanity { .. }
+        | DeadlineInstruction::InitializeCooldown { .. }
+        | DeadlineInstruction::CreateSession { .. }
+        | DeadlineInstruction::RevokeSession => false,
+    }
+}
+
+/// Combines two optional bounds, keeping whichever is set
+fn combine<T>(a: Option<T>, b: Option<T>, f: fn(T, T) -> T) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(f(a, b)),
+        (a, b) => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction::{
+        validate_clock_sanity, validate_deadline, validate_expression, validate_schedule,
+        versioned, Constraints, Op, SCHEDULE_LEN,
+    };
+    use solana_message::{compiled_instruction::CompiledInstruction, v0, Hash, Message};
+    use solana_pubkey::Pubkey;
+
+    fn legacy(instructions: &[solana_instruction::Instruction]) -> VersionedMessage {
+        VersionedMessage::Legacy(Message::new(instructions, Some(&Pubkey::new_unique())))
+    }
+
+    #[test]
+    fn test_scan_combines_constraints() {
+        let other = solana_instruction::Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[0, 1, 2],
+            vec![],
+        );
+        let message = legacy(&[
+            validate_deadline(1800000300),
+            other,
+            validate_deadline(0),
+            versioned(
+                validate_deadline(1800000000),
+                &Constraints {
+                    not_before: Some(1700000000),
+                    min_slot: Some(10),
+                    max_slot: Some(1000),
+                },
+            ),
+            versioned(
+                validate_clock_sanity(30),
+                &Constraints {
+                    not_before: Some(1700000060),
+                    min_slot: Some(5),
+                    max_slot: Some(900),
+                },
+            ),
+        ]);
+        assert_eq!(
+            scan_message(&message, None),
+            Ok(TimeConstraints {
+                deadline: Some(1800000000),
+                not_before: Some(1700000060),
+                min_slot: Some(10),
+                max_slot: Some(900),
+                uncombined: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn test_scan_reports_uncombined() {
+        let message = legacy(&[
+            validate_deadline(1800000000),
+            validate_schedule(&[0xFF; SCHEDULE_LEN]),
+            validate_expression(&[Op::TimeLe(1700000000)]),
+        ]);
+        let constraints = scan_message(&message, None).unwrap();
+        assert_eq!(constraints.deadline, Some(1800000000));
+        assert_eq!(constraints.uncombined, [1, 2]);
+
+        let message = legacy(&[validate_expression(&[Op::SlotGe(5)])]);
+        assert!(!scan_message(&message, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_scan_without_constraints() {
+        let constraints = scan_message(&legacy(&[validate_clock_sanity(30)]), None).unwrap();
+        assert!(constraints.is_empty());
+        assert!(scan_message(&legacy(&[]), None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_scan_v0_with_loaded_addresses() {
+        let payer = Pubkey::new_unique();
+        let mut message = v0::Message::try_compile(
+            &payer,
+            &[validate_deadline(1800000000)],
+            &[],
+            Hash::default(),
+        )
+        .unwrap();
+        // An instruction whose program ID resolves through a lookup table
+        let loaded = LoadedAddresses {
+            writable: vec![],
+            readonly: vec![ID],
+        };
+        let loaded_index = message.account_keys.len() as u8;
+        message
+            .instructions
+            .push(CompiledInstruction::new_from_raw_parts(
+                loaded_index,
+                validate_deadline(1700000000).data,
+                vec![],
+            ));
+        let message = VersionedMessage::V0(message);
+
+        assert_eq!(
+            scan_message(&message, Some(&loaded)).unwrap().deadline,
+            Some(1700000000)
+        );
+        assert_eq!(
+            scan_message(&message, None),
+            Err(ScanError::InvalidProgramIdIndex { index: 1 })
+        );
+    }
+
+    #[test]
+    fn test_scan_malformed() {
+        let mut malformed = validate_deadline(1800000000);
+        malformed.data.push(0);
+        let message = legacy(&[validate_deadline(1800000000), malformed]);
+        let error = scan_message(&message, None).unwrap_err();
+        assert_eq!(error, ScanError::InvalidInstructionData { index: 1 });
+        assert_eq!(
+            error.to_string(),
+            "Instruction 1: invalid deadline validator data"
+        );
+    }
+}
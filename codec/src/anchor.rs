@@ -0,0 +1,94 @@
+//! Anchor-compatible instruction discriminators
+//!
+//! Anchor identifies instructions by `sha256("global:<name>")[..8]`, with
+//! borsh-encoded arguments after it. Each instruction except the versioned
+//! envelope has such an alias next to its single-byte discriminator. None
+//! of the aliases starts with a byte used as a single-byte discriminator,
+//! so both forms can be told apart from the first byte. The program's
+//! IDL describes the aliases.
+
+/// `sha256("global:validate_deadline")[..8]`
+pub const VALIDATE_DEADLINE: [u8; 8] = [135, 237, 97, 217, 233, 252, 217, 178];
+
+/// `sha256("global:validate_oracle_clock")[..8]`
+pub const VALIDATE_ORACLE_CLOCK: [u8; 8] = [57, 199, 185, 114, 87, 95, 194, 149];
+
+/// `sha256("global:validate_clock_sanity")[..8]`
+pub const VALIDATE_CLOCK_SANITY: [u8; 8] = [240, 213, 229, 131, 14, 73, 147, 119];
+
+/// `sha256("global:validate_dutch_auction")[..8]`
+pub const VALIDATE_DUTCH_AUCTION: [u8; 8] = [54, 145, 111, 249, 238, 130, 145, 113];
+
+/// `sha256("global:validate_schedule")[..8]`
+pub const VALIDATE_SCHEDULE: [u8; 8] = [172, 20, 156, 86, 98, 144, 136, 48];
+
+/// `sha256("global:validate_expression")[..8]`
+pub const VALIDATE_EXPRESSION: [u8; 8] = [201, 73, 222, 100, 69, 149, 218, 187];
+
+/// `sha256("global:initialize_cooldown")[..8]`
+pub const INITIALIZE_COOLDOWN: [u8; 8] = [102, 21, 189, 88, 96, 93, 94, 38];
+
+/// `sha256("global:check_and_bump_cooldown")[..8]`
+pub const CHECK_AND_BUMP_COOLDOWN: [u8; 8] = [33, 167, 6, 14, 236, 202, 235, 82];
+
+/// `sha256("global:create_session")[..8]`
+pub const CREATE_SESSION: [u8; 8] = [242, 193, 143, 179, 150, 25, 122, 227];
+
+/// `sha256("global:revoke_session")[..8]`
+pub const REVOKE_SESSION: [u8; 8] = [86, 92, 198, 120, 144, 2, 7, 194];
+
+/// `sha256("global:validate_session")[..8]`
+pub const VALIDATE_SESSION: [u8; 8] = [126, 33, 177, 34, 33, 245, 46, 205];
+
+/// Anchor instruction names, aliases and the single-byte discriminators
+/// they stand for
+pub const ALIASES: [(&str, [u8; 8], u8); 11] = [
+    ("validate_deadline", VALIDATE_DEADLINE, 0),
+    ("validate_oracle_clock", VALIDATE_ORACLE_CLOCK, 1),
+    ("validate_clock_sanity", VALIDATE_CLOCK_SANITY, 2),
+    ("validate_dutch_auction", VALIDATE_DUTCH_AUCTION, 3),
+    ("validate_schedule", VALIDATE_SCHEDULE, 4),
+    ("validate_expression", VALIDATE_EXPRESSION, 5),
+    ("initialize_cooldown", INITIALIZE_COOLDOWN, 6),
+    ("check_and_bump_cooldown", CHECK_AND_BUMP_COOLDOWN, 7),
+    ("create_session", CREATE_SESSION, 8),
+    ("revoke_session", REVOKE_SESSION, 9),
+    ("validate_session", VALIDATE_SESSION, 10),
+];
+
+/// Returns the single-byte discriminator an Anchor alias stands for
+pub fn legacy_discriminator(alias: &[u8; 8]) -> Option<u8> {
+    ALIASES
+        .iter()
+        .find(|(_, discriminator, _)| discriminator == alias)
+        .map(|(_, _, variant)| *variant)
+}
+
+/// Returns the Anchor alias of a single-byte discriminator
+pub fn alias(variant: u8) -> Option<[u8; 8]> {
+    ALIASES
+        .iter()
+        .find(|(_, _, v)| *v == variant)
+        .map(|(_, discriminator, _)| *discriminator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction::{DEADLINE_U32_TAG, DEADLINE_VARINT_TAG, ENVELOPE_TAG};
+
+    #[test]
+    fn test_aliases_disjoint_from_single_byte_discriminators() {
+        for (name, discriminator, _) in ALIASES {
+            let first = discriminator[0];
+            assert!(
+                ALIASES.iter().all(|(_, _, variant)| *variant != first),
+                "{name}"
+            );
+            assert!(
+                ![DEADLINE_U32_TAG, DEADLINE_VARINT_TAG, ENVELOPE_TAG].contains(&first),
+                "{name}"
+            );
+        }
+    }
+}
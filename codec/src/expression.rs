@@ -0,0 +1,298 @@
+//! Time-predicate expression encoding
+//!
+//! An expression is a short stack program of comparisons against
+//! `Clock.unix_timestamp`, `Clock.slot` and `Clock.epoch`, combined with
+//! AND, OR and NOT. For example "(t <= D1 AND slot <= S) OR t <= D2" is
+//! `TimeLe(D1) SlotLe(S) And TimeLe(D2) Or`.
+//!
+//! ## Encoding
+//! Each op is one opcode byte. Comparisons are followed by an 8-byte
+//! little-endian operand (i64 for time, u64 for slot and epoch):
+//!
+//! | Opcode | Op      | Pushes                      |
+//! |--------|---------|-----------------------------|
+//! | 0x01   | TimeLe  | unix_timestamp <= operand   |
+//! | 0x02   | TimeGe  | unix_timestamp >= operand   |
+//! | 0x03   | SlotLe  | slot <= operand             |
+//! | 0x04   | SlotGe  | slot >= operand             |
+//! | 0x05   | EpochLe | epoch <= operand            |
+//! | 0x06   | EpochGe | epoch >= operand            |
+//! | 0x10   | And     | pops b, a; pushes a AND b   |
+//! | 0x11   | Or      | pops b, a; pushes a OR b    |
+//! | 0x12   | Not     | pops a; pushes NOT a        |
+//!
+//! Programs are limited to `MAX_OPS` ops and `MAX_STACK_DEPTH` stack
+//! entries, which bounds compute usage, and must leave exactly one value
+//! on the stack.
+
+use crate::Error;
+
+/// Maximum number of ops in an expression
+pub const MAX_OPS: usize = 32;
+
+/// Maximum number of values on the evaluation stack
+pub const MAX_STACK_DEPTH: usize = 8;
+
+/// Maximum encoded length of a single op
+pub const MAX_OP_LEN: usize = 9;
+
+/// Maximum encoded length of an expression
+pub const MAX_EXPRESSION_LEN: usize = MAX_OPS * MAX_OP_LEN;
+
+/// A single expression op
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    /// Pushes unix_timestamp <= operand
+    TimeLe(i64),
+    /// Pushes unix_timestamp >= operand
+    TimeGe(i64),
+    /// Pushes slot <= operand
+    SlotLe(u64),
+    /// Pushes slot >= operand
+    SlotGe(u64),
+    /// Pushes epoch <= operand
+    EpochLe(u64),
+    /// Pushes epoch >= operand
+    EpochGe(u64),
+    /// Pops two values and pushes their conjunction
+    And,
+    /// Pops two values and pushes their disjunction
+    Or,
+    /// Pops one value and pushes its negation
+    Not,
+}
+
+/// Splits the 8-byte operand off the start of `input`
+fn operand(input: &[u8]) -> Result<([u8; 8], &[u8]), Error> {
+    input
+        .split_first_chunk::<8>()
+        .map(|(operand, rest)| (*operand, rest))
+        .ok_or(Error::InvalidInstructionData)
+}
+
+impl Op {
+    /// Returns the opcode byte
+    pub fn opcode(&self) -> u8 {
+        match self {
+            Self::TimeLe(_) => 0x01,
+            Self::TimeGe(_) => 0x02,
+            Self::SlotLe(_) => 0x03,
+            Self::SlotGe(_) => 0x04,
+            Self::EpochLe(_) => 0x05,
+            Self::EpochGe(_) => 0x06,
+            Self::And => 0x10,
+            Self::Or => 0x11,
+            Self::Not => 0x12,
+        }
+    }
+
+    /// Returns the encoded length of the op
+    pub fn packed_len(&self) -> usize {
+        match self {
+            Self::And | Self::Or | Self::Not => 1,
+            _ => MAX_OP_LEN,
+        }
+    }
+
+    /// Writes the encoded op to the start of `dst` and returns its length
+    ///
+    /// Panics if `dst` is shorter than `packed_len()`.
+    pub fn pack_into(&self, dst: &mut [u8]) -> usize {
+        dst[0] = self.opcode();
+        let operand = match *self {
+            Self::TimeLe(t) | Self::TimeGe(t) => t.to_le_bytes(),
+            Self::SlotLe(v) | Self::SlotGe(v) | Self::EpochLe(v) | Self::EpochGe(v) => {
+                v.to_le_bytes()
+            }
+            Self::And | Self::Or | Self::Not => return 1,
+        };
+        dst[1..MAX_OP_LEN].copy_from_slice(&operand);
+        MAX_OP_LEN
+    }
+
+    /// Decodes the op at the start of `input`, returning the remaining bytes
+    pub fn unpack(input: &[u8]) -> Result<(Self, &[u8]), Error> {
+        let (&opcode, rest) = input.split_first().ok_or(Error::InvalidInstructionData)?;
+        Ok(match opcode {
+            0x01 => operand(rest).map(|(t, rest)| (Self::TimeLe(i64::from_le_bytes(t)), rest))?,
+            0x02 => operand(rest).map(|(t, rest)| (Self::TimeGe(i64::from_le_bytes(t)), rest))?,
+            0x03 => operand(rest).map(|(s, rest)| (Self::SlotLe(u64::from_le_bytes(s)), rest))?,
+            0x04 => operand(rest).map(|(s, rest)| (Self::SlotGe(u64::from_le_bytes(s)), rest))?,
+            0x05 => operand(rest).map(|(e, rest)| (Self::EpochLe(u64::from_le_bytes(e)), rest))?,
+            0x06 => operand(rest).map(|(e, rest)| (Self::EpochGe(u64::from_le_bytes(e)), rest))?,
+            0x10 => (Self::And, rest),
+            0x11 => (Self::Or, rest),
+            0x12 => (Self::Not, rest),
+            _ => return Err(Error::InvalidInstructionData),
+        })
+    }
+
+    /// Number of values this op pops from and pushes onto the stack
+    pub fn stack_effect(&self) -> (usize, usize) {
+        match self {
+            Self::And | Self::Or => (2, 1),
+            Self::Not => (1, 1),
+            _ => (0, 1),
+        }
+    }
+}
+
+/// Expression carried by `ValidateExpression`, either a slice of ops to
+/// pack or bytecode borrowed from unpacked instruction data
+#[derive(Clone, Copy, Debug)]
+pub struct Expression<'a>(Repr<'a>);
+
+#[derive(Clone, Copy, Debug)]
+enum Repr<'a> {
+    Ops(&'a [Op]),
+    Bytecode(&'a [u8]),
+}
+
+impl<'a> Expression<'a> {
+    /// Wraps ops for packing
+    ///
+    /// The ops are not validated here. The program rejects malformed
+    /// expressions when it unpacks them.
+    pub fn from_ops(ops: &'a [Op]) -> Self {
+        Self(Repr::Ops(ops))
+    }
+
+    /// Validates encoded ops without copying them
+    ///
+    /// Rejects unknown opcodes, truncated operands, more than `MAX_OPS`
+    /// ops, stack underflow, more than `MAX_STACK_DEPTH` values on the
+    /// stack, and programs that do not leave exactly one value.
+    pub fn from_bytecode(mut bytecode: &'a [u8]) -> Result<Self, Error> {
+        let input = bytecode;
+        let mut count = 0usize;
+        let mut depth = 0usize;
+
+        while !bytecode.is_empty() {
+            if count == MAX_OPS {
+                return Err(Error::InvalidInstructionData);
+            }
+            let (op, rest) = Op::unpack(bytecode)?;
+            let (popped, pushed) = op.stack_effect();
+            depth = depth
+                .checked_sub(popped)
+                .ok_or(Error::InvalidInstructionData)?
+                + pushed;
+            if depth > MAX_STACK_DEPTH {
+                return Err(Error::InvalidInstructionData);
+            }
+            count += 1;
+            bytecode = rest;
+        }
+
+        if depth != 1 {
+            return Err(Error::InvalidInstructionData);
+        }
+        Ok(Self(Repr::Bytecode(input)))
+    }
+
+    /// Returns an iterator over the ops
+    pub fn ops(&self) -> Ops<'a> {
+        Ops(self.0)
+    }
+
+    /// Returns the encoded length of the expression
+    pub fn packed_len(&self) -> usize {
+        match self.0 {
+            Repr::Ops(ops) => ops.iter().map(Op::packed_len).sum(),
+            Repr::Bytecode(bytecode) => bytecode.len(),
+        }
+    }
+
+    /// Writes the encoded ops to the start of `dst` and returns their length
+    ///
+    /// Panics if `dst` is shorter than `packed_len()`.
+    pub fn pack_into(&self, dst: &mut [u8]) -> usize {
+        match self.0 {
+            Repr::Ops(ops) => ops
+                .iter()
+                .fold(0, |len, op| len + op.pack_into(&mut dst[len..])),
+            Repr::Bytecode(bytecode) => {
+                dst[..bytecode.len()].copy_from_slice(bytecode);
+                bytecode.len()
+            }
+        }
+    }
+}
+
+impl PartialEq for Expression<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ops().eq(other.ops())
+    }
+}
+
+impl Eq for Expression<'_> {}
+
+/// Iterator over the ops of an `Expression`
+#[derive(Clone, Debug)]
+pub struct Ops<'a>(Repr<'a>);
+
+impl Iterator for Ops<'_> {
+    type Item = Op;
+
+    fn next(&mut self) -> Option<Op> {
+        match &mut self.0 {
+            Repr::Ops(ops) => {
+                let (op, rest) = ops.split_first()?;
+                *ops = rest;
+                Some(*op)
+            }
+            // Validated by `from_bytecode`
+            Repr::Bytecode(bytecode) => {
+                let (op, rest) = Op::unpack(bytecode).ok()?;
+                *bytecode = rest;
+                Some(op)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (t <= D1 AND slot <= S) OR t <= D2
+    const EXAMPLE: [Op; 5] = [
+        Op::TimeLe(1_000),
+        Op::SlotLe(500),
+        Op::And,
+        Op::TimeLe(2_000),
+        Op::Or,
+    ];
+
+    #[test]
+    fn test_expression_roundtrip() {
+        let mut buf = [0u8; MAX_EXPRESSION_LEN];
+        let expression = Expression::from_ops(&EXAMPLE);
+        let len = expression.pack_into(&mut buf);
+        assert_eq!(len, 29);
+        assert_eq!(len, expression.packed_len());
+
+        let unpacked = Expression::from_bytecode(&buf[..len]).unwrap();
+        assert_eq!(unpacked, expression);
+        assert!(unpacked.ops().eq(EXAMPLE));
+    }
+
+    #[test]
+    fn test_expression_invalid() {
+        let invalid: [&[u8]; 6] = [
+            &[],
+            &[0x10],
+            &[0x01, 0, 0],
+            &[0x07],
+            &[0x12; 2],
+            &[0x03, 0, 0, 0, 0, 0, 0, 0, 0, 0x03, 0, 0, 0, 0, 0, 0, 0, 0],
+        ];
+        for bytecode in invalid {
+            assert_eq!(
+                Expression::from_bytecode(bytecode),
+                Err(Error::InvalidInstructionData),
+                "{bytecode:?}"
+            );
+        }
+    }
+}
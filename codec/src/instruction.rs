@@ -0,0 +1,840 @@
+//! Instruction encoding
+//!
+//! Every instruction has a single-byte discriminator followed by its
+//! fixed-layout fields, and, except for the versioned envelope, an Anchor
+//! alias followed by borsh-encoded fields (see the `anchor` module).
+//! `ValidateDeadline` has two further compact encodings, and `pack_into`
+//! always picks the shortest one.
+
+use crate::{
+    anchor,
+    expression::{Expression, MAX_EXPRESSION_LEN},
+    Error,
+};
+
+/// Discriminator of the compact `ValidateDeadline` with a u32 offset
+pub const DEADLINE_U32_TAG: u8 = 11;
+
+/// Discriminator of the compact `ValidateDeadline` with a varint deadline
+pub const DEADLINE_VARINT_TAG: u8 = 12;
+
+/// Base of u32 compact deadlines (2024-01-01T00:00:00Z)
+///
+/// Offsets cover deadlines until 2160.
+pub const COMPACT_DEADLINE_BASE: i64 = 1_704_067_200;
+
+/// Maximum length of a LEB128-encoded u64
+const MAX_VARINT_LEN: usize = 10;
+
+/// Discriminator of the versioned envelope
+pub const ENVELOPE_TAG: u8 = 0xFF;
+
+/// Current envelope version
+pub const ENVELOPE_VERSION: u8 = 1;
+
+/// Envelope field type of `Constraints::not_before`
+const FIELD_NOT_BEFORE: u8 = 1;
+
+/// Envelope field type of `Constraints::min_slot`
+const FIELD_MIN_SLOT: u8 = 2;
+
+/// Envelope field type of `Constraints::max_slot`
+const FIELD_MAX_SLOT: u8 = 3;
+
+/// Envelope header: tag, version, flags and inner length (u16)
+const ENVELOPE_HEADER_LEN: usize = 5;
+
+/// Encoded length of a single envelope field
+const FIELD_LEN: usize = 10;
+
+/// Maximum length of an instruction other than `ValidateExpression` in
+/// its legacy encoding
+const MAX_FIXED_LEN: usize = 97;
+
+/// Maximum length of an instruction in its legacy encoding
+pub const MAX_INSTRUCTION_LEN: usize = 1 + MAX_EXPRESSION_LEN;
+
+/// Maximum length of any encoding produced by `pack_into`, `Envelope::pack_into`
+/// or `pack_anchor_into`, for expressions of at most `MAX_OPS` ops
+pub const MAX_PACKED_LEN: usize = ENVELOPE_HEADER_LEN + MAX_INSTRUCTION_LEN + 3 * FIELD_LEN;
+
+/// Optional constraints carried by a versioned envelope
+///
+/// Each constraint is checked against the clock before the wrapped
+/// instruction runs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Constraints {
+    /// Fails if current_time < not_before
+    pub not_before: Option<i64>,
+    /// Fails if slot < min_slot
+    pub min_slot: Option<u64>,
+    /// Fails if slot > max_slot
+    pub max_slot: Option<u64>,
+}
+
+impl Constraints {
+    /// Returns whether no constraint is set
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Instructions supported by the deadline validator program
+///
+/// The account lists and semantics are documented on the program's
+/// `DeadlineInstruction`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeadlineInstruction<'a> {
+    /// Discriminator 0: deadline (i64), or discriminator 11: deadline -
+    /// `COMPACT_DEADLINE_BASE` (u32), or discriminator 12: zigzag-encoded
+    /// deadline (minimal LEB128 varint)
+    ValidateDeadline {
+        /// Unix timestamp deadline, 0 for "never expires"
+        deadline: i64,
+    },
+    /// Discriminator 1: max_drift (u64), max_staleness_slots (u64),
+    /// feed_id (32 bytes), min_publish_time (i64)
+    ValidateOracleClock {
+        /// Maximum clock drift from the oracle, in seconds
+        max_drift: u64,
+        /// Maximum age of the oracle update, in slots
+        max_staleness_slots: u64,
+        /// Price feed the oracle account must hold
+        feed_id: [u8; 32],
+        /// Earliest accepted oracle publish time, as a Unix timestamp
+        min_publish_time: i64,
+    },
+    /// Discriminator 2: max_drift (u64)
+    ValidateClockSanity {
+        /// Maximum clock drift from slot progression, in seconds
+        max_drift: u64,
+    },
+    /// Discriminator 3: start_price (u64), floor_price (u64), start_time
+    /// (i64), end_time (i64), mint (32 bytes), destination (32 bytes), with
+    /// start_price >= floor_price and end_time > start_time
+    ValidateDutchAuction {
+        /// Price at start_time, in base units
+        start_price: u64,
+        /// Price from end_time on, in base units
+        floor_price: u64,
+        /// Unix timestamp the price starts falling
+        start_time: i64,
+        /// Unix timestamp the price reaches floor_price
+        end_time: i64,
+        /// Mint the payment must be made in
+        mint: [u8; 32],
+        /// Token account the payment must be made to
+        destination: [u8; 32],
+    },
+    /// Discriminator 4: 21-byte bitmap
+    ValidateSchedule {
+        /// One bit per UTC hour of the week, Monday 00:00 first
+        schedule: [u8; 21],
+    },
+    /// Discriminator 5: encoded ops (see the `expression` module)
+    ValidateExpression {
+        /// Expression that must evaluate to true
+        ops: Expression<'a>,
+    },
+    /// Discriminator 6: min_interval (u64), seed (u64)
+    InitializeCooldown {
+        /// Minimum number of seconds between two actions
+        min_interval: u64,
+        /// Seed distinguishing cooldowns of the same authority
+        seed: u64,
+    },
+    /// Discriminator 7
+    CheckAndBumpCooldown,
+    /// Discriminator 8: delegate (32 bytes), expires_at (i64), spend cap
+    /// flag (u8), spend cap (u64, zero when the flag is 0), seed (u64)
+    CreateSession {
+        /// Session key allowed to sign `ValidateSession`
+        delegate: [u8; 32],
+        /// Unix timestamp after which the session is expired, 0 for
+        /// "never expires"
+        expires_at: i64,
+        /// Maximum total amount the delegate may authorize
+        spend_cap: Option<u64>,
+        /// Seed distinguishing sessions of the same owner
+        seed: u64,
+    },
+    /// Discriminator 9
+    RevokeSession,
+    /// Discriminator 10: amount (u64)
+    ValidateSession {
+        /// Amount counted against the spend cap
+        amount: u64,
+    },
+}
+
+/// An instruction with the constraints of its versioned envelope
+///
+/// Instructions outside an envelope have no constraints. Envelopes cannot
+/// be nested, so an instruction carries at most one set of constraints.
+///
+/// Envelope layout:
+/// - Byte 0: Discriminator (0xFF = envelope)
+/// - Byte 1: version (must be 1)
+/// - Byte 2: flags (reserved, must be 0)
+/// - Bytes 3-4: length of the wrapped instruction (u16, little-endian)
+/// - Wrapped instruction, in the encoding `pack_into` picks
+/// - Fields, each: type (u8), length (u8), value
+///
+/// Fields:
+/// - 1 = not_before (i64, little-endian)
+/// - 2 = min_slot (u64, little-endian)
+/// - 3 = max_slot (u64, little-endian)
+///
+/// Encoding is canonical: fields appear in ascending type order, at most
+/// once, with their exact length. Unknown versions, flags and field types
+/// are rejected, since ignoring a constraint would weaken it. At least one
+/// field must be present, and min_slot must not exceed max_slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Envelope<'a> {
+    /// Instruction run after the constraints pass
+    pub instruction: DeadlineInstruction<'a>,
+    /// Constraints checked first
+    pub constraints: Constraints,
+}
+
+/// Bounds-checked writer over a caller-provided buffer
+struct Writer<'a> {
+    dst: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> Writer<'a> {
+    fn new(dst: &'a mut [u8]) -> Self {
+        Self { dst, len: 0 }
+    }
+
+    fn put(&mut self, bytes: &[u8]) {
+        self.dst[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+
+    fn put_u8(&mut self, byte: u8) {
+        self.put(&[byte]);
+    }
+
+    /// Returns the unwritten remainder of the buffer
+    fn rest(&mut self) -> &mut [u8] {
+        &mut self.dst[self.len..]
+    }
+}
+
+impl<'a> Envelope<'a> {
+    /// Unpacks instruction data, borrowing variable-length fields from
+    /// `input`
+    ///
+    /// Expected format:
+    /// - Byte 0: Discriminator
+    /// - Remaining bytes: fields of the variant, with exact length
+    ///
+    /// Anchor-style 8-byte discriminators are accepted as aliases,
+    /// followed by borsh-encoded arguments.
+    pub fn unpack(input: &'a [u8]) -> Result<Self, Error> {
+        let (&variant, rest) = input.split_first().ok_or(Error::InvalidInstructionData)?;
+
+        if let Some((alias, rest)) = input.split_first_chunk::<8>() {
+            if let Some(variant) = anchor::legacy_discriminator(alias) {
+                return Ok(DeadlineInstruction::unpack_anchor(variant, rest)?.into());
+            }
+        }
+        if variant == ENVELOPE_TAG {
+            return Self::unpack_envelope(rest);
+        }
+        Ok(DeadlineInstruction::unpack_variant(variant, rest)?.into())
+    }
+
+    fn unpack_envelope(input: &'a [u8]) -> Result<Self, Error> {
+        let [version, flags, len_lo, len_hi, rest @ ..] = input else {
+            return Err(Error::InvalidInstructionData);
+        };
+        if *version != ENVELOPE_VERSION || *flags != 0 {
+            return Err(Error::InvalidInstructionData);
+        }
+        let len = u16::from_le_bytes([*len_lo, *len_hi]) as usize;
+        if rest.len() < len || rest.first() == Some(&ENVELOPE_TAG) {
+            return Err(Error::InvalidInstructionData);
+        }
+        let (inner, mut fields) = rest.split_at(len);
+        let instruction = Self::unpack(inner)?.instruction;
+        // Unlike top-level data, the wrapped instruction must use the
+        // encoding `pack_into` picks, e.g. the shortest deadline encoding
+        let mut canonical = [0u8; MAX_INSTRUCTION_LEN];
+        let canonical_len = instruction.pack_into(&mut canonical);
+        if canonical[..canonical_len] != *inner {
+            return Err(Error::InvalidInstructionData);
+        }
+
+        let mut constraints = Constraints::default();
+        let mut last_type = 0u8;
+        while let [field_type, field_len, rest @ ..] = fields {
+            if *field_type <= last_type || *field_len != 8 {
+                return Err(Error::InvalidInstructionData);
+            }
+            last_type = *field_type;
+            let (value, rest) = unpack_u64(rest)?;
+            match *field_type {
+                FIELD_NOT_BEFORE => constraints.not_before = Some(value as i64),
+                FIELD_MIN_SLOT => constraints.min_slot = Some(value),
+                FIELD_MAX_SLOT => constraints.max_slot = Some(value),
+                _ => return Err(Error::InvalidInstructionData),
+            }
+            fields = rest;
+        }
+        if !fields.is_empty() || constraints.is_empty() {
+            return Err(Error::InvalidInstructionData);
+        }
+        if let (Some(min_slot), Some(max_slot)) = (constraints.min_slot, constraints.max_slot) {
+            if min_slot > max_slot {
+                return Err(Error::InvalidInstructionData);
+            }
+        }
+
+        Ok(Self {
+            instruction,
+            constraints,
+        })
+    }
+
+    /// Returns the length `pack_into` writes
+    pub fn packed_len(&self) -> usize {
+        if self.constraints.is_empty() {
+            return self.instruction.packed_len();
+        }
+        let fields = [
+            self.constraints.not_before.is_some(),
+            self.constraints.min_slot.is_some(),
+            self.constraints.max_slot.is_some(),
+        ];
+        let fields = fields.into_iter().filter(|set| *set).count();
+        ENVELOPE_HEADER_LEN + self.instruction.packed_len() + fields * FIELD_LEN
+    }
+
+    /// Writes the envelope to the start of `dst` and returns its length
+    ///
+    /// Without constraints, writes the legacy encoding of the instruction,
+    /// its only canonical form. Panics if `dst` is too short;
+    /// `MAX_PACKED_LEN` bytes always suffice.
+    pub fn pack_into(&self, dst: &mut [u8]) -> usize {
+        if self.constraints.is_empty() {
+            return self.instruction.pack_into(dst);
+        }
+        let mut writer = Writer::new(dst);
+        writer.put(&[ENVELOPE_TAG, ENVELOPE_VERSION, 0, 0, 0]);
+        let len = self.instruction.pack_into(writer.rest());
+        writer.dst[3..5].copy_from_slice(&(len as u16).to_le_bytes());
+        writer.len += len;
+        let fields = [
+            (
+                FIELD_NOT_BEFORE,
+                self.constraints.not_before.map(|t| t as u64),
+            ),
+            (FIELD_MIN_SLOT, self.constraints.min_slot),
+            (FIELD_MAX_SLOT, self.constraints.max_slot),
+        ];
+        for (field_type, value) in fields {
+            if let Some(value) = value {
+                writer.put(&[field_type, 8]);
+                writer.put(&value.to_le_bytes());
+            }
+        }
+        writer.len
+    }
+}
+
+impl<'a> From<DeadlineInstruction<'a>> for Envelope<'a> {
+    fn from(instruction: DeadlineInstruction<'a>) -> Self {
+        Self {
+            instruction,
+            constraints: Constraints::default(),
+        }
+    }
+}
+
+impl<'a> DeadlineInstruction<'a> {
+    fn unpack_variant(variant: u8, rest: &'a [u8]) -> Result<Self, Error> {
+        Ok(match variant {
+            0 => Self::ValidateDeadline {
+                deadline: i64::from_le_bytes(exact(rest)?),
+            },
+            1 => {
+                let data: [u8; 56] = exact(rest)?;
+                let (max_drift, rest) = unpack_u64(&data)?;
+                let (max_staleness_slots, rest) = unpack_u64(rest)?;
+                let (feed_id, rest) = rest.split_first_chunk::<32>().unwrap();
+                let (min_publish_time, _) = unpack_i64(rest)?;
+                Self::ValidateOracleClock {
+                    max_drift,
+                    max_staleness_slots,
+                    feed_id: *feed_id,
+                    min_publish_time,
+                }
+            }
+            2 => Self::ValidateClockSanity {
+                max_drift: u64::from_le_bytes(exact(rest)?),
+            },
+            3 => {
+                let data: [u8; 96] = exact(rest)?;
+                let (prices, rest) = data.split_first_chunk::<32>().unwrap();
+                let [start_price, floor_price, start_time, end_time] = unpack_u64s(prices)?;
+                let (start_time, end_time) = (start_time as i64, end_time as i64);
+                if start_price < floor_price || end_time <= start_time {
+                    return Err(Error::InvalidInstructionData);
+                }
+                let (mint, destination) = rest.split_first_chunk::<32>().unwrap();
+                Self::ValidateDutchAuction {
+                    start_price,
+                    floor_price,
+                    start_time,
+                    end_time,
+                    mint: *mint,
+                    destination: exact(destination)?,
+                }
+            }
+            4 => Self::ValidateSchedule {
+                schedule: exact(rest)?,
+            },
+            5 => Self::ValidateExpression {
+                ops: Expression::from_bytecode(rest)?,
+            },
+            6 => {
+                let [min_interval, seed] = unpack_u64s(rest)?;
+                Self::InitializeCooldown { min_interval, seed }
+            }
+            7 if rest.is_empty() => Self::CheckAndBumpCooldown,
+            8 => {
+                let data: [u8; 57] = exact(rest)?;
+                let (delegate, rest) = data.split_first_chunk::<32>().unwrap();
+                let (expires_at, rest) = unpack_i64(rest)?;
+                let (&has_spend_cap, rest) = rest.split_first().unwrap();
+                let (spend_cap, rest) = unpack_u64(rest)?;
+                let (seed, _) = unpack_u64(rest)?;
+                let spend_cap = match (has_spend_cap, spend_cap) {
+                    (0, 0) => None,
+                    (1, spend_cap) => Some(spend_cap),
+                    _ => return Err(Error::InvalidInstructionData),
+                };
+                Self::CreateSession {
+                    delegate: *delegate,
+                    expires_at,
+                    spend_cap,
+                    seed,
+                }
+            }
+            9 if rest.is_empty() => Self::RevokeSession,
+            10 => Self::ValidateSession {
+                amount: u64::from_le_bytes(exact(rest)?),
+            },
+            DEADLINE_U32_TAG => Self::ValidateDeadline {
+                deadline: COMPACT_DEADLINE_BASE + i64::from(u32::from_le_bytes(exact(rest)?)),
+            },
+            DEADLINE_VARINT_TAG => {
+                let zigzag = unpack_varint(rest)?;
+                Self::ValidateDeadline {
+                    deadline: (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64),
+                }
+            }
+            // ENVELOPE_TAG (0xFF) is unpacked by `Envelope::unpack`
+            _ => return Err(Error::InvalidInstructionData),
+        })
+    }
+
+    /// Unpacks the borsh-encoded arguments of an Anchor alias
+    ///
+    /// Borsh matches the single-byte layouts except for variable-length
+    /// and optional arguments.
+    fn unpack_anchor(variant: u8, rest: &'a [u8]) -> Result<Self, Error> {
+        match variant {
+            5 => {
+                let (len, bytecode) = rest
+                    .split_first_chunk::<4>()
+                    .ok_or(Error::InvalidInstructionData)?;
+                if bytecode.len() != u32::from_le_bytes(*len) as usize {
+                    return Err(Error::InvalidInstructionData);
+                }
+                Ok(Self::ValidateExpression {
+                    ops: Expression::from_bytecode(bytecode)?,
+                })
+            }
+            8 => {
+                let (delegate, rest) = rest
+                    .split_first_chunk::<32>()
+                    .ok_or(Error::InvalidInstructionData)?;
+                let (expires_at, rest) = unpack_i64(rest)?;
+                let (spend_cap, rest) = match rest.split_first() {
+                    Some((0, rest)) => (None, rest),
+                    Some((1, rest)) => {
+                        let (spend_cap, rest) = unpack_u64(rest)?;
+                        (Some(spend_cap), rest)
+                    }
+                    _ => return Err(Error::InvalidInstructionData),
+                };
+                let (seed, rest) = unpack_u64(rest)?;
+                if !rest.is_empty() {
+                    return Err(Error::InvalidInstructionData);
+                }
+                Ok(Self::CreateSession {
+                    delegate: *delegate,
+                    expires_at,
+                    spend_cap,
+                    seed,
+                })
+            }
+            _ => Self::unpack_variant(variant, rest),
+        }
+    }
+
+    /// Returns the length `pack_into` writes
+    pub fn packed_len(&self) -> usize {
+        match self {
+            Self::ValidateExpression { ops } => 1 + ops.packed_len(),
+            _ => self.pack_into(&mut [0u8; MAX_FIXED_LEN]),
+        }
+    }
+
+    /// Returns the length `pack_anchor_into` writes
+    pub fn anchor_packed_len(&self) -> usize {
+        match self {
+            Self::ValidateExpression { ops } => 12 + ops.packed_len(),
+            _ => self.pack_anchor_into(&mut [0u8; 8 + MAX_FIXED_LEN]),
+        }
+    }
+
+    /// Writes the instruction to the start of `dst` in its legacy encoding
+    /// and returns its length
+    ///
+    /// Panics if `dst` is too short; `MAX_INSTRUCTION_LEN` bytes always
+    /// suffice.
+    pub fn pack_into(&self, dst: &mut [u8]) -> usize {
+        let mut writer = Writer::new(dst);
+        match self {
+            Self::ValidateDeadline { deadline } => pack_deadline(*deadline, &mut writer),
+            Self::ValidateOracleClock {
+                max_drift,
+                max_staleness_slots,
+                feed_id,
+                min_publish_time,
+            } => {
+                writer.put_u8(1);
+                writer.put(&max_drift.to_le_bytes());
+                writer.put(&max_staleness_slots.to_le_bytes());
+                writer.put(feed_id);
+                writer.put(&min_publish_time.to_le_bytes());
+            }
+            Self::ValidateClockSanity { max_drift } => {
+                writer.put_u8(2);
+                writer.put(&max_drift.to_le_bytes());
+            }
+            Self::ValidateDutchAuction {
+                start_price,
+                floor_price,
+                start_time,
+                end_time,
+                mint,
+                destination,
+            } => {
+                writer.put_u8(3);
+                writer.put(&start_price.to_le_bytes());
+                writer.put(&floor_price.to_le_bytes());
+                writer.put(&start_time.to_le_bytes());
+                writer.put(&end_time.to_le_bytes());
+                writer.put(mint);
+                writer.put(destination);
+            }
+            Self::ValidateSchedule { schedule } => {
+                writer.put_u8(4);
+                writer.put(schedule);
+            }
+            Self::ValidateExpression { ops } => {
+                writer.put_u8(5);
+                writer.len += ops.pack_into(writer.rest());
+            }
+            Self::InitializeCooldown { min_interval, seed } => {
+                writer.put_u8(6);
+                writer.put(&min_interval.to_le_bytes());
+                writer.put(&seed.to_le_bytes());
+            }
+            Self::CheckAndBumpCooldown => writer.put_u8(7),
+            Self::CreateSession {
+                delegate,
+                expires_at,
+                spend_cap,
+                seed,
+            } => {
+                writer.put_u8(8);
+                writer.put(delegate);
+                writer.put(&expires_at.to_le_bytes());
+                writer.put_u8(spend_cap.is_some() as u8);
+                writer.put(&spend_cap.unwrap_or(0).to_le_bytes());
+                writer.put(&seed.to_le_bytes());
+            }
+            Self::RevokeSession => writer.put_u8(9),
+            Self::ValidateSession { amount } => {
+                writer.put_u8(10);
+                writer.put(&amount.to_le_bytes());
+            }
+        }
+        writer.len
+    }
+
+    /// Writes the instruction to the start of `dst` with its Anchor
+    /// discriminator and borsh-encoded arguments, and returns its length
+    ///
+    /// Panics if `dst` is too short; `MAX_PACKED_LEN` bytes always suffice.
+    pub fn pack_anchor_into(&self, dst: &mut [u8]) -> usize {
+        let mut writer = Writer::new(dst);
+        match self {
+            Self::ValidateDeadline { deadline } => {
+                writer.put(&anchor::VALIDATE_DEADLINE);
+                writer.put(&deadline.to_le_bytes());
+            }
+            Self::ValidateExpression { ops } => {
+                writer.put(&anchor::VALIDATE_EXPRESSION);
+                writer.put(&(ops.packed_len() as u32).to_le_bytes());
+                writer.len += ops.pack_into(writer.rest());
+            }
+            Self::CreateSession {
+                delegate,
+                expires_at,
+                spend_cap,
+                seed,
+            } => {
+                writer.put(&anchor::CREATE_SESSION);
+                writer.put(delegate);
+                writer.put(&expires_at.to_le_bytes());
+                match spend_cap {
+                    Some(spend_cap) => {
+                        writer.put_u8(1);
+                        writer.put(&spend_cap.to_le_bytes());
+                    }
+                    None => writer.put_u8(0),
+                }
+                writer.put(&seed.to_le_bytes());
+            }
+            _ => {
+                // Remaining variants share the single-byte layout
+                let mut packed = [0u8; MAX_INSTRUCTION_LEN];
+                let len = self.pack_into(&mut packed);
+                let alias = anchor::alias(packed[0]).expect("every variant has an alias");
+                writer.put(&alias);
+                writer.put(&packed[1..len]);
+            }
+        }
+        writer.len
+    }
+}
+
+/// Writes a `ValidateDeadline` in its shortest encoding
+///
+/// Ties go to the fixed-size encodings.
+fn pack_deadline(deadline: i64, writer: &mut Writer) {
+    let zigzag = ((deadline << 1) ^ (deadline >> 63)) as u64;
+    let varint_len = (64 - zigzag.leading_zeros() as usize).div_ceil(7).max(1);
+    let offset = deadline
+        .checked_sub(COMPACT_DEADLINE_BASE)
+        .and_then(|offset| u32::try_from(offset).ok());
+
+    match offset {
+        Some(offset) if varint_len >= 4 => {
+            writer.put_u8(DEADLINE_U32_TAG);
+            writer.put(&offset.to_le_bytes());
+        }
+        _ if varint_len < 8 => {
+            writer.put_u8(DEADLINE_VARINT_TAG);
+            let mut value = zigzag;
+            while value >= 0x80 {
+                writer.put_u8(value as u8 | 0x80);
+                value >>= 7;
+            }
+            writer.put_u8(value as u8);
+        }
+        _ => {
+            writer.put_u8(0); // instruction discriminator
+            writer.put(&deadline.to_le_bytes());
+        }
+    }
+}
+
+/// Decodes a minimal-length LEB128 varint spanning all of `input`
+fn unpack_varint(input: &[u8]) -> Result<u64, Error> {
+    let (&last, _) = input.split_last().ok_or(Error::InvalidInstructionData)?;
+    // Exactly one terminating byte, no redundant trailing zero groups
+    if input.len() > MAX_VARINT_LEN
+        || last & 0x80 != 0
+        || (last == 0 && input.len() > 1)
+        || input[..input.len() - 1].iter().any(|byte| byte & 0x80 == 0)
+    {
+        return Err(Error::InvalidInstructionData);
+    }
+    // The 10th byte may only carry the top bit of a u64
+    if input.len() == MAX_VARINT_LEN && last > 1 {
+        return Err(Error::InvalidInstructionData);
+    }
+
+    Ok(input.iter().enumerate().fold(0u64, |value, (i, byte)| {
+        value | (u64::from(byte & 0x7f) << (7 * i))
+    }))
+}
+
+/// Returns `input` as an array, failing unless it has exactly `N` bytes
+fn exact<const N: usize>(input: &[u8]) -> Result<[u8; N], Error> {
+    input.try_into().map_err(|_| Error::InvalidInstructionData)
+}
+
+/// Decodes exactly `N` little-endian u64s
+fn unpack_u64s<const N: usize>(mut input: &[u8]) -> Result<[u64; N], Error> {
+    if input.len() != N * 8 {
+        return Err(Error::InvalidInstructionData);
+    }
+    Ok(core::array::from_fn(|_| {
+        let (value, rest) = unpack_u64(input).unwrap();
+        input = rest;
+        value
+    }))
+}
+
+fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), Error> {
+    let (bytes, rest) = input
+        .split_first_chunk::<8>()
+        .ok_or(Error::InvalidInstructionData)?;
+    Ok((u64::from_le_bytes(*bytes), rest))
+}
+
+fn unpack_i64(input: &[u8]) -> Result<(i64, &[u8]), Error> {
+    let (bytes, rest) = input
+        .split_first_chunk::<8>()
+        .ok_or(Error::InvalidInstructionData)?;
+    Ok((i64::from_le_bytes(*bytes), rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expression::Op;
+    use proptest::prelude::*;
+
+    const OPS: [Op; 3] = [Op::TimeLe(1700000000), Op::SlotGe(5), Op::And];
+
+    fn instructions() -> [DeadlineInstruction<'static>; 12] {
+        [
+            DeadlineInstruction::ValidateDeadline { deadline: 0 },
+            DeadlineInstruction::ValidateOracleClock {
+                max_drift: 30,
+                max_staleness_slots: 25,
+                feed_id: [3; 32],
+                min_publish_time: 1700000000,
+            },
+            DeadlineInstruction::ValidateClockSanity { max_drift: 60 },
+            DeadlineInstruction::ValidateDutchAuction {
+                start_price: 10,
+                floor_price: 1,
+                start_time: 1700000000,
+                end_time: 1700000900,
+                mint: [4; 32],
+                destination: [5; 32],
+            },
+            DeadlineInstruction::ValidateSchedule {
+                schedule: [0xaa; 21],
+            },
+            DeadlineInstruction::ValidateExpression {
+                ops: Expression::from_ops(&OPS),
+            },
+            DeadlineInstruction::InitializeCooldown {
+                min_interval: 86_400,
+                seed: 7,
+            },
+            DeadlineInstruction::CheckAndBumpCooldown,
+            DeadlineInstruction::CreateSession {
+                delegate: [2; 32],
+                expires_at: 1800000000,
+                spend_cap: Some(5),
+                seed: 7,
+            },
+            DeadlineInstruction::CreateSession {
+                delegate: [2; 32],
+                expires_at: 0,
+                spend_cap: None,
+                seed: 7,
+            },
+            DeadlineInstruction::RevokeSession,
+            DeadlineInstruction::ValidateSession { amount: 11 },
+        ]
+    }
+
+    #[test]
+    fn test_roundtrip_all_encodings() {
+        let constraints = Constraints {
+            not_before: Some(1700000000),
+            min_slot: Some(1),
+            max_slot: Some(2),
+        };
+        let mut buf = [0u8; MAX_PACKED_LEN];
+        for instruction in instructions() {
+            let len = instruction.pack_into(&mut buf);
+            assert_eq!(len, instruction.packed_len());
+            assert_eq!(Envelope::unpack(&buf[..len]), Ok(instruction.into()));
+
+            let len = instruction.pack_anchor_into(&mut buf);
+            assert_eq!(len, instruction.anchor_packed_len());
+            assert_eq!(Envelope::unpack(&buf[..len]), Ok(instruction.into()));
+
+            let envelope = Envelope {
+                instruction,
+                constraints,
+            };
+            let len = envelope.pack_into(&mut buf);
+            assert_eq!(len, envelope.packed_len());
+            assert_eq!(buf[0], ENVELOPE_TAG);
+            assert_eq!(Envelope::unpack(&buf[..len]), Ok(envelope));
+        }
+    }
+
+    #[test]
+    fn test_max_packed_len() {
+        let ops = [Op::TimeLe(i64::MIN); crate::expression::MAX_OPS];
+        let envelope = Envelope {
+            instruction: DeadlineInstruction::ValidateExpression {
+                ops: Expression::from_ops(&ops),
+            },
+            constraints: Constraints {
+                not_before: Some(0),
+                min_slot: Some(0),
+                max_slot: Some(0),
+            },
+        };
+        let mut buf = [0u8; MAX_PACKED_LEN];
+        assert_eq!(envelope.pack_into(&mut buf), MAX_PACKED_LEN);
+        assert!(envelope.instruction.pack_anchor_into(&mut buf) <= MAX_PACKED_LEN);
+    }
+
+    #[test]
+    fn test_unpack_borrows_bytecode() {
+        let mut buf = [0u8; MAX_PACKED_LEN];
+        let instruction = DeadlineInstruction::ValidateExpression {
+            ops: Expression::from_ops(&OPS),
+        };
+        let len = instruction.pack_into(&mut buf);
+        let Envelope {
+            instruction: DeadlineInstruction::ValidateExpression { ops },
+            ..
+        } = Envelope::unpack(&buf[..len]).unwrap()
+        else {
+            panic!("wrong variant");
+        };
+        assert!(ops.ops().eq(OPS));
+    }
+
+    proptest! {
+        #[test]
+        fn fuzz_unpack_repacks_canonically(data in prop::collection::vec(any::<u8>(), 0..128)) {
+            if let Ok(envelope) = Envelope::unpack(&data) {
+                let mut buf = [0u8; MAX_PACKED_LEN];
+                let len = envelope.pack_into(&mut buf);
+                prop_assert_eq!(Envelope::unpack(&buf[..len]), Ok(envelope));
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! Instruction codec for the deadline validator program
+//!
+//! The single definition of the instruction byte layout, shared by the
+//! on-chain program and all clients. It is `no_std`, allocates nothing and
+//! has no dependencies, so it also builds for wasm and for hardware-wallet
+//! firmware.
+//!
+//! - `pack_into` writes into a caller-provided buffer and returns the
+//!   number of bytes written. `MAX_PACKED_LEN` bytes always suffice.
+//! - `Envelope::unpack` borrows variable-length data, such as expression
+//!   bytecode, from the input instead of copying it.
+//!
+//! ```
+//! use cascade_protocol_deadline_codec::{DeadlineInstruction, Envelope, MAX_PACKED_LEN};
+//!
+//! let mut buf = [0u8; MAX_PACKED_LEN];
+//! let len = DeadlineInstruction::ValidateDeadline { deadline: 1800000000 }.pack_into(&mut buf);
+//! let envelope = Envelope::unpack(&buf[..len]).unwrap();
+//! assert_eq!(
+//!     envelope.instruction,
+//!     DeadlineInstruction::ValidateDeadline { deadline: 1800000000 }
+//! );
+//! ```
+
+#![no_std]
+
+pub mod anchor;
+pub mod expression;
+pub mod instruction;
+
+pub use expression::{Expression, Op};
+pub use instruction::{Constraints, DeadlineInstruction, Envelope, MAX_PACKED_LEN};
+
+/// Errors that may be returned when unpacking instruction data
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Instruction data is malformed or not canonical
+    InvalidInstructionData,
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidInstructionData => f.write_str("Invalid instruction data"),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
@@ -0,0 +1,41 @@
+//! Program error types
+
+use solana_program::program_error::ProgramError;
+use thiserror::Error;
+
+/// Errors that may be returned by the deadline escrow program
+///
+/// Claims after the deadline fail with the deadline validator's
+/// `DeadlineExpired` so clients decode expiry the same way everywhere.
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EscrowError {
+    /// The instruction data is invalid or malformed
+    #[error("Invalid instruction data")]
+    InvalidInstructionData,
+
+    /// The deadline has not passed yet, so the payer cannot refund
+    #[error("Deadline has not passed")]
+    DeadlineNotReached,
+
+    /// An account does not match its expected address
+    #[error("Account does not match the expected address")]
+    IncorrectAccountAddress,
+
+    /// The signer is not allowed to perform this action on the escrow
+    #[error("Unauthorized signer")]
+    Unauthorized,
+
+    /// The escrow account is not initialized or malformed
+    #[error("Invalid escrow account")]
+    InvalidEscrow,
+
+    /// The escrowed amount must be non-zero
+    #[error("Invalid amount")]
+    InvalidAmount,
+}
+
+impl From<EscrowError> for ProgramError {
+    fn from(e: EscrowError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
@@ -0,0 +1,249 @@
+//! Program instructions
+
+use crate::{
+    error::EscrowError,
+    state::{get_escrow_address_and_bump_seed, get_vault_address_and_bump_seed},
+};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+/// Instructions supported by the deadline escrow program
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum EscrowInstruction {
+    /// Locks tokens in a new escrow for `pay_to` until `deadline`
+    ///
+    /// Accounts expected:
+    /// 0. `[w, s]` Payer (funds rent, owns the source token account)
+    /// 1. `[w]` Payer token account
+    /// 2. `[]` Mint
+    /// 3. `[w]` Escrow (PDA `["escrow", payer, seed]`)
+    /// 4. `[w]` Vault (PDA `["vault", escrow]`)
+    /// 5. `[]` Pay-to (merchant)
+    /// 6. `[]` SPL Token program
+    /// 7. `[]` System program
+    ///
+    /// Instruction data layout:
+    /// - Byte 0: Discriminator (0 = Lock)
+    /// - Bytes 1-8: amount (u64, little-endian)
+    /// - Bytes 9-16: deadline (i64, little-endian)
+    /// - Bytes 17-24: seed (u64, little-endian)
+    Lock {
+        /// Amount of tokens to lock
+        amount: u64,
+        /// Unix timestamp deadline (seconds since epoch)
+        /// Use 0 for "never expires"
+        deadline: i64,
+        /// Seed distinguishing escrows of the same payer
+        seed: u64,
+    },
+
+    /// Releases the escrowed tokens to the merchant
+    ///
+    /// Succeeds if: current_time <= deadline (inclusive), like `ValidateDeadline`.
+    /// Closes the vault and escrow, returning rent to the payer.
+    ///
+    /// Accounts expected:
+    /// 0. `[s]` Pay-to (merchant)
+    /// 1. `[w]` Escrow
+    /// 2. `[w]` Vault
+    /// 3. `[w]` Destination token account
+    /// 4. `[]` Mint
+    /// 5. `[w]` Payer (receives rent)
+    /// 6. `[]` SPL Token program
+    ///
+    /// Instruction data layout:
+    /// - Byte 0: Discriminator (1 = Claim)
+    Claim,
+
+    /// Returns the escrowed tokens to the payer after the deadline
+    ///
+    /// Succeeds if: current_time > deadline. Escrows with deadline = 0
+    /// never expire and cannot be refunded. Closes the vault and escrow,
+    /// returning rent to the payer.
+    ///
+    /// Accounts expected:
+    /// 0. `[w, s]` Payer
+    /// 1. `[w]` Escrow
+    /// 2. `[w]` Vault
+    /// 3. `[w]` Payer token account
+    /// 4. `[]` Mint
+    /// 5. `[]` SPL Token program
+    ///
+    /// Instruction data layout:
+    /// - Byte 0: Discriminator (2 = Refund)
+    Refund,
+}
+
+impl EscrowInstruction {
+    /// Unpacks instruction from byte buffer
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let (&variant, rest) = input
+            .split_first()
+            .ok_or(EscrowError::InvalidInstructionData)?;
+
+        Ok(match variant {
+            0 => {
+                let rest: &[u8; 24] = rest
+                    .try_into()
+                    .map_err(|_| EscrowError::InvalidInstructionData)?;
+                let (amount, rest) = rest.split_first_chunk::<8>().unwrap();
+                let (deadline, seed) = rest.split_first_chunk::<8>().unwrap();
+                Self::Lock {
+                    amount: u64::from_le_bytes(*amount),
+                    deadline: i64::from_le_bytes(*deadline),
+                    seed: u64::from_le_bytes(seed.try_into().unwrap()),
+                }
+            }
+            1 if rest.is_empty() => Self::Claim,
+            2 if rest.is_empty() => Self::Refund,
+            _ => return Err(EscrowError::InvalidInstructionData.into()),
+        })
+    }
+
+    /// Packs instruction into byte buffer
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(25);
+        match self {
+            Self::Lock {
+                amount,
+                deadline,
+                seed,
+            } => {
+                buf.push(0);
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&deadline.to_le_bytes());
+                buf.extend_from_slice(&seed.to_le_bytes());
+            }
+            Self::Claim => buf.push(1),
+            Self::Refund => buf.push(2),
+        }
+        buf
+    }
+}
+
+/// Creates a `Lock` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn lock(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    payer_token_account: &Pubkey,
+    mint: &Pubkey,
+    pay_to: &Pubkey,
+    amount: u64,
+    deadline: i64,
+    seed: u64,
+) -> Instruction {
+    let (escrow, _) = get_escrow_address_and_bump_seed(payer, seed, program_id);
+    let (vault, _) = get_vault_address_and_bump_seed(&escrow, program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*payer_token_account, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(escrow, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(*pay_to, false),
+            AccountMeta::new_readonly(spl_token_interface::ID, false),
+            AccountMeta::new_readonly(solana_system_interface::program::ID, false),
+        ],
+        data: EscrowInstruction::Lock {
+            amount,
+            deadline,
+            seed,
+        }
+        .pack(),
+    }
+}
+
+/// Creates a `Claim` instruction
+pub fn claim(
+    program_id: &Pubkey,
+    escrow: &Pubkey,
+    pay_to: &Pubkey,
+    destination_token_account: &Pubkey,
+    mint: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    let (vault, _) = get_vault_address_and_bump_seed(escrow, program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*pay_to, true),
+            AccountMeta::new(*escrow, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(*destination_token_account, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(*payer, false),
+            AccountMeta::new_readonly(spl_token_interface::ID, false),
+        ],
+        data: EscrowInstruction::Claim.pack(),
+    }
+}
+
+/// Creates a `Refund` instruction
+pub fn refund(
+    program_id: &Pubkey,
+    escrow: &Pubkey,
+    payer: &Pubkey,
+    payer_token_account: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    let (vault, _) = get_vault_address_and_bump_seed(escrow, program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*escrow, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(*payer_token_account, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(spl_token_interface::ID, false),
+        ],
+        data: EscrowInstruction::Refund.pack(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instruction_packing_roundtrip() {
+        for instruction in [
+            EscrowInstruction::Lock {
+                amount: 1_000_000,
+                deadline: 1800000000,
+                seed: 42,
+            },
+            EscrowInstruction::Lock {
+                amount: u64::MAX,
+                deadline: i64::MIN,
+                seed: u64::MAX,
+            },
+            EscrowInstruction::Claim,
+            EscrowInstruction::Refund,
+        ] {
+            let packed = instruction.pack();
+            assert_eq!(EscrowInstruction::unpack(&packed).unwrap(), instruction);
+        }
+    }
+
+    #[test]
+    fn test_invalid_instruction_wrong_length() {
+        assert!(EscrowInstruction::unpack(&[]).is_err());
+        assert!(EscrowInstruction::unpack(&[0u8; 24]).is_err());
+        assert!(EscrowInstruction::unpack(&[0u8; 26]).is_err());
+        assert!(EscrowInstruction::unpack(&[1u8, 0]).is_err());
+        assert!(EscrowInstruction::unpack(&[2u8, 0]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_instruction_wrong_variant() {
+        assert!(EscrowInstruction::unpack(&[3u8]).is_err());
+    }
+}
@@ -0,0 +1,45 @@
+//! Deadline-enforced token escrow
+//!
+//! Expresses "the merchant must deliver by T, otherwise the payer can
+//! reclaim": a payer locks SPL tokens for a `pay_to` merchant with a
+//! deadline. The merchant can claim them until the deadline; afterwards
+//! only the payer can take them back.
+//!
+//! ## Time Semantics
+//! Deadlines follow the deadline validator's `ValidateDeadline` exactly:
+//! - Claim succeeds while current_time <= deadline (inclusive)
+//! - Refund succeeds once current_time > deadline
+//! - deadline = 0 never expires, so such escrows can only be claimed
+//!
+//! ## Accounts
+//! - Escrow state: PDA `["escrow", payer, seed]`
+//! - Vault: SPL token account at PDA `["vault", escrow]`, owned by the
+//!   escrow PDA
+
+pub mod error;
+pub mod instruction;
+pub mod processor;
+pub mod state;
+
+#[cfg(not(feature = "no-entrypoint"))]
+mod entrypoint {
+    use super::*;
+    use solana_program::{
+        account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, pubkey::Pubkey,
+    };
+
+    entrypoint!(process_instruction);
+
+    pub fn process_instruction(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        instruction_data: &[u8],
+    ) -> ProgramResult {
+        processor::process_instruction(program_id, accounts, instruction_data)
+    }
+}
+
+// Re-export for downstream users
+pub use solana_program;
+
+solana_program::declare_id!("C57PM6BE4ZhhvLAKBtk2FUASB3oeeDoc24HZ3GWTrkKn");
@@ -0,0 +1,343 @@
+//! Program instruction processor
+
+use crate::{
+    error::EscrowError,
+    instruction::EscrowInstruction,
+    state::{
+        get_escrow_address_and_bump_seed, get_vault_address_and_bump_seed, Escrow, ESCROW_SEED,
+        VAULT_SEED,
+    },
+};
+use cascade_protocol_deadline_validator::processor::{deadline_expired, validate_deadline};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+use spl_token_interface::state::{Account as TokenAccount, Mint};
+
+/// Processes an instruction
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let instruction = EscrowInstruction::unpack(instruction_data)?;
+
+    match instruction {
+        EscrowInstruction::Lock {
+            amount,
+            deadline,
+            seed,
+        } => {
+            msg!("Instruction: Lock");
+            process_lock(program_id, accounts, amount, deadline, seed)
+        }
+        EscrowInstruction::Claim => {
+            msg!("Instruction: Claim");
+            process_claim(program_id, accounts)
+        }
+        EscrowInstruction::Refund => {
+            msg!("Instruction: Refund");
+            process_refund(program_id, accounts)
+        }
+    }
+}
+
+/// Creates the escrow and vault, then moves `amount` tokens into the vault
+fn process_lock(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    deadline: i64,
+    seed: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer_info = next_account_info(account_info_iter)?;
+    let source_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let escrow_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let pay_to_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let _system_program_info = next_account_info(account_info_iter)?;
+
+    if !payer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if amount == 0 {
+        return Err(EscrowError::InvalidAmount.into());
+    }
+    if token_program_info.key != &spl_token_interface::ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let (escrow_address, bump) = get_escrow_address_and_bump_seed(payer_info.key, seed, program_id);
+    let (vault_address, vault_bump) = get_vault_address_and_bump_seed(&escrow_address, program_id);
+    if escrow_info.key != &escrow_address || vault_info.key != &vault_address {
+        return Err(EscrowError::IncorrectAccountAddress.into());
+    }
+
+    let rent = Rent::get()?;
+    let seed_bytes = seed.to_le_bytes();
+    let escrow_seeds: &[&[u8]] = &[ESCROW_SEED, payer_info.key.as_ref(), &seed_bytes, &[bump]];
+    let vault_seeds: &[&[u8]] = &[VAULT_SEED, escrow_address.as_ref(), &[vault_bump]];
+
+    invoke_signed(
+        &solana_system_interface::instruction::create_account(
+            payer_info.key,
+            escrow_info.key,
+            rent.minimum_balance(Escrow::LEN),
+            Escrow::LEN as u64,
+            program_id,
+        ),
+        &[payer_info.clone(), escrow_info.clone()],
+        &[escrow_seeds],
+    )?;
+    invoke_signed(
+        &solana_system_interface::instruction::create_account(
+            payer_info.key,
+            vault_info.key,
+            rent.minimum_balance(TokenAccount::LEN),
+            TokenAccount::LEN as u64,
+            token_program_info.key,
+        ),
+        &[payer_info.clone(), vault_info.clone()],
+        &[vault_seeds],
+    )?;
+    invoke(
+        &spl_token_interface::instruction::initialize_account3(
+            token_program_info.key,
+            vault_info.key,
+            mint_info.key,
+            escrow_info.key,
+        )?,
+        &[vault_info.clone(), mint_info.clone()],
+    )?;
+
+    let decimals = Mint::unpack(&mint_info.try_borrow_data()?)?.decimals;
+    invoke(
+        &spl_token_interface::instruction::transfer_checked(
+            token_program_info.key,
+            source_info.key,
+            mint_info.key,
+            vault_info.key,
+            payer_info.key,
+            &[],
+            amount,
+            decimals,
+        )?,
+        &[
+            source_info.clone(),
+            mint_info.clone(),
+            vault_info.clone(),
+            payer_info.clone(),
+        ],
+    )?;
+
+    Escrow {
+        payer: *payer_info.key,
+        pay_to: *pay_to_info.key,
+        mint: *mint_info.key,
+        amount,
+        deadline,
+        seed,
+        bump,
+        vault_bump,
+    }
+    .pack(&mut escrow_info.try_borrow_mut_data()?)?;
+
+    msg!(
+        "Escrow locked: amount={}, pay_to={}, deadline={}",
+        amount,
+        pay_to_info.key,
+        deadline
+    );
+    Ok(())
+}
+
+/// Releases the vault to the merchant while the deadline holds
+///
+/// ## Behavior
+/// Same as `ValidateDeadline`: succeeds while current_time <= deadline.
+fn process_claim(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pay_to_info = next_account_info(account_info_iter)?;
+    let escrow_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    let escrow = load_escrow(program_id, escrow_info, vault_info, mint_info, payer_info)?;
+    if !pay_to_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if pay_to_info.key != &escrow.pay_to {
+        return Err(EscrowError::Unauthorized.into());
+    }
+
+    let clock = Clock::get()?;
+    validate_deadline(escrow.deadline, clock.unix_timestamp)?;
+
+    release(
+        &escrow,
+        escrow_info,
+        vault_info,
+        destination_info,
+        mint_info,
+        payer_info,
+        token_program_info,
+    )
+}
+
+/// Returns the vault to the payer once the deadline has passed
+///
+/// ## Behavior
+/// The inverse of `ValidateDeadline`: succeeds once current_time > deadline.
+/// Escrows with deadline = 0 never expire and cannot be refunded.
+fn process_refund(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer_info = next_account_info(account_info_iter)?;
+    let escrow_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    let escrow = load_escrow(program_id, escrow_info, vault_info, mint_info, payer_info)?;
+    if !payer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let clock = Clock::get()?;
+    if !deadline_expired(escrow.deadline, clock.unix_timestamp) {
+        msg!(
+            "Deadline not reached: current={}, deadline={}",
+            clock.unix_timestamp,
+            escrow.deadline
+        );
+        return Err(EscrowError::DeadlineNotReached.into());
+    }
+
+    release(
+        &escrow,
+        escrow_info,
+        vault_info,
+        destination_info,
+        mint_info,
+        payer_info,
+        token_program_info,
+    )
+}
+
+/// Loads the escrow and checks the accounts derived from it
+fn load_escrow(
+    program_id: &Pubkey,
+    escrow_info: &AccountInfo,
+    vault_info: &AccountInfo,
+    mint_info: &AccountInfo,
+    payer_info: &AccountInfo,
+) -> Result<Escrow, ProgramError> {
+    if escrow_info.owner != program_id {
+        return Err(EscrowError::InvalidEscrow.into());
+    }
+    let escrow = Escrow::unpack(&escrow_info.try_borrow_data()?)?;
+
+    let vault_address = Pubkey::create_program_address(
+        &[VAULT_SEED, escrow_info.key.as_ref(), &[escrow.vault_bump]],
+        program_id,
+    )?;
+    if vault_info.key != &vault_address
+        || mint_info.key != &escrow.mint
+        || payer_info.key != &escrow.payer
+    {
+        return Err(EscrowError::IncorrectAccountAddress.into());
+    }
+    Ok(escrow)
+}
+
+/// Moves the vault balance to `destination_info`, then closes the vault
+/// and the escrow, returning all rent to the payer
+///
+/// Releases the vault's actual balance rather than `escrow.amount`: anyone
+/// can transfer tokens into the vault, and the token program refuses to
+/// close an account with a balance left.
+fn release<'a>(
+    escrow: &Escrow,
+    escrow_info: &AccountInfo<'a>,
+    vault_info: &AccountInfo<'a>,
+    destination_info: &AccountInfo<'a>,
+    mint_info: &AccountInfo<'a>,
+    payer_info: &AccountInfo<'a>,
+    token_program_info: &AccountInfo<'a>,
+) -> ProgramResult {
+    if token_program_info.key != &spl_token_interface::ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let seed_bytes = escrow.seed.to_le_bytes();
+    let escrow_seeds: &[&[u8]] = &[
+        ESCROW_SEED,
+        escrow.payer.as_ref(),
+        &seed_bytes,
+        &[escrow.bump],
+    ];
+    let decimals = Mint::unpack(&mint_info.try_borrow_data()?)?.decimals;
+    let amount = TokenAccount::unpack(&vault_info.try_borrow_data()?)?.amount;
+
+    invoke_signed(
+        &spl_token_interface::instruction::transfer_checked(
+            token_program_info.key,
+            vault_info.key,
+            mint_info.key,
+            destination_info.key,
+            escrow_info.key,
+            &[],
+            amount,
+            decimals,
+        )?,
+        &[
+            vault_info.clone(),
+            mint_info.clone(),
+            destination_info.clone(),
+            escrow_info.clone(),
+        ],
+        &[escrow_seeds],
+    )?;
+    invoke_signed(
+        &spl_token_interface::instruction::close_account(
+            token_program_info.key,
+            vault_info.key,
+            payer_info.key,
+            escrow_info.key,
+            &[],
+        )?,
+        &[vault_info.clone(), payer_info.clone(), escrow_info.clone()],
+        &[escrow_seeds],
+    )?;
+
+    let escrow_lamports = escrow_info.lamports();
+    **payer_info.try_borrow_mut_lamports()? = payer_info
+        .lamports()
+        .checked_add(escrow_lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **escrow_info.try_borrow_mut_lamports()? = 0;
+    escrow_info.resize(0)?;
+    escrow_info.assign(&solana_system_interface::program::ID);
+
+    msg!(
+        "Escrow released: amount={}, destination={}",
+        amount,
+        destination_info.key
+    );
+    Ok(())
+}
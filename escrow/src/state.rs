@@ -0,0 +1,130 @@
+//! Program state
+
+use crate::error::EscrowError;
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+/// Seed prefix for escrow state PDAs
+pub const ESCROW_SEED: &[u8] = b"escrow";
+
+/// Seed prefix for vault token account PDAs
+pub const VAULT_SEED: &[u8] = b"vault";
+
+/// Returns the escrow address and bump seed for a payer and seed
+pub fn get_escrow_address_and_bump_seed(
+    payer: &Pubkey,
+    seed: u64,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[ESCROW_SEED, payer.as_ref(), &seed.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Returns the vault address and bump seed for an escrow
+pub fn get_vault_address_and_bump_seed(escrow: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_SEED, escrow.as_ref()], program_id)
+}
+
+/// Escrow state
+///
+/// Layout:
+/// - Bytes 0-31: payer (Pubkey)
+/// - Bytes 32-63: pay_to (Pubkey)
+/// - Bytes 64-95: mint (Pubkey)
+/// - Bytes 96-103: amount (u64, little-endian)
+/// - Bytes 104-111: deadline (i64, little-endian)
+/// - Bytes 112-119: seed (u64, little-endian)
+/// - Byte 120: bump seed
+/// - Byte 121: vault bump seed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Escrow {
+    /// Account that locked the tokens and receives refunds and rent
+    pub payer: Pubkey,
+    /// Merchant allowed to claim before the deadline
+    pub pay_to: Pubkey,
+    /// Mint of the escrowed tokens
+    pub mint: Pubkey,
+    /// Amount of tokens locked in the vault
+    pub amount: u64,
+    /// Unix timestamp deadline (seconds since epoch)
+    /// Use 0 for "never expires"
+    pub deadline: i64,
+    /// Seed distinguishing escrows of the same payer
+    pub seed: u64,
+    /// Bump seed of the escrow PDA
+    pub bump: u8,
+    /// Bump seed of the vault PDA
+    pub vault_bump: u8,
+}
+
+impl Escrow {
+    /// Serialized size of the escrow account
+    pub const LEN: usize = 122;
+
+    /// Unpacks the escrow from account data
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let input: &[u8; Self::LEN] = input.try_into().map_err(|_| EscrowError::InvalidEscrow)?;
+        let (payer, rest) = input.split_first_chunk::<32>().unwrap();
+        let (pay_to, rest) = rest.split_first_chunk::<32>().unwrap();
+        let (mint, rest) = rest.split_first_chunk::<32>().unwrap();
+        let (amount, rest) = rest.split_first_chunk::<8>().unwrap();
+        let (deadline, rest) = rest.split_first_chunk::<8>().unwrap();
+        let (seed, rest) = rest.split_first_chunk::<8>().unwrap();
+
+        Ok(Self {
+            payer: Pubkey::new_from_array(*payer),
+            pay_to: Pubkey::new_from_array(*pay_to),
+            mint: Pubkey::new_from_array(*mint),
+            amount: u64::from_le_bytes(*amount),
+            deadline: i64::from_le_bytes(*deadline),
+            seed: u64::from_le_bytes(*seed),
+            bump: rest[0],
+            vault_bump: rest[1],
+        })
+    }
+
+    /// Packs the escrow into account data
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        if dst.len() != Self::LEN {
+            return Err(EscrowError::InvalidEscrow.into());
+        }
+        dst[0..32].copy_from_slice(self.payer.as_ref());
+        dst[32..64].copy_from_slice(self.pay_to.as_ref());
+        dst[64..96].copy_from_slice(self.mint.as_ref());
+        dst[96..104].copy_from_slice(&self.amount.to_le_bytes());
+        dst[104..112].copy_from_slice(&self.deadline.to_le_bytes());
+        dst[112..120].copy_from_slice(&self.seed.to_le_bytes());
+        dst[120] = self.bump;
+        dst[121] = self.vault_bump;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escrow_roundtrip() {
+        let escrow = Escrow {
+            payer: Pubkey::new_from_array([1; 32]),
+            pay_to: Pubkey::new_from_array([2; 32]),
+            mint: Pubkey::new_from_array([3; 32]),
+            amount: 1_000_000,
+            deadline: 1800000000,
+            seed: 7,
+            bump: 254,
+            vault_bump: 253,
+        };
+        let mut data = [0u8; Escrow::LEN];
+        escrow.pack(&mut data).unwrap();
+        assert_eq!(Escrow::unpack(&data).unwrap(), escrow);
+    }
+
+    #[test]
+    fn test_escrow_wrong_length() {
+        assert!(Escrow::unpack(&[0u8; Escrow::LEN - 1]).is_err());
+        assert!(Escrow::unpack(&[0u8; Escrow::LEN + 1]).is_err());
+    }
+}
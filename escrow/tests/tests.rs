@@ -0,0 +1,452 @@
+//! Integration tests for the deadline escrow program
+//!
+//! These tests run the escrow under Mollusk with the SPL Token program
+//! loaded, mirroring the deadline validator's boundary cases for claims
+//! and refunds.
+//!
+//! ## Running Tests
+//! For clean output without verbose DEBUG logs, use:
+//! ```bash
+//! RUST_LOG=off cargo test --test tests
+//! ```
+
+use {
+    cascade_protocol_deadline_escrow::{
+        error::EscrowError,
+        id,
+        instruction::{claim, lock, refund},
+        solana_program::{program_option::COption, program_pack::Pack},
+        state::{get_escrow_address_and_bump_seed, get_vault_address_and_bump_seed, Escrow},
+    },
+    cascade_protocol_deadline_validator::error::DeadlineError,
+    mollusk_svm::{program, result::Check, Mollusk},
+    mollusk_svm_programs_token::token,
+    solana_account::Account,
+    solana_instruction::Instruction,
+    solana_program_error::ProgramError,
+    solana_pubkey::Pubkey,
+    spl_token_interface::state::{Account as TokenAccount, AccountState, Mint},
+};
+
+const DEADLINE: i64 = 1800000000; // Jan 15, 2027
+const AMOUNT: u64 = 1_000_000;
+const SEED: u64 = 7;
+
+/// Helper function to create a Mollusk instance with the escrow and SPL Token
+fn setup_mollusk(unix_timestamp: i64) -> Mollusk {
+    std::env::set_var("SBF_OUT_DIR", "../target/deploy");
+    let mut mollusk = Mollusk::new(&id(), "cascade_protocol_deadline_escrow");
+    token::add_program(&mut mollusk);
+    mollusk.sysvars.clock.unix_timestamp = unix_timestamp;
+    mollusk
+}
+
+/// Keys involved in an escrow
+struct Fixture {
+    payer: Pubkey,
+    payer_token_account: Pubkey,
+    pay_to: Pubkey,
+    pay_to_token_account: Pubkey,
+    mint: Pubkey,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        Self {
+            payer: Pubkey::new_unique(),
+            payer_token_account: Pubkey::new_unique(),
+            pay_to: Pubkey::new_unique(),
+            pay_to_token_account: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+        }
+    }
+
+    fn escrow(&self) -> Pubkey {
+        get_escrow_address_and_bump_seed(&self.payer, SEED, &id()).0
+    }
+
+    fn vault(&self) -> Pubkey {
+        get_vault_address_and_bump_seed(&self.escrow(), &id()).0
+    }
+
+    /// Accounts before the escrow is created
+    fn accounts(&self) -> Vec<(Pubkey, Account)> {
+        let system_account =
+            |lamports| Account::new(lamports, 0, &solana_system_interface::program::ID);
+        vec![
+            (self.payer, system_account(10_000_000_000)),
+            (self.pay_to, system_account(1_000_000_000)),
+            (
+                self.mint,
+                token::create_account_for_mint(Mint {
+                    mint_authority: COption::None,
+                    supply: AMOUNT,
+                    decimals: 6,
+                    is_initialized: true,
+                    freeze_authority: COption::None,
+                }),
+            ),
+            (
+                self.payer_token_account,
+                self.token_account(self.payer, AMOUNT),
+            ),
+            (
+                self.pay_to_token_account,
+                self.token_account(self.pay_to, 0),
+            ),
+            (self.escrow(), system_account(0)),
+            (self.vault(), system_account(0)),
+            program::keyed_account_for_system_program(),
+            token::keyed_account(),
+            (id(), program::create_program_account_loader_v3(&id())),
+        ]
+    }
+
+    fn token_account(&self, owner: Pubkey, amount: u64) -> Account {
+        token::create_account_for_token_account(TokenAccount {
+            mint: self.mint,
+            owner,
+            amount,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        })
+    }
+
+    fn lock(&self, deadline: i64) -> Instruction {
+        lock(
+            &id(),
+            &self.payer,
+            &self.payer_token_account,
+            &self.mint,
+            &self.pay_to,
+            AMOUNT,
+            deadline,
+            SEED,
+        )
+    }
+
+    fn claim(&self) -> Instruction {
+        claim(
+            &id(),
+            &self.escrow(),
+            &self.pay_to,
+            &self.pay_to_token_account,
+            &self.mint,
+            &self.payer,
+        )
+    }
+
+    fn refund(&self) -> Instruction {
+        refund(
+            &id(),
+            &self.escrow(),
+            &self.payer,
+            &self.payer_token_account,
+            &self.mint,
+        )
+    }
+}
+
+/// Locks the escrow and returns the resulting accounts
+fn locked(mollusk: &Mollusk, fixture: &Fixture, deadline: i64) -> Vec<(Pubkey, Account)> {
+    mollusk
+        .process_and_validate_instruction(
+            &fixture.lock(deadline),
+            &fixture.accounts(),
+            &[Check::success()],
+        )
+        .resulting_accounts
+}
+
+fn account<'a>(accounts: &'a [(Pubkey, Account)], key: &Pubkey) -> &'a Account {
+    &accounts.iter().find(|(k, _)| k == key).unwrap().1
+}
+
+fn token_amount(accounts: &[(Pubkey, Account)], key: &Pubkey) -> u64 {
+    TokenAccount::unpack(&account(accounts, key).data)
+        .unwrap()
+        .amount
+}
+
+#[test]
+fn test_lock_moves_tokens_into_vault() {
+    let mollusk = setup_mollusk(1700000000);
+    let fixture = Fixture::new();
+
+    let accounts = locked(&mollusk, &fixture, DEADLINE);
+
+    assert_eq!(token_amount(&accounts, &fixture.payer_token_account), 0);
+    assert_eq!(token_amount(&accounts, &fixture.vault()), AMOUNT);
+
+    let escrow = Escrow::unpack(&account(&accounts, &fixture.escrow()).data).unwrap();
+    assert_eq!(escrow.payer, fixture.payer);
+    assert_eq!(escrow.pay_to, fixture.pay_to);
+    assert_eq!(escrow.amount, AMOUNT);
+    assert_eq!(escrow.deadline, DEADLINE);
+}
+
+#[test]
+fn test_lock_zero_amount_fails() {
+    let mollusk = setup_mollusk(1700000000);
+    let fixture = Fixture::new();
+
+    let mut instruction = fixture.lock(DEADLINE);
+    instruction.data[1..9].copy_from_slice(&0u64.to_le_bytes());
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &fixture.accounts(),
+        &[Check::err(ProgramError::from(EscrowError::InvalidAmount))],
+    );
+}
+
+#[test]
+fn test_claim_before_deadline_succeeds() {
+    let mollusk = setup_mollusk(DEADLINE - 3600);
+    let fixture = Fixture::new();
+    let accounts = locked(&mollusk, &fixture, DEADLINE);
+
+    let result = mollusk.process_and_validate_instruction(
+        &fixture.claim(),
+        &accounts,
+        &[
+            Check::success(),
+            Check::account(&fixture.escrow()).closed().build(),
+            Check::account(&fixture.vault()).closed().build(),
+        ],
+    );
+
+    assert_eq!(
+        token_amount(&result.resulting_accounts, &fixture.pay_to_token_account),
+        AMOUNT
+    );
+}
+
+#[test]
+fn test_claim_after_donation_releases_vault_balance() {
+    let mollusk = setup_mollusk(DEADLINE - 3600);
+    let fixture = Fixture::new();
+    let mut accounts = locked(&mollusk, &fixture, DEADLINE);
+
+    // Someone transfers extra tokens into the vault after the lock
+    let vault = accounts
+        .iter_mut()
+        .find(|(key, _)| key == &fixture.vault())
+        .unwrap();
+    let mut state = TokenAccount::unpack(&vault.1.data).unwrap();
+    state.amount += 500;
+    TokenAccount::pack(state, &mut vault.1.data).unwrap();
+
+    let result = mollusk.process_and_validate_instruction(
+        &fixture.claim(),
+        &accounts,
+        &[
+            Check::success(),
+            Check::account(&fixture.escrow()).closed().build(),
+            Check::account(&fixture.vault()).closed().build(),
+        ],
+    );
+
+    assert_eq!(
+        token_amount(&result.resulting_accounts, &fixture.pay_to_token_account),
+        AMOUNT + 500
+    );
+}
+
+#[test]
+fn test_claim_boundary_exact_match() {
+    let mollusk = setup_mollusk(DEADLINE);
+    let fixture = Fixture::new();
+    let accounts = locked(&mollusk, &fixture, DEADLINE);
+
+    // Deadline exactly matching current time should succeed (inclusive behavior)
+    mollusk.process_and_validate_instruction(&fixture.claim(), &accounts, &[Check::success()]);
+}
+
+#[test]
+fn test_claim_boundary_one_second_past() {
+    let mut mollusk = setup_mollusk(DEADLINE - 3600);
+    let fixture = Fixture::new();
+    let accounts = locked(&mollusk, &fixture, DEADLINE);
+
+    mollusk.sysvars.clock.unix_timestamp = DEADLINE + 1;
+
+    mollusk.process_and_validate_instruction(
+        &fixture.claim(),
+        &accounts,
+        &[Check::err(ProgramError::from(
+            DeadlineError::DeadlineExpired,
+        ))],
+    );
+}
+
+#[test]
+fn test_claim_by_other_signer_fails() {
+    let mollusk = setup_mollusk(DEADLINE - 3600);
+    let fixture = Fixture::new();
+    let mut accounts = locked(&mollusk, &fixture, DEADLINE);
+
+    let impostor = Pubkey::new_unique();
+    accounts.push((
+        impostor,
+        Account::new(1_000_000_000, 0, &solana_system_interface::program::ID),
+    ));
+    let mut instruction = fixture.claim();
+    instruction.accounts[0].pubkey = impostor;
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &accounts,
+        &[Check::err(ProgramError::from(EscrowError::Unauthorized))],
+    );
+}
+
+#[test]
+fn test_claim_without_signature_fails() {
+    let mollusk = setup_mollusk(DEADLINE - 3600);
+    let fixture = Fixture::new();
+    let accounts = locked(&mollusk, &fixture, DEADLINE);
+
+    let mut instruction = fixture.claim();
+    instruction.accounts[0].is_signer = false;
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &accounts,
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_refund_before_deadline_fails() {
+    let mollusk = setup_mollusk(DEADLINE - 3600);
+    let fixture = Fixture::new();
+    let accounts = locked(&mollusk, &fixture, DEADLINE);
+
+    mollusk.process_and_validate_instruction(
+        &fixture.refund(),
+        &accounts,
+        &[Check::err(ProgramError::from(
+            EscrowError::DeadlineNotReached,
+        ))],
+    );
+}
+
+#[test]
+fn test_refund_boundary_exact_match_fails() {
+    let mollusk = setup_mollusk(DEADLINE);
+    let fixture = Fixture::new();
+    let accounts = locked(&mollusk, &fixture, DEADLINE);
+
+    // At the deadline the merchant can still claim, so no refund yet
+    mollusk.process_and_validate_instruction(
+        &fixture.refund(),
+        &accounts,
+        &[Check::err(ProgramError::from(
+            EscrowError::DeadlineNotReached,
+        ))],
+    );
+}
+
+#[test]
+fn test_refund_boundary_one_second_past() {
+    let mut mollusk = setup_mollusk(DEADLINE - 3600);
+    let fixture = Fixture::new();
+    let accounts = locked(&mollusk, &fixture, DEADLINE);
+
+    mollusk.sysvars.clock.unix_timestamp = DEADLINE + 1;
+
+    let result = mollusk.process_and_validate_instruction(
+        &fixture.refund(),
+        &accounts,
+        &[
+            Check::success(),
+            Check::account(&fixture.escrow()).closed().build(),
+            Check::account(&fixture.vault()).closed().build(),
+        ],
+    );
+
+    assert_eq!(
+        token_amount(&result.resulting_accounts, &fixture.payer_token_account),
+        AMOUNT
+    );
+}
+
+#[test]
+fn test_refund_by_merchant_fails() {
+    let mut mollusk = setup_mollusk(DEADLINE - 3600);
+    let fixture = Fixture::new();
+    let accounts = locked(&mollusk, &fixture, DEADLINE);
+
+    mollusk.sysvars.clock.unix_timestamp = DEADLINE + 1;
+
+    let mut instruction = fixture.refund();
+    instruction.accounts[0].pubkey = fixture.pay_to;
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &accounts,
+        &[Check::err(ProgramError::from(
+            EscrowError::IncorrectAccountAddress,
+        ))],
+    );
+}
+
+#[test]
+fn test_zero_deadline_never_refundable() {
+    let mut mollusk = setup_mollusk(1700000000);
+    let fixture = Fixture::new();
+    let accounts = locked(&mollusk, &fixture, 0);
+
+    mollusk.sysvars.clock.unix_timestamp = i64::MAX;
+
+    mollusk.process_and_validate_instruction(
+        &fixture.refund(),
+        &accounts,
+        &[Check::err(ProgramError::from(
+            EscrowError::DeadlineNotReached,
+        ))],
+    );
+    mollusk.process_and_validate_instruction(&fixture.claim(), &accounts, &[Check::success()]);
+}
+
+#[test]
+fn test_negative_deadline_always_refundable() {
+    let mollusk = setup_mollusk(1700000000);
+    let fixture = Fixture::new();
+    let accounts = locked(&mollusk, &fixture, -1000);
+
+    mollusk.process_and_validate_instruction(
+        &fixture.claim(),
+        &accounts,
+        &[Check::err(ProgramError::from(
+            DeadlineError::DeadlineExpired,
+        ))],
+    );
+    mollusk.process_and_validate_instruction(&fixture.refund(), &accounts, &[Check::success()]);
+}
+
+#[test]
+fn test_claim_after_refund_fails() {
+    let mut mollusk = setup_mollusk(DEADLINE - 3600);
+    let fixture = Fixture::new();
+    let accounts = locked(&mollusk, &fixture, DEADLINE);
+
+    mollusk.sysvars.clock.unix_timestamp = DEADLINE + 1;
+    let refund = fixture.refund();
+    let claim = fixture.claim();
+
+    mollusk.process_and_validate_instruction_chain(
+        &[
+            (&refund, &[Check::success()]),
+            (
+                &claim,
+                &[Check::err(ProgramError::from(EscrowError::InvalidEscrow))],
+            ),
+        ],
+        &accounts,
+    );
+}
@@ -4,7 +4,8 @@
 //! Results are written to: ../target/benches/compute_units.md (gitignored)
 
 use {
-    cascade_protocol_deadline_validator::{id, instruction::DeadlineInstruction},
+    cascade_protocol_deadline_validator::id,
+    cascade_protocol_deadline_validator_client::{instruction::validate_deadline, Deadline},
     mollusk_svm::Mollusk,
     mollusk_svm_bencher::MolluskComputeUnitBencher,
 };
@@ -18,23 +19,9 @@ fn main() {
     let mollusk = Mollusk::new(&id(), "cascade_protocol_deadline_validator");
 
     // Create test instructions
-    let future_deadline_ix = {
-        let instruction_data = DeadlineInstruction::ValidateDeadline {
-            deadline: 1800000000,
-        }
-        .pack();
-        solana_instruction::Instruction::new_with_bytes(id(), &instruction_data, vec![])
-    };
-
-    let zero_deadline_ix = {
-        let instruction_data = DeadlineInstruction::ValidateDeadline { deadline: 0 }.pack();
-        solana_instruction::Instruction::new_with_bytes(id(), &instruction_data, vec![])
-    };
-
-    let max_deadline_ix = {
-        let instruction_data = DeadlineInstruction::ValidateDeadline { deadline: i64::MAX }.pack();
-        solana_instruction::Instruction::new_with_bytes(id(), &instruction_data, vec![])
-    };
+    let future_deadline_ix = validate_deadline(1800000000);
+    let zero_deadline_ix = validate_deadline(Deadline::NEVER);
+    let max_deadline_ix = validate_deadline(i64::MAX);
 
     // Run benchmarks
     MolluskComputeUnitBencher::new(mollusk)
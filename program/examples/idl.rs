@@ -0,0 +1,8 @@
+//! Prints the Anchor IDL of the program
+//!
+//! Usage: `cargo run -p cascade-protocol-deadline-validator --example idl > idl/deadline_validator.json`
+
+fn main() {
+    let idl = cascade_protocol_deadline_validator::idl::idl();
+    println!("{}", serde_json::to_string_pretty(&idl).unwrap());
+}
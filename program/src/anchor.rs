@@ -0,0 +1,18 @@
+//! Anchor-compatible instruction discriminators, re-exported from
+//! `cascade_protocol_deadline_codec::anchor`
+
+pub use cascade_protocol_deadline_codec::anchor::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::hash::hash;
+
+    #[test]
+    fn test_aliases_match_anchor_sighash() {
+        for (name, discriminator, _) in ALIASES {
+            let sighash = hash(format!("global:{name}").as_bytes());
+            assert_eq!(sighash.as_ref()[..8], discriminator, "{name}");
+        }
+    }
+}
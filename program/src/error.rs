@@ -1,8 +1,18 @@
 //! Program error types
+//!
+//! Errors are returned as `ProgramError::Custom(ERROR_CODE_BASE + index)`,
+//! where index is the position in `DeadlineError::ALL`, so they do not
+//! collide with the small codes used by most programs. `ValidateDeadline`
+//! with the legacy discriminator 0 keeps returning the bare index, as it
+//! did before the base was introduced.
 
 use solana_program::program_error::ProgramError;
+use solana_program_error::ToStr;
 use thiserror::Error;
 
+/// Base of the custom error codes, "DEAD" in hex
+pub const ERROR_CODE_BASE: u32 = 0xDEAD_0000;
+
 /// Errors that may be returned by the deadline validator program
 #[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
 pub enum DeadlineError {
@@ -13,10 +23,272 @@ pub enum DeadlineError {
     /// The instruction data is invalid or malformed
     #[error("Invalid instruction data")]
     InvalidInstructionData,
+
+    /// The cluster clock differs from the reference time by more than allowed
+    #[error("Clock drift exceeds tolerance")]
+    ClockDrift,
+
+    /// The oracle update is older than the allowed staleness
+    #[error("Oracle update is stale")]
+    OracleStale,
+
+    /// The oracle account has the wrong owner or an invalid layout
+    #[error("Invalid oracle account")]
+    InvalidOracleAccount,
+
+    /// No token transfer in the transaction pays the current auction price
+    #[error("Insufficient payment")]
+    InsufficientPayment,
+
+    /// The current time is outside the allowed weekly schedule
+    #[error("Outside allowed schedule")]
+    OutsideSchedule,
+
+    /// The time-predicate expression evaluated to false
+    #[error("Expression not satisfied")]
+    ExpressionFalse,
+
+    /// The minimum interval since the last action has not elapsed
+    #[error("Cooldown still active")]
+    CooldownActive,
+
+    /// The cooldown account is not initialized, malformed or misplaced
+    #[error("Invalid cooldown account")]
+    InvalidCooldown,
+
+    /// The signer is not the authority of the account
+    #[error("Unauthorized signer")]
+    Unauthorized,
+
+    /// The session has expired (current time > expires_at)
+    #[error("Session has expired")]
+    SessionExpired,
+
+    /// The owner has revoked the session
+    #[error("Session has been revoked")]
+    SessionRevoked,
+
+    /// The amount would exceed the session's spend cap
+    #[error("Session spend cap exceeded")]
+    SpendCapExceeded,
+
+    /// The session account is not initialized, malformed or misplaced
+    #[error("Invalid session account")]
+    InvalidSession,
+
+    /// The current time is before the envelope's not_before
+    #[error("Not yet valid")]
+    NotYetValid,
+
+    /// The current slot is outside the envelope's slot range
+    #[error("Slot out of range")]
+    SlotOutOfRange,
+
+    /// The session amount does not match the delegate's token transfers
+    #[error("Session transfer mismatch")]
+    SessionTransferMismatch,
+}
+
+impl DeadlineError {
+    /// All errors, ordered by code
+    pub const ALL: [DeadlineError; 18] = [
+        DeadlineError::DeadlineExpired,
+        DeadlineError::InvalidInstructionData,
+        DeadlineError::ClockDrift,
+        DeadlineError::OracleStale,
+        DeadlineError::InvalidOracleAccount,
+        DeadlineError::InsufficientPayment,
+        DeadlineError::OutsideSchedule,
+        DeadlineError::ExpressionFalse,
+        DeadlineError::CooldownActive,
+        DeadlineError::InvalidCooldown,
+        DeadlineError::Unauthorized,
+        DeadlineError::SessionExpired,
+        DeadlineError::SessionRevoked,
+        DeadlineError::SpendCapExceeded,
+        DeadlineError::InvalidSession,
+        DeadlineError::NotYetValid,
+        DeadlineError::SlotOutOfRange,
+        DeadlineError::SessionTransferMismatch,
+    ];
+
+    /// Returns the custom error code
+    pub fn code(self) -> u32 {
+        ERROR_CODE_BASE + self as u32
+    }
+
+    /// Returns the custom error code of `ValidateDeadline` with the legacy
+    /// discriminator 0
+    pub fn legacy_code(self) -> u32 {
+        self as u32
+    }
 }
 
 impl From<DeadlineError> for ProgramError {
     fn from(e: DeadlineError) -> Self {
-        ProgramError::Custom(e as u32)
+        ProgramError::Custom(e.code())
+    }
+}
+
+impl TryFrom<u32> for DeadlineError {
+    /// The unknown code
+    type Error = u32;
+
+    /// Returns the error with the given `ProgramError::Custom` code, either
+    /// a code or a legacy code
+    fn try_from(code: u32) -> Result<Self, u32> {
+        let index = code.checked_sub(ERROR_CODE_BASE).unwrap_or(code);
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| Self::ALL.get(index))
+            .copied()
+            .ok_or(code)
+    }
+}
+
+/// Replaces the code of a `DeadlineError` with its legacy code
+///
+/// Other errors are returned unchanged.
+pub fn to_legacy(error: ProgramError) -> ProgramError {
+    match error {
+        ProgramError::Custom(code) if code >= ERROR_CODE_BASE => {
+            match DeadlineError::try_from(code) {
+                Ok(error) => ProgramError::Custom(error.legacy_code()),
+                Err(_) => error,
+            }
+        }
+        error => error,
+    }
+}
+
+impl ToStr for DeadlineError {
+    fn to_str(&self) -> &'static str {
+        match self {
+            DeadlineError::DeadlineExpired => "Deadline has expired",
+            DeadlineError::InvalidInstructionData => "Invalid instruction data",
+            DeadlineError::ClockDrift => "Clock drift exceeds tolerance",
+            DeadlineError::OracleStale => "Oracle update is stale",
+            DeadlineError::InvalidOracleAccount => "Invalid oracle account",
+            DeadlineError::InsufficientPayment => "Insufficient payment",
+            DeadlineError::OutsideSchedule => "Outside allowed schedule",
+            DeadlineError::ExpressionFalse => "Expression not satisfied",
+            DeadlineError::CooldownActive => "Cooldown still active",
+            DeadlineError::InvalidCooldown => "Invalid cooldown account",
+            DeadlineError::Unauthorized => "Unauthorized signer",
+            DeadlineError::SessionExpired => "Session has expired",
+            DeadlineError::SessionRevoked => "Session has been revoked",
+            DeadlineError::SpendCapExceeded => "Session spend cap exceeded",
+            DeadlineError::InvalidSession => "Invalid session account",
+            DeadlineError::NotYetValid => "Not yet valid",
+            DeadlineError::SlotOutOfRange => "Slot out of range",
+            DeadlineError::SessionTransferMismatch => "Session transfer mismatch",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_ordered_by_code() {
+        for (code, error) in DeadlineError::ALL.into_iter().enumerate() {
+            assert_eq!(error as usize, code);
+            // Stops compiling when a variant is added, as a reminder to
+            // extend ALL
+            match error {
+                DeadlineError::DeadlineExpired
+                | DeadlineError::InvalidInstructionData
+                | DeadlineError::ClockDrift
+                | DeadlineError::OracleStale
+                | DeadlineError::InvalidOracleAccount
+                | DeadlineError::InsufficientPayment
+                | DeadlineError::OutsideSchedule
+                | DeadlineError::ExpressionFalse
+                | DeadlineError::CooldownActive
+                | DeadlineError::InvalidCooldown
+                | DeadlineError::Unauthorized
+                | DeadlineError::SessionExpired
+                | DeadlineError::SessionRevoked
+                | DeadlineError::SpendCapExceeded
+                | DeadlineError::InvalidSession
+                | DeadlineError::NotYetValid
+                | DeadlineError::SlotOutOfRange
+                | DeadlineError::SessionTransferMismatch => {}
+            }
+        }
+    }
+
+    /// The published code table: error, legacy code, code
+    const CODES: [(DeadlineError, u32, u32); 18] = [
+        (DeadlineError::DeadlineExpired, 0, 0xDEAD_0000),
+        (DeadlineError::InvalidInstructionData, 1, 0xDEAD_0001),
+        (DeadlineError::ClockDrift, 2, 0xDEAD_0002),
+        (DeadlineError::OracleStale, 3, 0xDEAD_0003),
+        (DeadlineError::InvalidOracleAccount, 4, 0xDEAD_0004),
+        (DeadlineError::InsufficientPayment, 5, 0xDEAD_0005),
+        (DeadlineError::OutsideSchedule, 6, 0xDEAD_0006),
+        (DeadlineError::ExpressionFalse, 7, 0xDEAD_0007),
+        (DeadlineError::CooldownActive, 8, 0xDEAD_0008),
+        (DeadlineError::InvalidCooldown, 9, 0xDEAD_0009),
+        (DeadlineError::Unauthorized, 10, 0xDEAD_000A),
+        (DeadlineError::SessionExpired, 11, 0xDEAD_000B),
+        (DeadlineError::SessionRevoked, 12, 0xDEAD_000C),
+        (DeadlineError::SpendCapExceeded, 13, 0xDEAD_000D),
+        (DeadlineError::InvalidSession, 14, 0xDEAD_000E),
+        (DeadlineError::NotYetValid, 15, 0xDEAD_000F),
+        (DeadlineError::SlotOutOfRange, 16, 0xDEAD_0010),
+        (DeadlineError::SessionTransferMismatch, 17, 0xDEAD_0011),
+    ];
+
+    #[test]
+    fn test_code_table() {
+        for ((error, legacy_code, code), expected) in CODES.into_iter().zip(DeadlineError::ALL) {
+            assert_eq!(error, expected);
+            assert_eq!(error.legacy_code(), legacy_code, "{error:?}");
+            assert_eq!(error.code(), code, "{error:?}");
+            assert_eq!(ProgramError::from(error), ProgramError::Custom(code));
+            assert_eq!(
+                to_legacy(ProgramError::from(error)),
+                ProgramError::Custom(legacy_code)
+            );
+        }
+    }
+
+    #[test]
+    fn test_try_from_code() {
+        for error in DeadlineError::ALL {
+            assert_eq!(DeadlineError::try_from(error.code()), Ok(error));
+            assert_eq!(DeadlineError::try_from(error.legacy_code()), Ok(error));
+        }
+        for code in [18, ERROR_CODE_BASE - 1, ERROR_CODE_BASE + 18, u32::MAX] {
+            assert_eq!(DeadlineError::try_from(code), Err(code));
+        }
+    }
+
+    #[test]
+    fn test_to_legacy_keeps_other_errors() {
+        for error in [
+            ProgramError::Custom(0),
+            ProgramError::Custom(ERROR_CODE_BASE + 18),
+            ProgramError::InvalidArgument,
+        ] {
+            assert_eq!(to_legacy(error.clone()), error);
+        }
+    }
+
+    #[test]
+    fn test_to_str_matches_display() {
+        for error in DeadlineError::ALL {
+            assert_eq!(error.to_str(), error.to_string());
+            assert_eq!(
+                ProgramError::from(error).to_str::<DeadlineError>(),
+                error.to_str()
+            );
+        }
+        assert_eq!(
+            ProgramError::Custom(ERROR_CODE_BASE + 18).to_str::<DeadlineError>(),
+            "Error: Unknown"
+        );
     }
 }
@@ -0,0 +1,154 @@
+//! Time-predicate expressions
+//!
+//! An expression is a short stack program of comparisons against
+//! `Clock.unix_timestamp`, `Clock.slot` and `Clock.epoch`, combined with
+//! AND, OR and NOT. For example "(t <= D1 AND slot <= S) OR t <= D2" is
+//! `TimeLe(D1) SlotLe(S) And TimeLe(D2) Or`.
+//!
+//! The encoding, `Op` and the limits are defined in
+//! `cascade_protocol_deadline_codec::expression`. Programs are limited to
+//! `MAX_OPS` ops and `MAX_STACK_DEPTH` stack entries, which bounds compute
+//! usage, and must leave exactly one value on the stack.
+
+use crate::error::DeadlineError;
+use solana_program::clock::Clock;
+
+pub use cascade_protocol_deadline_codec::expression::{Op, MAX_OPS, MAX_STACK_DEPTH};
+
+/// Evaluates an expression against the clock
+///
+/// Expressions produced by `DeadlineInstruction::unpack` are already
+/// validated, but the interpreter enforces the same limits itself and
+/// reports malformed programs as `InvalidInstructionData` instead of
+/// panicking.
+pub fn evaluate(ops: &[Op], clock: &Clock) -> Result<bool, DeadlineError> {
+    if ops.len() > MAX_OPS {
+        return Err(DeadlineError::InvalidInstructionData);
+    }
+
+    let mut stack = [false; MAX_STACK_DEPTH];
+    let mut depth = 0usize;
+
+    for op in ops {
+        let value = match *op {
+            Op::TimeLe(t) => clock.unix_timestamp <= t,
+            Op::TimeGe(t) => clock.unix_timestamp >= t,
+            Op::SlotLe(s) => clock.slot <= s,
+            Op::SlotGe(s) => clock.slot >= s,
+            Op::EpochLe(e) => clock.epoch <= e,
+            Op::EpochGe(e) => clock.epoch >= e,
+            Op::And | Op::Or => {
+                if depth < 2 {
+                    return Err(DeadlineError::InvalidInstructionData);
+                }
+                depth -= 2;
+                let (a, b) = (stack[depth], stack[depth + 1]);
+                if *op == Op::And {
+                    a && b
+                } else {
+                    a || b
+                }
+            }
+            Op::Not => {
+                if depth < 1 {
+                    return Err(DeadlineError::InvalidInstructionData);
+                }
+                depth -= 1;
+                !stack[depth]
+            }
+        };
+
+        if depth == MAX_STACK_DEPTH {
+            return Err(DeadlineError::InvalidInstructionData);
+        }
+        stack[depth] = value;
+        depth += 1;
+    }
+
+    if depth != 1 {
+        return Err(DeadlineError::InvalidInstructionData);
+    }
+    Ok(stack[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn clock(unix_timestamp: i64, slot: u64, epoch: u64) -> Clock {
+        Clock {
+            slot,
+            epoch,
+            unix_timestamp,
+            ..Clock::default()
+        }
+    }
+
+    /// (t <= D1 AND slot <= S) OR t <= D2
+    fn example() -> [Op; 5] {
+        [
+            Op::TimeLe(1_000),
+            Op::SlotLe(500),
+            Op::And,
+            Op::TimeLe(2_000),
+            Op::Or,
+        ]
+    }
+
+    #[test]
+    fn test_evaluate_example() {
+        assert_eq!(evaluate(&example(), &clock(1_000, 500, 0)), Ok(true));
+        // Slot bound exceeded, but still before D2
+        assert_eq!(evaluate(&example(), &clock(1_500, 501, 0)), Ok(true));
+        assert_eq!(evaluate(&example(), &clock(2_000, 501, 0)), Ok(true));
+        assert_eq!(evaluate(&example(), &clock(2_001, 0, 0)), Ok(false));
+    }
+
+    #[test]
+    fn test_evaluate_not_and_epoch() {
+        let ops = [Op::EpochGe(10), Op::EpochLe(20), Op::And, Op::Not];
+        assert_eq!(evaluate(&ops, &clock(0, 0, 9)), Ok(true));
+        assert_eq!(evaluate(&ops, &clock(0, 0, 10)), Ok(false));
+        assert_eq!(evaluate(&ops, &clock(0, 0, 21)), Ok(true));
+    }
+
+    #[test]
+    fn test_evaluate_malformed() {
+        let c = clock(0, 0, 0);
+        assert!(evaluate(&[], &c).is_err());
+        assert!(evaluate(&[Op::And], &c).is_err());
+        assert!(evaluate(&[Op::Not], &c).is_err());
+        assert!(evaluate(&[Op::TimeLe(0), Op::TimeLe(0)], &c).is_err());
+        assert!(evaluate(&[Op::TimeLe(0); MAX_STACK_DEPTH + 1], &c).is_err());
+        let mut long = vec![Op::TimeLe(0)];
+        long.extend([Op::Not; MAX_OPS]);
+        assert!(evaluate(&long, &c).is_err());
+    }
+
+    fn any_op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            any::<i64>().prop_map(Op::TimeLe),
+            any::<i64>().prop_map(Op::TimeGe),
+            any::<u64>().prop_map(Op::SlotLe),
+            any::<u64>().prop_map(Op::SlotGe),
+            any::<u64>().prop_map(Op::EpochLe),
+            any::<u64>().prop_map(Op::EpochGe),
+            Just(Op::And),
+            Just(Op::Or),
+            Just(Op::Not),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn fuzz_evaluate_never_panics(
+            ops in prop::collection::vec(any_op(), 0..MAX_OPS * 2),
+            unix_timestamp: i64,
+            slot: u64,
+            epoch: u64,
+        ) {
+            let _ = evaluate(&ops, &clock(unix_timestamp, slot, epoch));
+        }
+    }
+}
@@ -0,0 +1,626 @@
+//! Anchor IDL generated from the program definitions
+//!
+//! Instruction names and discriminators come from `anchor::ALIASES`,
+//! errors from `DeadlineError::ALL`, and PDA seeds from `state`. The
+//! account and argument tables below are checked in the tests against
+//! `DeadlineInstruction`, its `pack_anchor` encoding and the account metas
+//! of the client's instruction builders, and the committed
+//! `idl/deadline_validator.json` is checked against `idl()`.
+//!
+//! Regenerate the committed IDL with:
+//! `cargo run -p cascade-protocol-deadline-validator --example idl > idl/deadline_validator.json`
+//!
+//! The program emits no events, so `events` is always empty.
+
+use crate::{
+    anchor,
+    error::DeadlineError,
+    schedule::WeeklySchedule,
+    state::{COOLDOWN_SEED, SESSION_SEED},
+};
+use serde_json::{json, Value};
+use solana_program::{pubkey::Pubkey, sysvar};
+
+/// Borsh type of an instruction argument
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IdlType {
+    /// Unsigned 8-bit integer
+    U8,
+    /// Unsigned 64-bit integer
+    U64,
+    /// Signed 64-bit integer
+    I64,
+    /// 32-byte public key
+    Pubkey,
+    /// Byte vector with a u32 length prefix
+    Bytes,
+    /// Option, with a one-byte tag before the value
+    Option(&'static IdlType),
+    /// Fixed-size array
+    Array(&'static IdlType, usize),
+}
+
+impl IdlType {
+    /// Returns the IDL representation of the type
+    pub fn to_json(&self) -> Value {
+        match self {
+            Self::U8 => json!("u8"),
+            Self::U64 => json!("u64"),
+            Self::I64 => json!("i64"),
+            Self::Pubkey => json!("pubkey"),
+            Self::Bytes => json!("bytes"),
+            Self::Option(inner) => json!({ "option": inner.to_json() }),
+            Self::Array(inner, len) => json!({ "array": [inner.to_json(), len] }),
+        }
+    }
+}
+
+/// PDA seed of an instruction account
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IdlSeed {
+    /// Constant bytes
+    Const(&'static [u8]),
+    /// Address of another account of the instruction
+    Account(&'static str),
+    /// Instruction argument
+    Arg(&'static str),
+}
+
+impl IdlSeed {
+    /// Returns the IDL representation of the seed
+    pub fn to_json(&self) -> Value {
+        match self {
+            Self::Const(value) => json!({ "kind": "const", "value": value }),
+            Self::Account(path) => json!({ "kind": "account", "path": path }),
+            Self::Arg(path) => json!({ "kind": "arg", "path": path }),
+        }
+    }
+}
+
+/// Account expected by an instruction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IdlAccount {
+    /// Account name
+    pub name: &'static str,
+    /// Documentation lines
+    pub docs: &'static [&'static str],
+    /// Whether the account is written
+    pub writable: bool,
+    /// Whether the account must sign
+    pub signer: bool,
+    /// Whether the account may be omitted
+    pub optional: bool,
+    /// Fixed address of the account, if any
+    pub address: Option<Pubkey>,
+    /// PDA seeds of the account, if it is a PDA of this program
+    pub seeds: &'static [IdlSeed],
+}
+
+impl IdlAccount {
+    const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            docs: &[],
+            writable: false,
+            signer: false,
+            optional: false,
+            address: None,
+            seeds: &[],
+        }
+    }
+
+    const fn writable(mut self) -> Self {
+        self.writable = true;
+        self
+    }
+
+    const fn signer(mut self) -> Self {
+        self.signer = true;
+        self
+    }
+
+    const fn address(mut self, address: Pubkey) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    const fn seeds(mut self, seeds: &'static [IdlSeed]) -> Self {
+        self.seeds = seeds;
+        self
+    }
+
+    /// Returns the IDL representation of the account
+    pub fn to_json(&self) -> Value {
+        let mut account = json!({ "name": self.name });
+        if !self.docs.is_empty() {
+            account["docs"] = json!(self.docs);
+        }
+        if self.writable {
+            account["writable"] = json!(true);
+        }
+        if self.signer {
+            account["signer"] = json!(true);
+        }
+        if self.optional {
+            account["optional"] = json!(true);
+        }
+        if let Some(address) = self.address {
+            account["address"] = json!(address.to_string());
+        }
+        if !self.seeds.is_empty() {
+            let seeds: Vec<Value> = self.seeds.iter().map(IdlSeed::to_json).collect();
+            account["pda"] = json!({ "seeds": seeds });
+        }
+        account
+    }
+}
+
+/// Instruction with its accounts and borsh arguments
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IdlInstruction {
+    /// Single-byte discriminator the instruction's Anchor alias stands for
+    pub variant: u8,
+    /// Documentation lines
+    pub docs: &'static [&'static str],
+    /// Accounts, in order
+    pub accounts: &'static [IdlAccount],
+    /// Argument names and types, in order
+    pub args: &'static [(&'static str, IdlType)],
+}
+
+impl IdlInstruction {
+    /// Returns the Anchor name of the instruction
+    pub fn name(&self) -> &'static str {
+        anchor::ALIASES
+            .iter()
+            .find(|(_, _, variant)| *variant == self.variant)
+            .map(|(name, _, _)| *name)
+            .expect("every instruction has an Anchor alias")
+    }
+
+    /// Returns the IDL representation of the instruction
+    pub fn to_json(&self) -> Value {
+        let discriminator = anchor::alias(self.variant).expect("every instruction has an alias");
+        let accounts: Vec<Value> = self.accounts.iter().map(IdlAccount::to_json).collect();
+        let args: Vec<Value> = self
+            .args
+            .iter()
+            .map(|(name, ty)| json!({ "name": name, "type": ty.to_json() }))
+            .collect();
+        json!({
+            "name": self.name(),
+            "docs": self.docs,
+            "discriminator": discriminator,
+            "accounts": accounts,
+            "args": args,
+        })
+    }
+}
+
+/// Optional Clock sysvar, for SVM forks without the clock syscall
+const CLOCK: IdlAccount = IdlAccount {
+    docs: &["Clock sysvar, for SVM forks without the clock syscall"],
+    optional: true,
+    ..IdlAccount::new("clock").address(sysvar::clock::ID)
+};
+
+const SYSTEM_PROGRAM: IdlAccount =
+    IdlAccount::new("system_program").address(solana_system_interface::program::ID);
+
+/// Instructions with an Anchor alias, ordered by discriminator
+pub const INSTRUCTIONS: [IdlInstruction; 11] = [
+    IdlInstruction {
+        variant: 0,
+        docs: &["Fails if the current unix timestamp is past the deadline (0 never expires)"],
+        accounts: &[CLOCK],
+        args: &[("deadline", IdlType::I64)],
+    },
+    IdlInstruction {
+        variant: 1,
+        docs: &["Fails if the clock drifts from a Pyth price update's publish_time"],
+        accounts: &[IdlAccount::new("price_update")],
+        args: &[
+            ("max_drift", IdlType::U64),
+            ("max_staleness_slots", IdlType::U64),
+            ("feed_id", IdlType::Array(&IdlType::U8, 32)),
+            ("min_publish_time", IdlType::I64),
+        ],
+    },
+    IdlInstruction {
+        variant: 2,
+        docs: &["Fails if the clock drifts from the time implied by slot progression"],
+        accounts: &[],
+        args: &[("max_drift", IdlType::U64)],
+    },
+    IdlInstruction {
+        variant: 3,
+        docs: &["Fails unless a TransferChecked of mint to destination in the transaction pays the current auction price"],
+        accounts: &[IdlAccount::new("instructions_sysvar").address(sysvar::instructions::ID)],
+        args: &[
+            ("start_price", IdlType::U64),
+            ("floor_price", IdlType::U64),
+            ("start_time", IdlType::I64),
+            ("end_time", IdlType::I64),
+            ("mint", IdlType::Pubkey),
+            ("destination", IdlType::Pubkey),
+        ],
+    },
+    IdlInstruction {
+        variant: 4,
+        docs: &["Fails if the current UTC hour of the week is not set in the schedule bitmap"],
+        accounts: &[CLOCK],
+        args: &[(
+            "schedule",
+            IdlType::Array(&IdlType::U8, WeeklySchedule::LEN),
+        )],
+    },
+    IdlInstruction {
+        variant: 5,
+        docs: &["Fails if the time-predicate bytecode evaluates to false"],
+        accounts: &[CLOCK],
+        args: &[("bytecode", IdlType::Bytes)],
+    },
+    IdlInstruction {
+        variant: 6,
+        docs: &["Creates a cooldown enforcing a minimum interval between actions"],
+        accounts: &[
+            IdlAccount::new("payer").writable().signer(),
+            IdlAccount::new("authority").signer(),
+            IdlAccount::new("cooldown").writable().seeds(&[
+                IdlSeed::Const(COOLDOWN_SEED),
+                IdlSeed::Account("authority"),
+                IdlSeed::Arg("seed"),
+            ]),
+            SYSTEM_PROGRAM,
+        ],
+        args: &[("min_interval", IdlType::U64), ("seed", IdlType::U64)],
+    },
+    IdlInstruction {
+        variant: 7,
+        docs: &["Records an action, failing if the previous one was too recent"],
+        accounts: &[
+            IdlAccount::new("cooldown").writable(),
+            IdlAccount::new("authority").signer(),
+        ],
+        args: &[],
+    },
+    IdlInstruction {
+        variant: 8,
+        docs: &["Creates a session letting a delegate authorize payments until expires_at"],
+        accounts: &[
+            IdlAccount::new("payer").writable().signer(),
+            IdlAccount::new("owner").signer(),
+            IdlAccount::new("session").writable().seeds(&[
+                IdlSeed::Const(SESSION_SEED),
+                IdlSeed::Account("owner"),
+                IdlSeed::Arg("seed"),
+            ]),
+            SYSTEM_PROGRAM,
+        ],
+        args: &[
+            ("delegate", IdlType::Pubkey),
+            ("expires_at", IdlType::I64),
+            ("spend_cap", IdlType::Option(&IdlType::U64)),
+            ("seed", IdlType::U64),
+        ],
+    },
+    IdlInstruction {
+        variant: 9,
+        docs: &["Revokes a session"],
+        accounts: &[
+            IdlAccount::new("session").writable(),
+            IdlAccount::new("owner").signer(),
+        ],
+        args: &[],
+    },
+    IdlInstruction {
+        variant: 10,
+        docs: &["Fails unless the session delegate signed, the session is live and amount, the total of the delegate's transfers, is within its spend cap"],
+        accounts: &[
+            IdlAccount::new("session").writable(),
+            IdlAccount::new("delegate").signer(),
+            IdlAccount::new("instructions_sysvar").address(sysvar::instructions::ID),
+        ],
+        args: &[("amount", IdlType::U64)],
+    },
+];
+
+/// Returns the Anchor IDL of the program
+pub fn idl() -> Value {
+    let instructions: Vec<Value> = INSTRUCTIONS.iter().map(IdlInstruction::to_json).collect();
+    let errors: Vec<Value> = DeadlineError::ALL
+        .iter()
+        .map(|error| {
+            json!({
+                "code": error.code(),
+                "name": format!("{error:?}"),
+                "msg": error.to_string(),
+            })
+        })
+        .collect();
+    json!({
+        "address": crate::id().to_string(),
+        "metadata": {
+            "name": "deadline_validator",
+            "version": env!("CARGO_PKG_VERSION"),
+            "spec": "0.1.0",
+            "description": "Deadline validation for Solana transactions",
+        },
+        "instructions": instructions,
+        "errors": errors,
+        "events": [],
+        "types": [],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{expression::Op, instruction::DeadlineInstruction};
+    use cascade_protocol_deadline_validator_client::instruction as client;
+
+    /// One instruction per variant with an Anchor alias
+    fn instructions() -> Vec<DeadlineInstruction> {
+        vec![
+            DeadlineInstruction::ValidateDeadline {
+                deadline: 1700000000,
+            },
+            DeadlineInstruction::ValidateOracleClock {
+                max_drift: 30,
+                max_staleness_slots: 25,
+                feed_id: [3; 32],
+                min_publish_time: 1700000000,
+            },
+            DeadlineInstruction::ValidateClockSanity { max_drift: 60 },
+            DeadlineInstruction::ValidateDutchAuction {
+                start_price: 1_000_000,
+                floor_price: 100_000,
+                start_time: 1700000000,
+                end_time: 1700003600,
+                mint: Pubkey::new_from_array([4; 32]),
+                destination: Pubkey::new_from_array([5; 32]),
+            },
+            DeadlineInstruction::ValidateSchedule {
+                schedule: WeeklySchedule::ALWAYS,
+            },
+            DeadlineInstruction::ValidateExpression {
+                ops: vec![Op::TimeLe(1700000000), Op::SlotGe(5), Op::And],
+            },
+            DeadlineInstruction::InitializeCooldown {
+                min_interval: 86_400,
+                seed: 7,
+            },
+            DeadlineInstruction::CheckAndBumpCooldown,
+            DeadlineInstruction::CreateSession {
+                delegate: Pubkey::new_from_array([2; 32]),
+                expires_at: 1800000000,
+                spend_cap: Some(5_000_000),
+                seed: 7,
+            },
+            DeadlineInstruction::CreateSession {
+                delegate: Pubkey::new_from_array([2; 32]),
+                expires_at: 0,
+                spend_cap: None,
+                seed: 7,
+            },
+            DeadlineInstruction::RevokeSession,
+            DeadlineInstruction::ValidateSession { amount: 1_000 },
+        ]
+    }
+
+    /// Stops compiling when a variant is added, as a reminder to describe it
+    /// in `INSTRUCTIONS`
+    fn has_alias(instruction: &DeadlineInstruction) -> bool {
+        match instruction {
+            DeadlineInstruction::ValidateDeadline { .. }
+            | DeadlineInstruction::ValidateOracleClock { .. }
+            | DeadlineInstruction::ValidateClockSanity { .. }
+            | DeadlineInstruction::ValidateDutchAuction { .. }
+            | DeadlineInstruction::ValidateSchedule { .. }
+            | DeadlineInstruction::ValidateExpression { .. }
+            | DeadlineInstruction::InitializeCooldown { .. }
+            | DeadlineInstruction::CheckAndBumpCooldown
+            | DeadlineInstruction::CreateSession { .. }
+            | DeadlineInstruction::RevokeSession
+            | DeadlineInstruction::ValidateSession { .. } => true,
+            DeadlineInstruction::Versioned { .. } => false,
+        }
+    }
+
+    /// Consumes a borsh value of type `ty` from `data`
+    fn skip<'a>(ty: &IdlType, data: &'a [u8]) -> Option<&'a [u8]> {
+        match ty {
+            IdlType::U8 => data.get(1..),
+            IdlType::U64 | IdlType::I64 => data.get(8..),
+            IdlType::Pubkey => data.get(32..),
+            IdlType::Bytes => {
+                let (len, rest) = data.split_first_chunk::<4>()?;
+                rest.get(u32::from_le_bytes(*len) as usize..)
+            }
+            IdlType::Option(inner) => match data.split_first()? {
+                (0, rest) => Some(rest),
+                (1, rest) => skip(inner, rest),
+                _ => None,
+            },
+            IdlType::Array(inner, len) => (0..*len).try_fold(data, |data, _| skip(inner, data)),
+        }
+    }
+
+    /// Returns the instruction's entry in `INSTRUCTIONS` and its Anchor
+    /// encoding
+    fn spec(instruction: &DeadlineInstruction) -> (&'static IdlInstruction, Vec<u8>) {
+        let data = instruction.pack_anchor().unwrap();
+        let variant = anchor::legacy_discriminator(data.first_chunk::<8>().unwrap()).unwrap();
+        let spec = INSTRUCTIONS.iter().find(|i| i.variant == variant).unwrap();
+        (spec, data)
+    }
+
+    /// Returns the borsh encoding of the argument `name` in Anchor
+    /// instruction data
+    fn arg<'a>(spec: &IdlInstruction, data: &'a [u8], name: &str) -> &'a [u8] {
+        let mut rest = &data[8..];
+        for (arg, ty) in spec.args {
+            let next = skip(ty, rest).unwrap();
+            if *arg == name {
+                return &rest[..rest.len() - next.len()];
+            }
+            rest = next;
+        }
+        panic!("{}: no argument {name}", spec.name())
+    }
+
+    /// Returns the field names of an instruction, in order, from its derived
+    /// `Debug` representation
+    fn field_names(instruction: &DeadlineInstruction) -> Vec<String> {
+        let debug = format!("{instruction:?}");
+        let Some(fields) = debug
+            .split_once(" { ")
+            .and_then(|(_, fields)| fields.strip_suffix(" }"))
+        else {
+            return vec![];
+        };
+        let mut names = vec![];
+        let (mut depth, mut start) = (0, 0);
+        for (i, c) in fields.char_indices() {
+            match c {
+                '{' | '[' | '(' => depth += 1,
+                '}' | ']' | ')' => depth -= 1,
+                ',' if depth == 0 => {
+                    names.push(&fields[start..i]);
+                    start = i + 2;
+                }
+                _ => {}
+            }
+        }
+        names.push(&fields[start..]);
+        names
+            .into_iter()
+            .map(|field| field.split_once(": ").unwrap().0.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_instructions_cover_aliases() {
+        assert_eq!(INSTRUCTIONS.len(), anchor::ALIASES.len());
+        for (instruction, (name, _, variant)) in INSTRUCTIONS.iter().zip(anchor::ALIASES) {
+            assert_eq!(instruction.variant, variant);
+            assert_eq!(instruction.name(), name);
+        }
+    }
+
+    #[test]
+    fn test_args_match_anchor_encoding() {
+        for instruction in instructions() {
+            assert!(has_alias(&instruction));
+            let (spec, data) = spec(&instruction);
+            let mut rest = &data[8..];
+            for (name, ty) in spec.args {
+                rest = skip(ty, rest).unwrap_or_else(|| panic!("{}: {name}", spec.name()));
+            }
+            assert!(rest.is_empty(), "{}", spec.name());
+            assert_eq!(DeadlineInstruction::unpack(&data).unwrap(), instruction);
+        }
+    }
+
+    #[test]
+    fn test_args_match_instruction_fields() {
+        for instruction in instructions() {
+            let (spec, _) = spec(&instruction);
+            let args: Vec<&str> = spec.args.iter().map(|(name, _)| *name).collect();
+            let fields: Vec<String> = field_names(&instruction)
+                .into_iter()
+                // The Anchor argument is the encoded expression
+                .map(|field| {
+                    if field == "ops" {
+                        "bytecode".into()
+                    } else {
+                        field
+                    }
+                })
+                .collect();
+            assert_eq!(fields, args, "{}", spec.name());
+        }
+    }
+
+    #[test]
+    fn test_accounts_match_client_builders() {
+        let [payer, authority, owner, delegate, oracle, mint, destination, cooldown, session] =
+            [1, 2, 3, 4, 5, 6, 7, 8, 9].map(|byte| Pubkey::new_from_array([byte; 32]));
+        let schedule = [0xff; WeeklySchedule::LEN];
+        let builders = [
+            client::validate_deadline(1700000000),
+            client::with_clock_sysvar(client::validate_deadline(1700000000)),
+            client::validate_oracle_clock(&oracle, 30, 25, &[3; 32], 1700000000),
+            client::validate_clock_sanity(60),
+            client::validate_dutch_auction(&mint, &destination, 10, 1, 1700000000, 1700000900),
+            client::validate_schedule(&schedule),
+            client::with_clock_sysvar(client::validate_schedule(&schedule)),
+            client::validate_expression(&[client::Op::TimeLe(1700000000)]),
+            client::with_clock_sysvar(client::validate_expression(&[client::Op::SlotGe(5)])),
+            client::initialize_cooldown(&payer, &authority, 86_400, 7),
+            client::check_and_bump_cooldown(&cooldown, &authority),
+            client::create_session(&payer, &owner, &delegate, 1800000000, Some(5_000_000), 7),
+            client::revoke_session(&session, &owner),
+            client::validate_session(&session, &delegate, 1_000),
+        ];
+
+        let mut covered = vec![];
+        for instruction in builders {
+            let (spec, data) = spec(&DeadlineInstruction::unpack(&instruction.data).unwrap());
+            let required = spec.accounts.iter().filter(|a| !a.optional).count();
+            assert!(
+                [required, spec.accounts.len()].contains(&instruction.accounts.len()),
+                "{}",
+                spec.name()
+            );
+            for (meta, account) in instruction.accounts.iter().zip(spec.accounts) {
+                let context = format!("{}: {}", spec.name(), account.name);
+                assert_eq!(meta.is_signer, account.signer, "{context}");
+                assert_eq!(meta.is_writable, account.writable, "{context}");
+                if let Some(address) = account.address {
+                    assert_eq!(meta.pubkey, address, "{context}");
+                }
+                if !account.seeds.is_empty() {
+                    let seeds: Vec<&[u8]> = account
+                        .seeds
+                        .iter()
+                        .map(|seed| match seed {
+                            IdlSeed::Const(value) => value,
+                            IdlSeed::Account(name) => {
+                                let index = spec.accounts.iter().position(|a| a.name == *name);
+                                instruction.accounts[index.unwrap()].pubkey.as_ref()
+                            }
+                            IdlSeed::Arg(name) => arg(spec, &data, name),
+                        })
+                        .collect();
+                    let (address, _) = Pubkey::find_program_address(&seeds, &crate::id());
+                    assert_eq!(meta.pubkey, address, "{context}");
+                }
+            }
+            covered.push(spec.variant);
+        }
+        covered.dedup();
+        assert_eq!(covered.len(), INSTRUCTIONS.len());
+    }
+
+    #[test]
+    fn test_errors_match_program_errors() {
+        let idl = idl();
+        let errors = idl["errors"].as_array().unwrap();
+        assert_eq!(errors.len(), DeadlineError::ALL.len());
+        assert_eq!(errors[0]["code"], DeadlineError::DeadlineExpired.code());
+        assert_eq!(errors[0]["name"], "DeadlineExpired");
+        assert_eq!(errors[0]["msg"], "Deadline has expired");
+    }
+
+    #[test]
+    fn test_committed_idl_is_up_to_date() {
+        let committed = include_str!("../../idl/deadline_validator.json");
+        let generated = serde_json::to_string_pretty(&idl()).unwrap() + "\n";
+        assert!(
+            committed == generated,
+            "idl/deadline_validator.json is out of date, regenerate it with \
+             `cargo run -p cascade-protocol-deadline-validator --example idl > idl/deadline_validator.json`"
+        );
+    }
+}
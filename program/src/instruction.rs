@@ -1,7 +1,29 @@
 //! Program instructions
+//!
+//! The byte layout is defined by `cascade_protocol_deadline_codec`. This
+//! module wraps it in an owned `DeadlineInstruction` with Solana types.
 
-use crate::error::DeadlineError;
-use solana_program::program_error::ProgramError;
+use crate::{
+    error::DeadlineError,
+    expression::Op,
+    schedule::WeeklySchedule,
+    state::{get_cooldown_address_and_bump_seed, get_session_address_and_bump_seed},
+};
+use cascade_protocol_deadline_codec::{self as codec, Envelope, Expression};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar,
+};
+
+pub use codec::{
+    instruction::{
+        COMPACT_DEADLINE_BASE, DEADLINE_U32_TAG, DEADLINE_VARINT_TAG, ENVELOPE_TAG,
+        ENVELOPE_VERSION,
+    },
+    Constraints,
+};
 
 /// Instructions supported by the deadline validator program
 #[repr(C)]
@@ -9,12 +31,22 @@ use solana_program::program_error::ProgramError;
 pub enum DeadlineInstruction {
     /// Validates that current unix timestamp is before the given deadline
     ///
-    /// Accounts expected: none (uses Clock sysvar directly)
+    /// Accounts expected: none (uses Clock sysvar directly), or
+    /// 0. `[]` Clock sysvar, for SVM forks without the clock syscall
     ///
     /// Instruction data layout:
     /// - Byte 0: Discriminator (0 = ValidateDeadline)
     /// - Bytes 1-8: deadline (i64, little-endian)
     ///
+    /// Compact layouts with identical semantics:
+    /// - Byte 0: Discriminator (11), Bytes 1-4: deadline -
+    ///   `COMPACT_DEADLINE_BASE` (u32, little-endian)
+    /// - Byte 0: Discriminator (12), Bytes 1-: zigzag-encoded deadline
+    ///   (LEB128 varint, minimal length)
+    ///
+    /// `pack` picks the shortest of the three, e.g. 5 bytes for current
+    /// deadlines and 2 bytes for deadline = 0. All remain accepted.
+    ///
     /// Behavior:
     /// - Succeeds if: current_time <= deadline (inclusive)
     /// - Fails if: current_time > deadline (exclusive)
@@ -29,51 +61,633 @@ pub enum DeadlineInstruction {
         /// Use 0 for "never expires"
         deadline: i64,
     },
+
+    /// Cross-checks the cluster clock against a Pyth price update account
+    ///
+    /// `Clock.unix_timestamp` is a stake-weighted estimate and may drift
+    /// from wall-clock time within the runtime's allowed bounds. This
+    /// instruction compares it with the oracle's `publish_time` to catch
+    /// a drifted consensus clock before time-sensitive checks run.
+    ///
+    /// Accounts expected:
+    /// 0. `[]` Pyth `PriceUpdateV2` account (owned by the Pyth receiver)
+    ///
+    /// Instruction data layout:
+    /// - Byte 0: Discriminator (1 = ValidateOracleClock)
+    /// - Bytes 1-8: max_drift (u64, little-endian)
+    /// - Bytes 9-16: max_staleness_slots (u64, little-endian)
+    /// - Bytes 17-48: feed_id (32 bytes)
+    /// - Bytes 49-56: min_publish_time (i64, little-endian)
+    ///
+    /// Behavior:
+    /// - Fails if: the account's feed_id != feed_id
+    /// - Fails if: current_slot - posted_slot > max_staleness_slots
+    /// - Fails if: publish_time < min_publish_time
+    /// - Fails if: |current_time - publish_time| > max_drift
+    ///
+    /// Any account owned by the Pyth receiver passes the owner check, so
+    /// feed_id pins the price feed the caller trusts. A recently posted
+    /// update may still carry an old publish_time, and a drifted clock
+    /// cannot bound its age, so min_publish_time is set by the signer from
+    /// its own clock when building the transaction.
+    ValidateOracleClock {
+        /// Maximum allowed difference between the clock and the oracle
+        /// `publish_time`, in seconds
+        max_drift: u64,
+        /// Maximum age of the oracle update, in slots since it was posted
+        max_staleness_slots: u64,
+        /// Pyth price feed the oracle account must hold
+        feed_id: [u8; 32],
+        /// Earliest accepted oracle `publish_time`, as a Unix timestamp
+        min_publish_time: i64,
+    },
+
+    /// Validates the cluster clock against slot progression
+    ///
+    /// Compares `Clock.unix_timestamp` with the time implied by slot
+    /// progression since the start of the epoch:
+    /// `epoch_start_timestamp + (slot - first_slot_in_epoch) * 400ms`.
+    /// Place it before `ValidateDeadline` where a drifted consensus clock
+    /// would make the deadline check meaningless.
+    ///
+    /// Accounts expected: none (uses Clock and EpochSchedule sysvars directly)
+    ///
+    /// Instruction data layout:
+    /// - Byte 0: Discriminator (2 = ValidateClockSanity)
+    /// - Bytes 1-8: max_drift (u64, little-endian)
+    ///
+    /// Behavior:
+    /// - Fails if: |current_time - expected_time| > max_drift
+    ///
+    /// Note: real slots are usually slower than the nominal 400ms, so the
+    /// expected time lags further behind as the epoch progresses. Choose
+    /// max_drift with that in mind.
+    ValidateClockSanity {
+        /// Maximum allowed difference from the slot-derived time, in seconds
+        max_drift: u64,
+    },
+
+    /// Validates that the transaction pays the current Dutch-auction price
+    ///
+    /// The required price falls linearly from `start_price` at `start_time`
+    /// to `floor_price` at `end_time`, evaluated at `Clock.unix_timestamp`.
+    /// The instruction succeeds if any top-level SPL Token or Token-2022
+    /// `TransferChecked` of `mint` to `destination` in the transaction moves
+    /// at least that amount.
+    ///
+    /// Accounts expected:
+    /// 0. `[]` Instructions sysvar
+    ///
+    /// Instruction data layout:
+    /// - Byte 0: Discriminator (3 = ValidateDutchAuction)
+    /// - Bytes 1-8: start_price (u64, little-endian)
+    /// - Bytes 9-16: floor_price (u64, little-endian)
+    /// - Bytes 17-24: start_time (i64, little-endian)
+    /// - Bytes 25-32: end_time (i64, little-endian)
+    /// - Bytes 33-64: mint (Pubkey)
+    /// - Bytes 65-96: destination (Pubkey)
+    ///
+    /// Behavior:
+    /// - Only transfers whose mint (account 1) is `mint` and whose
+    ///   destination (account 2) is `destination` count
+    /// - current_time <= start_time: requires start_price
+    /// - current_time >= end_time: requires floor_price
+    /// - in between: linear interpolation, rounded up to the next base unit
+    ///
+    /// Note: start_price must be >= floor_price and end_time must be
+    /// > start_time, otherwise the instruction data is rejected.
+    ValidateDutchAuction {
+        /// Required amount at and before `start_time`, in token base units
+        start_price: u64,
+        /// Required amount at and after `end_time`, in token base units
+        floor_price: u64,
+        /// Unix timestamp at which the price starts to fall
+        start_time: i64,
+        /// Unix timestamp at which the price reaches the floor
+        end_time: i64,
+        /// Mint the payment must be made in
+        mint: Pubkey,
+        /// Token account the payment must be made to
+        destination: Pubkey,
+    },
+
+    /// Validates that current unix timestamp falls into a weekly schedule
+    ///
+    /// Accounts expected: none (uses Clock sysvar directly), or
+    /// 0. `[]` Clock sysvar, for SVM forks without the clock syscall
+    ///
+    /// Instruction data layout:
+    /// - Byte 0: Discriminator (4 = ValidateSchedule)
+    /// - Bytes 1-21: schedule (168-bit bitmap, one bit per UTC hour of the
+    ///   week starting Monday 00:00, least significant bit first)
+    ///
+    /// Behavior:
+    /// - Succeeds if: the bit for the current UTC hour of the week is set
+    /// - Fails if: the bit is clear
+    ValidateSchedule {
+        /// Allowed hours of the week
+        schedule: WeeklySchedule,
+    },
+
+    /// Validates a predicate over unix timestamp, slot and epoch
+    ///
+    /// See the `expression` module for the op encoding, e.g.
+    /// "(t <= D1 AND slot <= S) OR t <= D2".
+    ///
+    /// Accounts expected: none (uses Clock sysvar directly), or
+    /// 0. `[]` Clock sysvar, for SVM forks without the clock syscall
+    ///
+    /// Instruction data layout:
+    /// - Byte 0: Discriminator (5 = ValidateExpression)
+    /// - Bytes 1-: encoded ops, at most `MAX_OPS`
+    ///
+    /// Behavior:
+    /// - Succeeds if: the expression evaluates to true
+    /// - Fails if: the expression evaluates to false
+    ///
+    /// Note: expressions that underflow the stack, exceed `MAX_STACK_DEPTH`
+    /// or do not leave exactly one value are rejected at unpack time.
+    ValidateExpression {
+        /// Ops in evaluation order
+        ops: Vec<Op>,
+    },
+
+    /// Creates a cooldown account enforcing a minimum interval between
+    /// actions of `authority`
+    ///
+    /// Accounts expected:
+    /// 0. `[w, s]` Payer (funds rent)
+    /// 1. `[s]` Authority
+    /// 2. `[w]` Cooldown (PDA `["cooldown", authority, seed]`)
+    /// 3. `[]` System program
+    ///
+    /// Instruction data layout:
+    /// - Byte 0: Discriminator (6 = InitializeCooldown)
+    /// - Bytes 1-8: min_interval (u64, little-endian)
+    /// - Bytes 9-16: seed (u64, little-endian)
+    ///
+    /// Note: programs relying on a cooldown should check its
+    /// `min_interval`, since the authority chooses it.
+    InitializeCooldown {
+        /// Minimum number of seconds between two actions
+        min_interval: u64,
+        /// Seed distinguishing cooldowns of the same authority
+        seed: u64,
+    },
+
+    /// Records an action, failing if the previous one was too recent
+    ///
+    /// Callable by CPI: the calling program forwards the authority's
+    /// signature.
+    ///
+    /// Accounts expected:
+    /// 0. `[w]` Cooldown
+    /// 1. `[s]` Authority
+    ///
+    /// Instruction data layout:
+    /// - Byte 0: Discriminator (7 = CheckAndBumpCooldown)
+    ///
+    /// Behavior:
+    /// - Succeeds if: current_time - last_executed >= min_interval, and
+    ///   sets last_executed = current_time
+    /// - Fails if: current_time - last_executed < min_interval
+    CheckAndBumpCooldown,
+
+    /// Creates a session that lets `delegate` authorize payments on behalf
+    /// of `owner` until `expires_at`
+    ///
+    /// Accounts expected:
+    /// 0. `[w, s]` Payer (funds rent)
+    /// 1. `[s]` Owner
+    /// 2. `[w]` Session (PDA `["session", owner, seed]`)
+    /// 3. `[]` System program
+    ///
+    /// Instruction data layout:
+    /// - Byte 0: Discriminator (8 = CreateSession)
+    /// - Bytes 1-32: delegate (Pubkey)
+    /// - Bytes 33-40: expires_at (i64, little-endian)
+    /// - Byte 41: spend cap flag (0 = uncapped, 1 = capped)
+    /// - Bytes 42-49: spend cap (u64, little-endian, must be zero when
+    ///   uncapped)
+    /// - Bytes 50-57: seed (u64, little-endian)
+    CreateSession {
+        /// Session key allowed to sign `ValidateSession`
+        delegate: Pubkey,
+        /// Unix timestamp after which the session is expired
+        /// Use 0 for "never expires"
+        expires_at: i64,
+        /// Maximum total amount the delegate may authorize
+        spend_cap: Option<u64>,
+        /// Seed distinguishing sessions of the same owner
+        seed: u64,
+    },
+
+    /// Revokes a session so that every later `ValidateSession` fails
+    ///
+    /// Accounts expected:
+    /// 0. `[w]` Session
+    /// 1. `[s]` Owner
+    ///
+    /// Instruction data layout:
+    /// - Byte 0: Discriminator (9 = RevokeSession)
+    RevokeSession,
+
+    /// Validates that the session delegate authorized this transaction
+    ///
+    /// Composes with `ValidateDeadline` and the other checks: include both
+    /// instructions in the same transaction.
+    ///
+    /// Accounts expected:
+    /// 0. `[w]` Session
+    /// 1. `[s]` Delegate
+    /// 2. `[]` Instructions sysvar
+    ///
+    /// Instruction data layout:
+    /// - Byte 0: Discriminator (10 = ValidateSession)
+    /// - Bytes 1-8: amount (u64, little-endian)
+    ///
+    /// Behavior:
+    /// - Succeeds if: the delegate signed, the session is not revoked,
+    ///   current_time <= expires_at (inclusive, 0 = never expires),
+    ///   amount is the total of the delegate's transfers and
+    ///   spent + amount <= spend cap; adds amount to spent
+    /// - Fails otherwise
+    ///
+    /// The delegate's transfers are the top-level SPL Token or Token-2022
+    /// `TransferChecked` instructions with the delegate as authority, read
+    /// from the Instructions sysvar. They must share one source and mint,
+    /// and the delegate may sign no other instruction outside this
+    /// program, since a CPI transfer would not be visible.
+    ValidateSession {
+        /// Total amount of the delegate's transfers in this transaction,
+        /// counted against the spend cap
+        amount: u64,
+    },
+
+    /// Wraps another instruction with optional constraints
+    ///
+    /// Lets new optional fields be added without a new discriminator per
+    /// combination. Legacy encodings, such as the 9-byte
+    /// `ValidateDeadline`, remain valid and unchanged.
+    ///
+    /// Accounts expected: those of the wrapped instruction
+    ///
+    /// Instruction data layout:
+    /// - Byte 0: Discriminator (0xFF = envelope)
+    /// - Byte 1: version (must be 1)
+    /// - Byte 2: flags (reserved, must be 0)
+    /// - Bytes 3-4: length of the wrapped instruction (u16, little-endian)
+    /// - Wrapped instruction in its legacy encoding (not an envelope)
+    /// - Fields, each: type (u8), length (u8), value
+    ///
+    /// Fields:
+    /// - 1 = not_before (i64, little-endian)
+    /// - 2 = min_slot (u64, little-endian)
+    /// - 3 = max_slot (u64, little-endian)
+    ///
+    /// Encoding is canonical: fields appear in ascending type order, at
+    /// most once, with their exact length. Unknown versions, flags and
+    /// field types are rejected, since ignoring a constraint would weaken
+    /// it. At least one field must be present, and min_slot must not
+    /// exceed max_slot.
+    Versioned {
+        /// Instruction run after the constraints pass
+        instruction: InnerInstruction,
+        /// Constraints checked first
+        constraints: Constraints,
+    },
+}
+
+/// Instruction wrapped by `Versioned`, which is never itself `Versioned`,
+/// as envelopes do not nest
+#[derive(Clone, Debug, PartialEq)]
+pub struct InnerInstruction(Box<DeadlineInstruction>);
+
+impl InnerInstruction {
+    /// Returns the wrapped instruction
+    pub fn into_inner(self) -> DeadlineInstruction {
+        *self.0
+    }
+}
+
+impl std::ops::Deref for InnerInstruction {
+    type Target = DeadlineInstruction;
+
+    fn deref(&self) -> &DeadlineInstruction {
+        &self.0
+    }
+}
+
+impl TryFrom<DeadlineInstruction> for InnerInstruction {
+    type Error = ProgramError;
+
+    /// Fails with `InvalidInstructionData` for a `Versioned` instruction
+    fn try_from(instruction: DeadlineInstruction) -> Result<Self, ProgramError> {
+        match instruction {
+            DeadlineInstruction::Versioned { .. } => {
+                Err(DeadlineError::InvalidInstructionData.into())
+            }
+            instruction => Ok(Self(Box::new(instruction))),
+        }
+    }
 }
 
 impl DeadlineInstruction {
     /// Unpacks instruction from byte buffer
     ///
     /// Expected format:
-    /// - Byte 0: Discriminator (must be 0)
-    /// - Bytes 1-8: Deadline as i64 little-endian
+    /// - Byte 0: Discriminator
+    /// - Remaining bytes: fields of the variant, with exact length
+    ///
+    /// Anchor-style 8-byte discriminators (see the `anchor` module) are
+    /// accepted as aliases, followed by borsh-encoded arguments.
     pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
-        let (&variant, rest) = input
-            .split_first()
-            .ok_or(DeadlineError::InvalidInstructionData)?;
-
-        Ok(match variant {
-            0 => {
-                if rest.len() != 8 {
-                    return Err(DeadlineError::InvalidInstructionData.into());
-                }
-                let deadline = i64::from_le_bytes(
-                    rest[..8]
-                        .try_into()
-                        .map_err(|_| DeadlineError::InvalidInstructionData)?,
-                );
-                Self::ValidateDeadline { deadline }
-            }
-            _ => return Err(DeadlineError::InvalidInstructionData.into()),
+        let Envelope {
+            instruction,
+            constraints,
+        } = Envelope::unpack(input).map_err(|_| DeadlineError::InvalidInstructionData)?;
+        let instruction = Self::from_codec(instruction);
+        if constraints.is_empty() {
+            return Ok(instruction);
+        }
+        Ok(Self::Versioned {
+            instruction: InnerInstruction(Box::new(instruction)),
+            constraints,
         })
     }
 
-    /// Packs instruction into byte buffer
-    pub fn pack(&self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(9);
-        match self {
-            Self::ValidateDeadline { deadline } => {
-                buf.push(0); // instruction discriminator
-                buf.extend_from_slice(&deadline.to_le_bytes());
+    fn from_codec(instruction: codec::DeadlineInstruction) -> Self {
+        use codec::DeadlineInstruction as Codec;
+        match instruction {
+            Codec::ValidateDeadline { deadline } => Self::ValidateDeadline { deadline },
+            Codec::ValidateOracleClock {
+                max_drift,
+                max_staleness_slots,
+                feed_id,
+                min_publish_time,
+            } => Self::ValidateOracleClock {
+                max_drift,
+                max_staleness_slots,
+                feed_id,
+                min_publish_time,
+            },
+            Codec::ValidateClockSanity { max_drift } => Self::ValidateClockSanity { max_drift },
+            Codec::ValidateDutchAuction {
+                start_price,
+                floor_price,
+                start_time,
+                end_time,
+                mint,
+                destination,
+            } => Self::ValidateDutchAuction {
+                start_price,
+                floor_price,
+                start_time,
+                end_time,
+                mint: Pubkey::new_from_array(mint),
+                destination: Pubkey::new_from_array(destination),
+            },
+            Codec::ValidateSchedule { schedule } => Self::ValidateSchedule {
+                schedule: WeeklySchedule(schedule),
+            },
+            Codec::ValidateExpression { ops } => Self::ValidateExpression {
+                ops: ops.ops().collect(),
+            },
+            Codec::InitializeCooldown { min_interval, seed } => {
+                Self::InitializeCooldown { min_interval, seed }
             }
+            Codec::CheckAndBumpCooldown => Self::CheckAndBumpCooldown,
+            Codec::CreateSession {
+                delegate,
+                expires_at,
+                spend_cap,
+                seed,
+            } => Self::CreateSession {
+                delegate: Pubkey::new_from_array(delegate),
+                expires_at,
+                spend_cap,
+                seed,
+            },
+            Codec::RevokeSession => Self::RevokeSession,
+            Codec::ValidateSession { amount } => Self::ValidateSession { amount },
+        }
+    }
+
+    /// Returns the codec view of the instruction, borrowing expression ops
+    fn to_codec(&self) -> Envelope<'_> {
+        use codec::DeadlineInstruction as Codec;
+        let instruction = match self {
+            Self::ValidateDeadline { deadline } => Codec::ValidateDeadline {
+                deadline: *deadline,
+            },
+            Self::ValidateOracleClock {
+                max_drift,
+                max_staleness_slots,
+                feed_id,
+                min_publish_time,
+            } => Codec::ValidateOracleClock {
+                max_drift: *max_drift,
+                max_staleness_slots: *max_staleness_slots,
+                feed_id: *feed_id,
+                min_publish_time: *min_publish_time,
+            },
+            Self::ValidateClockSanity { max_drift } => Codec::ValidateClockSanity {
+                max_drift: *max_drift,
+            },
+            Self::ValidateDutchAuction {
+                start_price,
+                floor_price,
+                start_time,
+                end_time,
+                mint,
+                destination,
+            } => Codec::ValidateDutchAuction {
+                start_price: *start_price,
+                floor_price: *floor_price,
+                start_time: *start_time,
+                end_time: *end_time,
+                mint: mint.to_bytes(),
+                destination: destination.to_bytes(),
+            },
+            Self::ValidateSchedule { schedule } => Codec::ValidateSchedule {
+                schedule: schedule.0,
+            },
+            Self::ValidateExpression { ops } => Codec::ValidateExpression {
+                ops: Expression::from_ops(ops),
+            },
+            Self::InitializeCooldown { min_interval, seed } => Codec::InitializeCooldown {
+                min_interval: *min_interval,
+                seed: *seed,
+            },
+            Self::CheckAndBumpCooldown => Codec::CheckAndBumpCooldown,
+            Self::CreateSession {
+                delegate,
+                expires_at,
+                spend_cap,
+                seed,
+            } => Codec::CreateSession {
+                delegate: delegate.to_bytes(),
+                expires_at: *expires_at,
+                spend_cap: *spend_cap,
+                seed: *seed,
+            },
+            Self::RevokeSession => Codec::RevokeSession,
+            Self::ValidateSession { amount } => Codec::ValidateSession { amount: *amount },
+            Self::Versioned {
+                instruction,
+                constraints,
+            } => {
+                return Envelope {
+                    instruction: instruction.to_codec().instruction,
+                    constraints: *constraints,
+                };
+            }
+        };
+        instruction.into()
+    }
+
+    /// Decodes and validates an encoded expression
+    ///
+    /// Rejects unknown opcodes, truncated operands, more than `MAX_OPS`
+    /// ops, stack underflow, more than `MAX_STACK_DEPTH` values on the
+    /// stack, and programs that do not leave exactly one value.
+    pub fn unpack_expression(input: &[u8]) -> Result<Vec<Op>, ProgramError> {
+        let expression =
+            Expression::from_bytecode(input).map_err(|_| DeadlineError::InvalidInstructionData)?;
+        Ok(expression.ops().collect())
+    }
+
+    /// Packs instruction with its Anchor discriminator and borsh-encoded
+    /// arguments
+    ///
+    /// Returns `None` for `Versioned`, which has no Anchor alias.
+    pub fn pack_anchor(&self) -> Option<Vec<u8>> {
+        if let Self::Versioned { .. } = self {
+            return None;
         }
+        let instruction = self.to_codec().instruction;
+        let mut buf = vec![0u8; instruction.anchor_packed_len()];
+        instruction.pack_anchor_into(&mut buf);
+        Some(buf)
+    }
+
+    /// Packs instruction into byte buffer
+    ///
+    /// A `Versioned` instruction without constraints packs to the legacy
+    /// encoding of the wrapped instruction, its only canonical form.
+    pub fn pack(&self) -> Vec<u8> {
+        let envelope = self.to_codec();
+        let mut buf = vec![0u8; envelope.packed_len()];
+        envelope.pack_into(&mut buf);
         buf
     }
 }
 
+/// Creates an `InitializeCooldown` instruction
+pub fn initialize_cooldown(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    authority: &Pubkey,
+    min_interval: u64,
+    seed: u64,
+) -> Instruction {
+    let (cooldown, _) = get_cooldown_address_and_bump_seed(authority, seed, program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(cooldown, false),
+            AccountMeta::new_readonly(solana_system_interface::program::ID, false),
+        ],
+        data: DeadlineInstruction::InitializeCooldown { min_interval, seed }.pack(),
+    }
+}
+
+/// Creates a `CheckAndBumpCooldown` instruction
+pub fn check_and_bump_cooldown(
+    program_id: &Pubkey,
+    cooldown: &Pubkey,
+    authority: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*cooldown, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+        data: DeadlineInstruction::CheckAndBumpCooldown.pack(),
+    }
+}
+
+/// Creates a `CreateSession` instruction
+pub fn create_session(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    owner: &Pubkey,
+    delegate: &Pubkey,
+    expires_at: i64,
+    spend_cap: Option<u64>,
+    seed: u64,
+) -> Instruction {
+    let (session, _) = get_session_address_and_bump_seed(owner, seed, program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new(session, false),
+            AccountMeta::new_readonly(solana_system_interface::program::ID, false),
+        ],
+        data: DeadlineInstruction::CreateSession {
+            delegate: *delegate,
+            expires_at,
+            spend_cap,
+            seed,
+        }
+        .pack(),
+    }
+}
+
+/// Creates a `RevokeSession` instruction
+pub fn revoke_session(program_id: &Pubkey, session: &Pubkey, owner: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*session, false),
+            AccountMeta::new_readonly(*owner, true),
+        ],
+        data: DeadlineInstruction::RevokeSession.pack(),
+    }
+}
+
+/// Creates a `ValidateSession` instruction
+pub fn validate_session(
+    program_id: &Pubkey,
+    session: &Pubkey,
+    delegate: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*session, false),
+            AccountMeta::new_readonly(*delegate, true),
+            AccountMeta::new_readonly(sysvar::instructions::ID, false),
+        ],
+        data: DeadlineInstruction::ValidateSession { amount }.pack(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        anchor,
+        expression::{MAX_OPS, MAX_STACK_DEPTH},
+        schedule::Weekday,
+    };
+    use proptest::prelude::*;
 
     #[test]
     fn test_instruction_packing_roundtrip() {
@@ -102,6 +716,713 @@ mod tests {
         assert_eq!(instruction, unpacked);
     }
 
+    #[test]
+    fn test_instruction_packing_oracle_clock() {
+        let instruction = DeadlineInstruction::ValidateOracleClock {
+            max_drift: 30,
+            max_staleness_slots: 150,
+            feed_id: [7; 32],
+            min_publish_time: 1_700_000_000,
+        };
+        let packed = instruction.pack();
+        assert_eq!(packed.len(), 57);
+        let unpacked = DeadlineInstruction::unpack(&packed).unwrap();
+        assert_eq!(instruction, unpacked);
+    }
+
+    #[test]
+    fn test_invalid_instruction_oracle_clock_wrong_length() {
+        let data = [1u8, 0, 0, 0, 0, 0, 0, 0, 0];
+        assert!(DeadlineInstruction::unpack(&data).is_err());
+
+        // The baseline 17-byte layout without feed_id and min_publish_time
+        let data = [1u8; 17];
+        assert!(DeadlineInstruction::unpack(&data).is_err());
+
+        let mut data = DeadlineInstruction::ValidateOracleClock {
+            max_drift: 1,
+            max_staleness_slots: 1,
+            feed_id: [0; 32],
+            min_publish_time: 0,
+        }
+        .pack();
+        data.push(0);
+        assert!(DeadlineInstruction::unpack(&data).is_err());
+    }
+
+    #[test]
+    fn test_instruction_packing_clock_sanity() {
+        let instruction = DeadlineInstruction::ValidateClockSanity { max_drift: 120 };
+        let packed = instruction.pack();
+        assert_eq!(packed.len(), 9);
+        let unpacked = DeadlineInstruction::unpack(&packed).unwrap();
+        assert_eq!(instruction, unpacked);
+    }
+
+    #[test]
+    fn test_invalid_instruction_clock_sanity_wrong_length() {
+        let data = [2u8, 0, 0, 0];
+        assert!(DeadlineInstruction::unpack(&data).is_err());
+
+        let data = [2u8, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        assert!(DeadlineInstruction::unpack(&data).is_err());
+    }
+
+    #[test]
+    fn test_instruction_packing_dutch_auction() {
+        let instruction = DeadlineInstruction::ValidateDutchAuction {
+            start_price: 10_000_000,
+            floor_price: 1_000_000,
+            start_time: 1700000000,
+            end_time: 1700003600,
+            mint: Pubkey::new_from_array([4; 32]),
+            destination: Pubkey::new_from_array([5; 32]),
+        };
+        let packed = instruction.pack();
+        assert_eq!(packed.len(), 97);
+        let unpacked = DeadlineInstruction::unpack(&packed).unwrap();
+        assert_eq!(instruction, unpacked);
+
+        // Negative times and a constant price are representable
+        let instruction = DeadlineInstruction::ValidateDutchAuction {
+            start_price: 5,
+            floor_price: 5,
+            start_time: i64::MIN,
+            end_time: -1,
+            mint: Pubkey::default(),
+            destination: Pubkey::default(),
+        };
+        let unpacked = DeadlineInstruction::unpack(&instruction.pack()).unwrap();
+        assert_eq!(instruction, unpacked);
+    }
+
+    #[test]
+    fn test_invalid_instruction_dutch_auction_parameters() {
+        // Price rising over time
+        let data = DeadlineInstruction::ValidateDutchAuction {
+            start_price: 1,
+            floor_price: 2,
+            start_time: 0,
+            end_time: 1,
+            mint: Pubkey::default(),
+            destination: Pubkey::default(),
+        }
+        .pack();
+        assert!(DeadlineInstruction::unpack(&data).is_err());
+
+        // Empty or inverted time range
+        for (start_time, end_time) in [(100, 100), (100, 99)] {
+            let data = DeadlineInstruction::ValidateDutchAuction {
+                start_price: 2,
+                floor_price: 1,
+                start_time,
+                end_time,
+                mint: Pubkey::default(),
+                destination: Pubkey::default(),
+            }
+            .pack();
+            assert!(DeadlineInstruction::unpack(&data).is_err());
+        }
+
+        // Wrong length, including the baseline layout without mint and
+        // destination
+        let data = [3u8; 32];
+        assert!(DeadlineInstruction::unpack(&data).is_err());
+        let mut data = [0u8; 33];
+        data[0] = 3;
+        data[1] = 2;
+        data[25] = 1;
+        assert!(DeadlineInstruction::unpack(&data).is_err());
+    }
+
+    #[test]
+    fn test_instruction_packing_schedule() {
+        let instruction = DeadlineInstruction::ValidateSchedule {
+            schedule: WeeklySchedule::default().allow(Weekday::Monday, 8, 20),
+        };
+        let packed = instruction.pack();
+        assert_eq!(packed.len(), 22);
+        let unpacked = DeadlineInstruction::unpack(&packed).unwrap();
+        assert_eq!(instruction, unpacked);
+    }
+
+    #[test]
+    fn test_invalid_instruction_schedule_wrong_length() {
+        assert!(DeadlineInstruction::unpack(&[4u8; 21]).is_err());
+        assert!(DeadlineInstruction::unpack(&[4u8; 23]).is_err());
+    }
+
+    #[test]
+    fn test_instruction_packing_expression() {
+        let instruction = DeadlineInstruction::ValidateExpression {
+            ops: vec![
+                Op::TimeLe(1_700_000_000),
+                Op::SlotLe(250_000_000),
+                Op::And,
+                Op::TimeLe(1_700_003_600),
+                Op::Or,
+            ],
+        };
+        let packed = instruction.pack();
+        assert_eq!(packed.len(), 1 + 3 * 9 + 2);
+        let unpacked = DeadlineInstruction::unpack(&packed).unwrap();
+        assert_eq!(instruction, unpacked);
+    }
+
+    #[test]
+    fn test_invalid_expression() {
+        let unpack = |ops: &[Op]| {
+            DeadlineInstruction::unpack(
+                &DeadlineInstruction::ValidateExpression { ops: ops.to_vec() }.pack(),
+            )
+        };
+
+        // Empty, underflow, leftover values
+        assert!(unpack(&[]).is_err());
+        assert!(unpack(&[Op::TimeLe(0), Op::And]).is_err());
+        assert!(unpack(&[Op::Not]).is_err());
+        assert!(unpack(&[Op::TimeLe(0), Op::TimeGe(0)]).is_err());
+
+        // Stack depth limit
+        let mut deep = vec![Op::TimeLe(0); MAX_STACK_DEPTH];
+        deep.extend([Op::And; MAX_STACK_DEPTH - 1]);
+        assert!(unpack(&deep).is_ok());
+        deep.insert(0, Op::TimeLe(0));
+        deep.push(Op::And);
+        assert!(unpack(&deep).is_err());
+
+        // Op limit
+        let mut long = vec![Op::TimeLe(0)];
+        long.extend([Op::Not; MAX_OPS - 1]);
+        assert!(unpack(&long).is_ok());
+        long.push(Op::Not);
+        assert!(unpack(&long).is_err());
+
+        // Unknown opcode and truncated operand
+        assert!(DeadlineInstruction::unpack(&[5, 0x07]).is_err());
+        assert!(DeadlineInstruction::unpack(&[5, 0x01, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn test_instruction_packing_cooldown() {
+        for instruction in [
+            DeadlineInstruction::InitializeCooldown {
+                min_interval: 30 * 86_400,
+                seed: 42,
+            },
+            DeadlineInstruction::CheckAndBumpCooldown,
+        ] {
+            let packed = instruction.pack();
+            assert_eq!(DeadlineInstruction::unpack(&packed).unwrap(), instruction);
+        }
+        assert!(DeadlineInstruction::unpack(&[6u8; 16]).is_err());
+        assert!(DeadlineInstruction::unpack(&[6u8; 18]).is_err());
+        assert!(DeadlineInstruction::unpack(&[7u8, 0]).is_err());
+    }
+
+    #[test]
+    fn test_instruction_packing_session() {
+        for instruction in [
+            DeadlineInstruction::CreateSession {
+                delegate: Pubkey::new_from_array([9; 32]),
+                expires_at: 1800000000,
+                spend_cap: Some(1_000_000),
+                seed: 3,
+            },
+            DeadlineInstruction::CreateSession {
+                delegate: Pubkey::new_from_array([9; 32]),
+                expires_at: 0,
+                spend_cap: None,
+                seed: 0,
+            },
+            DeadlineInstruction::RevokeSession,
+            DeadlineInstruction::ValidateSession { amount: 42 },
+        ] {
+            let packed = instruction.pack();
+            assert_eq!(DeadlineInstruction::unpack(&packed).unwrap(), instruction);
+        }
+    }
+
+    #[test]
+    fn test_invalid_instruction_session() {
+        let packed = DeadlineInstruction::CreateSession {
+            delegate: Pubkey::new_from_array([9; 32]),
+            expires_at: 1800000000,
+            spend_cap: None,
+            seed: 0,
+        }
+        .pack();
+
+        // Non-canonical spend cap
+        let mut data = packed.clone();
+        data[42] = 1;
+        assert!(DeadlineInstruction::unpack(&data).is_err());
+        let mut data = packed.clone();
+        data[41] = 2;
+        assert!(DeadlineInstruction::unpack(&data).is_err());
+
+        assert!(DeadlineInstruction::unpack(&packed[..packed.len() - 1]).is_err());
+        assert!(DeadlineInstruction::unpack(&[9u8, 0]).is_err());
+        assert!(DeadlineInstruction::unpack(&[10u8; 8]).is_err());
+    }
+
+    /// Every combination of constraints, including none
+    fn all_constraints() -> Vec<Constraints> {
+        let mut all = Vec::new();
+        for not_before in [None, Some(i64::MIN), Some(-1), Some(1_700_000_000)] {
+            for min_slot in [None, Some(0), Some(1_000), Some(2_000)] {
+                for max_slot in [None, Some(1_000), Some(u64::MAX)] {
+                    all.push(Constraints {
+                        not_before,
+                        min_slot,
+                        max_slot,
+                    });
+                }
+            }
+        }
+        all
+    }
+
+    fn wrappable_instructions() -> Vec<DeadlineInstruction> {
+        vec![
+            DeadlineInstruction::ValidateDeadline {
+                deadline: 1_800_000_000,
+            },
+            DeadlineInstruction::ValidateClockSanity { max_drift: 60 },
+            DeadlineInstruction::ValidateSchedule {
+                schedule: WeeklySchedule::ALWAYS,
+            },
+            DeadlineInstruction::ValidateExpression {
+                ops: [Op::TimeLe(0)]
+                    .into_iter()
+                    .chain([[Op::SlotLe(0), Op::And]; (MAX_OPS - 1) / 2].concat())
+                    .collect(),
+            },
+            DeadlineInstruction::RevokeSession,
+        ]
+    }
+
+    #[test]
+    fn test_envelope_roundtrip_exhaustive() {
+        for inner in wrappable_instructions() {
+            for constraints in all_constraints() {
+                let instruction = DeadlineInstruction::Versioned {
+                    instruction: InnerInstruction::try_from(inner.clone()).unwrap(),
+                    constraints,
+                };
+                let packed = instruction.pack();
+                let unpacked = DeadlineInstruction::unpack(&packed);
+
+                if constraints.is_empty() {
+                    // Canonical form of an empty envelope is the legacy one
+                    assert_eq!(packed, inner.pack());
+                } else if matches!(
+                    (constraints.min_slot, constraints.max_slot),
+                    (Some(min_slot), Some(max_slot)) if min_slot > max_slot
+                ) {
+                    assert!(unpacked.is_err());
+                } else {
+                    assert_eq!(unpacked.unwrap(), instruction);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_envelope_legacy_format_unchanged() {
+        let mut legacy = vec![0u8];
+        legacy.extend_from_slice(&1234567890i64.to_le_bytes());
+        assert_eq!(
+            DeadlineInstruction::unpack(&legacy).unwrap(),
+            DeadlineInstruction::ValidateDeadline {
+                deadline: 1234567890,
+            }
+        );
+    }
+
+    #[test]
+    fn test_envelope_layout() {
+        let packed = DeadlineInstruction::Versioned {
+            instruction: InnerInstruction::try_from(DeadlineInstruction::ValidateDeadline {
+                deadline: 5,
+            })
+            .unwrap(),
+            constraints: Constraints {
+                not_before: Some(1),
+                min_slot: None,
+                max_slot: Some(2),
+            },
+        }
+        .pack();
+
+        let mut expected = vec![0xFF, 1, 0, 2, 0, DEADLINE_VARINT_TAG, 10];
+        expected.extend_from_slice(&[1, 8, 1, 0, 0, 0, 0, 0, 0, 0]);
+        expected.extend_from_slice(&[3, 8, 2, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expected);
+    }
+
+    #[test]
+    fn test_envelope_non_canonical_rejected() {
+        let packed = DeadlineInstruction::Versioned {
+            instruction: InnerInstruction::try_from(DeadlineInstruction::ValidateDeadline {
+                deadline: 5,
+            })
+            .unwrap(),
+            constraints: Constraints {
+                not_before: Some(1),
+                min_slot: Some(2),
+                max_slot: None,
+            },
+        }
+        .pack();
+        let fields = 3 + 2 + 9;
+        let reject = |data: &[u8]| assert!(DeadlineInstruction::unpack(data).is_err());
+
+        // Unknown version and non-zero flags
+        let mut data = packed.clone();
+        data[1] = 2;
+        reject(&data);
+        let mut data = packed.clone();
+        data[2] = 1;
+        reject(&data);
+
+        // Fields out of order
+        let mut data = packed[..fields].to_vec();
+        data.extend_from_slice(&packed[fields + 10..]);
+        data.extend_from_slice(&packed[fields..fields + 10]);
+        reject(&data);
+
+        // Duplicate field
+        let mut data = packed.clone();
+        data.extend_from_slice(&packed[fields + 10..]);
+        reject(&data);
+
+        // Unknown field type and wrong field length
+        let mut data = packed.clone();
+        data.extend_from_slice(&[4, 8, 0, 0, 0, 0, 0, 0, 0, 0]);
+        reject(&data);
+        let mut data = packed[..fields].to_vec();
+        data.extend_from_slice(&[1, 4, 0, 0, 0, 0]);
+        reject(&data);
+
+        // Truncated or trailing bytes
+        reject(&packed[..packed.len() - 1]);
+        let mut data = packed.clone();
+        data.push(0);
+        reject(&data);
+
+        // Empty envelope
+        reject(&packed[..fields]);
+
+        // Nested envelope
+        let mut data = vec![0xFF, 1, 0];
+        data.extend_from_slice(&(packed.len() as u16).to_le_bytes());
+        data.extend_from_slice(&packed);
+        data.extend_from_slice(&packed[fields..fields + 10]);
+        reject(&data);
+
+        // Inner length mismatch
+        let mut data = packed.clone();
+        data[3] = 8;
+        reject(&data);
+        let mut data = packed.clone();
+        data[3] = 10;
+        reject(&data);
+    }
+
+    #[test]
+    fn test_envelope_does_not_nest() {
+        let versioned = DeadlineInstruction::Versioned {
+            instruction: InnerInstruction::try_from(DeadlineInstruction::RevokeSession).unwrap(),
+            constraints: Constraints {
+                min_slot: Some(1),
+                ..Constraints::default()
+            },
+        };
+        assert_eq!(
+            InnerInstruction::try_from(versioned),
+            Err(DeadlineError::InvalidInstructionData.into())
+        );
+    }
+
+    #[test]
+    fn test_envelope_single_byte_mutations_stay_canonical() {
+        let packed = DeadlineInstruction::Versioned {
+            instruction: InnerInstruction::try_from(DeadlineInstruction::ValidateDeadline {
+                deadline: 5,
+            })
+            .unwrap(),
+            constraints: Constraints {
+                not_before: Some(1),
+                min_slot: Some(2),
+                max_slot: Some(3),
+            },
+        }
+        .pack();
+
+        // Any accepted encoding must be the one `pack` produces
+        for i in 0..packed.len() {
+            for byte in 0..=u8::MAX {
+                let mut data = packed.clone();
+                data[i] = byte;
+                if let Ok(instruction) = DeadlineInstruction::unpack(&data) {
+                    assert_eq!(instruction.pack(), data);
+                }
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn fuzz_unpack_never_panics(data in prop::collection::vec(any::<u8>(), 0..512)) {
+            let _ = DeadlineInstruction::unpack(&data);
+        }
+
+        #[test]
+        fn fuzz_compact_deadline_roundtrip(deadline: i64) {
+            let instruction = DeadlineInstruction::ValidateDeadline { deadline };
+            let packed = instruction.pack();
+            prop_assert!(packed.len() <= 9);
+            prop_assert_eq!(DeadlineInstruction::unpack(&packed).unwrap(), instruction);
+        }
+
+        #[test]
+        fn fuzz_unpack_is_canonical(data in prop::collection::vec(any::<u8>(), 0..128)) {
+            let mut data = data;
+            if let Some(first) = data.first_mut() {
+                *first = ENVELOPE_TAG;
+            }
+            if let Ok(instruction) = DeadlineInstruction::unpack(&data) {
+                prop_assert_eq!(instruction.pack(), data);
+            }
+        }
+
+        #[test]
+        fn fuzz_unpack_expression_never_panics(
+            data in prop::collection::vec(
+                prop_oneof![Just(0x01u8), Just(0x03), Just(0x10), Just(0x11), Just(0x12), any::<u8>()],
+                0..128,
+            ),
+        ) {
+            if let Ok(ops) = DeadlineInstruction::unpack_expression(&data) {
+                // Anything the validator accepts must evaluate and re-encode
+                prop_assert!(crate::expression::evaluate(&ops, &Default::default()).is_ok());
+                let packed = DeadlineInstruction::ValidateExpression { ops }.pack();
+                prop_assert_eq!(&packed[1..], &data[..]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_compact_deadline_lengths() {
+        let len = |deadline| {
+            DeadlineInstruction::ValidateDeadline { deadline }
+                .pack()
+                .len()
+        };
+        assert_eq!(len(0), 2);
+        assert_eq!(len(-1), 2);
+        assert_eq!(len(63), 2);
+        assert_eq!(len(64), 3);
+        assert_eq!(len(COMPACT_DEADLINE_BASE - 1), 6);
+        assert_eq!(len(COMPACT_DEADLINE_BASE), 5);
+        assert_eq!(len(1_800_000_000), 5);
+        assert_eq!(len(COMPACT_DEADLINE_BASE + u32::MAX as i64), 5);
+        assert_eq!(len(COMPACT_DEADLINE_BASE + u32::MAX as i64 + 1), 6);
+        assert_eq!(len(i64::MAX), 9);
+        assert_eq!(len(i64::MIN), 9);
+    }
+
+    #[test]
+    fn test_compact_deadline_roundtrip() {
+        let mut deadlines = vec![
+            0,
+            1,
+            -1,
+            i64::MIN,
+            i64::MAX,
+            1_700_000_000,
+            COMPACT_DEADLINE_BASE,
+            COMPACT_DEADLINE_BASE + u32::MAX as i64,
+        ];
+        for shift in 0..63 {
+            deadlines.extend([1i64 << shift, (1i64 << shift) - 1, -(1i64 << shift)]);
+        }
+
+        for deadline in deadlines {
+            let instruction = DeadlineInstruction::ValidateDeadline { deadline };
+            let packed = instruction.pack();
+            assert!(packed.len() <= 9);
+            assert_eq!(DeadlineInstruction::unpack(&packed).unwrap(), instruction);
+
+            // Every encoding decodes to the same deadline
+            let mut legacy = vec![0u8];
+            legacy.extend_from_slice(&deadline.to_le_bytes());
+            assert_eq!(DeadlineInstruction::unpack(&legacy).unwrap(), instruction);
+        }
+    }
+
+    #[test]
+    fn test_compact_deadline_u32_layout() {
+        let packed = DeadlineInstruction::ValidateDeadline {
+            deadline: COMPACT_DEADLINE_BASE + 0x01020304,
+        }
+        .pack();
+        assert_eq!(packed, [DEADLINE_U32_TAG, 4, 3, 2, 1]);
+
+        assert!(DeadlineInstruction::unpack(&[DEADLINE_U32_TAG, 0, 0, 0]).is_err());
+        assert!(DeadlineInstruction::unpack(&[DEADLINE_U32_TAG, 0, 0, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn test_compact_deadline_varint_non_canonical() {
+        // 0 and -1 in minimal form
+        assert_eq!(
+            DeadlineInstruction::unpack(&[DEADLINE_VARINT_TAG, 0]).unwrap(),
+            DeadlineInstruction::ValidateDeadline { deadline: 0 }
+        );
+        assert_eq!(
+            DeadlineInstruction::unpack(&[DEADLINE_VARINT_TAG, 1]).unwrap(),
+            DeadlineInstruction::ValidateDeadline { deadline: -1 }
+        );
+
+        // Empty, padded, unterminated, continuation after terminator
+        for data in [
+            &[DEADLINE_VARINT_TAG][..],
+            &[DEADLINE_VARINT_TAG, 0x80, 0],
+            &[DEADLINE_VARINT_TAG, 0x80],
+            &[DEADLINE_VARINT_TAG, 0, 0],
+            &[DEADLINE_VARINT_TAG, 1, 0x80, 1],
+        ] {
+            assert!(DeadlineInstruction::unpack(data).is_err());
+        }
+
+        // 10th byte overflowing u64, and 11 bytes
+        let mut data = vec![DEADLINE_VARINT_TAG];
+        data.extend([0xff; 9]);
+        data.push(1);
+        assert!(DeadlineInstruction::unpack(&data).is_ok());
+        *data.last_mut().unwrap() = 2;
+        assert!(DeadlineInstruction::unpack(&data).is_err());
+        *data.last_mut().unwrap() = 0xff;
+        data.push(1);
+        assert!(DeadlineInstruction::unpack(&data).is_err());
+    }
+
+    fn anchor_instructions() -> Vec<DeadlineInstruction> {
+        vec![
+            DeadlineInstruction::ValidateDeadline { deadline: 0 },
+            DeadlineInstruction::ValidateDeadline {
+                deadline: 1_800_000_000,
+            },
+            DeadlineInstruction::ValidateOracleClock {
+                max_drift: 30,
+                max_staleness_slots: 150,
+                feed_id: [7; 32],
+                min_publish_time: 1_700_000_000,
+            },
+            DeadlineInstruction::ValidateClockSanity { max_drift: 120 },
+            DeadlineInstruction::ValidateDutchAuction {
+                start_price: 1_000,
+                floor_price: 100,
+                start_time: 1_700_000_000,
+                end_time: 1_700_003_600,
+                mint: Pubkey::new_from_array([4; 32]),
+                destination: Pubkey::new_from_array([5; 32]),
+            },
+            DeadlineInstruction::ValidateSchedule {
+                schedule: WeeklySchedule::default().allow(Weekday::Friday, 9, 17),
+            },
+            DeadlineInstruction::ValidateExpression {
+                ops: vec![Op::TimeLe(1_700_000_000), Op::Not],
+            },
+            DeadlineInstruction::InitializeCooldown {
+                min_interval: 30 * 86_400,
+                seed: 1,
+            },
+            DeadlineInstruction::CheckAndBumpCooldown,
+            DeadlineInstruction::CreateSession {
+                delegate: Pubkey::new_from_array([7; 32]),
+                expires_at: 1_800_000_000,
+                spend_cap: Some(5_000),
+                seed: 2,
+            },
+            DeadlineInstruction::CreateSession {
+                delegate: Pubkey::new_from_array([7; 32]),
+                expires_at: 0,
+                spend_cap: None,
+                seed: 0,
+            },
+            DeadlineInstruction::RevokeSession,
+            DeadlineInstruction::ValidateSession { amount: 10 },
+        ]
+    }
+
+    #[test]
+    fn test_anchor_roundtrip() {
+        for instruction in anchor_instructions() {
+            let packed = instruction.pack_anchor().unwrap();
+            assert!(anchor::legacy_discriminator(packed[..8].try_into().unwrap()).is_some());
+            assert_eq!(DeadlineInstruction::unpack(&packed).unwrap(), instruction);
+        }
+    }
+
+    #[test]
+    fn test_anchor_borsh_layouts() {
+        let packed = DeadlineInstruction::ValidateDeadline { deadline: -2 }
+            .pack_anchor()
+            .unwrap();
+        assert_eq!(packed[..8], anchor::VALIDATE_DEADLINE);
+        assert_eq!(packed[8..], (-2i64).to_le_bytes());
+
+        let packed = DeadlineInstruction::ValidateExpression {
+            ops: vec![Op::SlotGe(1)],
+        }
+        .pack_anchor()
+        .unwrap();
+        assert_eq!(packed[8..12], [9, 0, 0, 0]);
+        assert_eq!(packed[12], 0x04);
+
+        let packed = DeadlineInstruction::CreateSession {
+            delegate: Pubkey::default(),
+            expires_at: 0,
+            spend_cap: None,
+            seed: 0,
+        }
+        .pack_anchor()
+        .unwrap();
+        assert_eq!(packed.len(), 8 + 32 + 8 + 1 + 8);
+    }
+
+    #[test]
+    fn test_anchor_invalid_arguments() {
+        for instruction in anchor_instructions() {
+            let packed = instruction.pack_anchor().unwrap();
+            assert!(DeadlineInstruction::unpack(&packed[..packed.len() - 1]).is_err());
+            let mut data = packed.clone();
+            data.push(0);
+            assert!(DeadlineInstruction::unpack(&data).is_err());
+        }
+
+        // Borsh option tag other than 0 or 1
+        let mut data = anchor::CREATE_SESSION.to_vec();
+        data.extend_from_slice(&[0; 40]);
+        data.push(2);
+        data.extend_from_slice(&[0; 8]);
+        assert!(DeadlineInstruction::unpack(&data).is_err());
+
+        // Versioned has no alias
+        assert!(DeadlineInstruction::Versioned {
+            instruction: InnerInstruction::try_from(DeadlineInstruction::RevokeSession).unwrap(),
+            constraints: Constraints {
+                min_slot: Some(1),
+                ..Constraints::default()
+            },
+        }
+        .pack_anchor()
+        .is_none());
+    }
+
     #[test]
     fn test_invalid_instruction_empty_data() {
         assert!(DeadlineInstruction::unpack(&[]).is_err());
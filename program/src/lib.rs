@@ -5,7 +5,8 @@
 //! payment protocol and other time-sensitive applications.
 //!
 //! ## Security Properties
-//! - Stateless: No account storage
+//! - Stateless: No account storage, except opt-in cooldown and session
+//!   accounts
 //! - Immutable: Deployed without upgrade authority
 //! - Consensus-based: Uses Clock sysvar (consensus-managed)
 //!
@@ -14,15 +15,23 @@
 //! a unix timestamp deadline. The instruction will fail atomically
 //! if the current time exceeds the deadline.
 
+pub mod anchor;
 pub mod error;
+pub mod expression;
+#[cfg(not(target_os = "solana"))]
+pub mod idl;
 pub mod instruction;
+pub mod oracle;
 pub mod processor;
+pub mod schedule;
+pub mod state;
 
 #[cfg(not(feature = "no-entrypoint"))]
 mod entrypoint {
     use super::*;
+    use error::DeadlineError;
     use solana_program::{
-        account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, pubkey::Pubkey,
+        account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, msg, pubkey::Pubkey,
     };
 
     entrypoint!(process_instruction);
@@ -32,7 +41,13 @@ mod entrypoint {
         _accounts: &[AccountInfo],
         instruction_data: &[u8],
     ) -> ProgramResult {
-        processor::process_instruction(_program_id, _accounts, instruction_data)
+        if let Err(error) = processor::process_instruction(_program_id, _accounts, instruction_data)
+        {
+            // Names the error in the logs, next to the bare custom code
+            msg!(error.to_str::<DeadlineError>());
+            return Err(error);
+        }
+        Ok(())
     }
 }
 
@@ -0,0 +1,166 @@
+//! Price oracle account layouts
+//!
+//! Only the fields needed for clock cross-checks are decoded; price data
+//! is skipped over but never interpreted.
+
+use crate::error::DeadlineError;
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+/// Pyth Solana receiver program, owner of all `PriceUpdateV2` accounts
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+/// Anchor account discriminator: `sha256("account:PriceUpdateV2")[..8]`
+pub const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+/// Pyth `VerificationLevel` as stored on-chain (borsh enum)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerificationLevel {
+    /// Verified with a subset of Wormhole guardian signatures
+    Partial {
+        /// Number of guardian signatures checked
+        num_signatures: u8,
+    },
+    /// Verified with a full guardian quorum
+    Full,
+}
+
+/// Decoded subset of a Pyth `PriceUpdateV2` account
+///
+/// Layout (borsh, after the 8-byte discriminator):
+/// - write_authority: Pubkey (32)
+/// - verification_level: enum (1 byte tag, +1 byte for `Partial`)
+/// - price_message: feed_id (32), price (i64), conf (u64), exponent (i32),
+///   publish_time (i64), prev_publish_time (i64), ema_price (i64),
+///   ema_conf (u64)
+/// - posted_slot: u64
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceUpdate {
+    /// How thoroughly the update was verified before being posted
+    pub verification_level: VerificationLevel,
+    /// Pyth price feed identifier
+    pub feed_id: [u8; 32],
+    /// Unix timestamp at which the publishers produced this price
+    pub publish_time: i64,
+    /// Slot in which the update was posted to this account
+    pub posted_slot: u64,
+}
+
+impl PriceUpdate {
+    /// Unpacks a `PriceUpdateV2` account from its raw data
+    ///
+    /// Trailing bytes are ignored, since the receiver allocates accounts
+    /// for the largest `VerificationLevel` variant.
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let rest = data
+            .strip_prefix(&PRICE_UPDATE_V2_DISCRIMINATOR)
+            .ok_or(DeadlineError::InvalidOracleAccount)?;
+
+        // write_authority
+        let (_, rest) = Self::take::<32>(rest)?;
+
+        let (&tag, rest) = rest
+            .split_first()
+            .ok_or(DeadlineError::InvalidOracleAccount)?;
+        let (verification_level, rest) = match tag {
+            0 => {
+                let (&num_signatures, rest) = rest
+                    .split_first()
+                    .ok_or(DeadlineError::InvalidOracleAccount)?;
+                (VerificationLevel::Partial { num_signatures }, rest)
+            }
+            1 => (VerificationLevel::Full, rest),
+            _ => return Err(DeadlineError::InvalidOracleAccount.into()),
+        };
+
+        let (feed_id, rest) = Self::take::<32>(rest)?;
+        // price (i64), conf (u64), exponent (i32)
+        let (_, rest) = Self::take::<20>(rest)?;
+        let (publish_time, rest) = Self::take::<8>(rest)?;
+        // prev_publish_time (i64), ema_price (i64), ema_conf (u64)
+        let (_, rest) = Self::take::<24>(rest)?;
+        let (posted_slot, _) = Self::take::<8>(rest)?;
+
+        Ok(Self {
+            verification_level,
+            feed_id: *feed_id,
+            publish_time: i64::from_le_bytes(*publish_time),
+            posted_slot: u64::from_le_bytes(*posted_slot),
+        })
+    }
+
+    fn take<const N: usize>(input: &[u8]) -> Result<(&[u8; N], &[u8]), ProgramError> {
+        input
+            .split_first_chunk::<N>()
+            .ok_or_else(|| DeadlineError::InvalidOracleAccount.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price_update_data(verification_level: VerificationLevel) -> Vec<u8> {
+        let mut data = PRICE_UPDATE_V2_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&[7u8; 32]); // write_authority
+        match verification_level {
+            VerificationLevel::Partial { num_signatures } => {
+                data.extend_from_slice(&[0, num_signatures])
+            }
+            VerificationLevel::Full => data.push(1),
+        }
+        data.extend_from_slice(&[9u8; 32]); // feed_id
+        data.extend_from_slice(&100i64.to_le_bytes()); // price
+        data.extend_from_slice(&1u64.to_le_bytes()); // conf
+        data.extend_from_slice(&(-8i32).to_le_bytes()); // exponent
+        data.extend_from_slice(&1700000000i64.to_le_bytes()); // publish_time
+        data.extend_from_slice(&1699999999i64.to_le_bytes()); // prev_publish_time
+        data.extend_from_slice(&100i64.to_le_bytes()); // ema_price
+        data.extend_from_slice(&1u64.to_le_bytes()); // ema_conf
+        data.extend_from_slice(&42u64.to_le_bytes()); // posted_slot
+        data
+    }
+
+    #[test]
+    fn test_unpack_full_verification() {
+        let update = PriceUpdate::unpack(&price_update_data(VerificationLevel::Full)).unwrap();
+        assert_eq!(update.verification_level, VerificationLevel::Full);
+        assert_eq!(update.feed_id, [9u8; 32]);
+        assert_eq!(update.publish_time, 1700000000);
+        assert_eq!(update.posted_slot, 42);
+    }
+
+    #[test]
+    fn test_unpack_partial_verification_with_padding() {
+        let level = VerificationLevel::Partial { num_signatures: 5 };
+        let mut data = price_update_data(level);
+        data.push(0); // receiver over-allocates for the larger variant
+        let update = PriceUpdate::unpack(&data).unwrap();
+        assert_eq!(update.verification_level, level);
+        assert_eq!(update.publish_time, 1700000000);
+        assert_eq!(update.posted_slot, 42);
+    }
+
+    #[test]
+    fn test_unpack_rejects_wrong_discriminator() {
+        let mut data = price_update_data(VerificationLevel::Full);
+        data[0] ^= 1;
+        assert_eq!(
+            PriceUpdate::unpack(&data).unwrap_err(),
+            DeadlineError::InvalidOracleAccount.into()
+        );
+    }
+
+    #[test]
+    fn test_unpack_rejects_truncated_data() {
+        let data = price_update_data(VerificationLevel::Full);
+        assert!(PriceUpdate::unpack(&data[..data.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_unpack_rejects_unknown_verification_level() {
+        let mut data = price_update_data(VerificationLevel::Full);
+        data[40] = 2;
+        assert!(PriceUpdate::unpack(&data).is_err());
+    }
+}
@@ -1,43 +1,229 @@
 //! Program instruction processor
 
-use crate::{error::DeadlineError, instruction::DeadlineInstruction};
+use crate::{
+    error::{self, DeadlineError},
+    expression::{self, Op},
+    instruction::{Constraints, DeadlineInstruction},
+    oracle::{PriceUpdate, VerificationLevel, PYTH_RECEIVER_PROGRAM_ID},
+    schedule::{hour_of_week, WeeklySchedule},
+    state::{
+        get_cooldown_address_and_bump_seed, get_session_address_and_bump_seed, Cooldown, Session,
+        COOLDOWN_SEED, SESSION_SEED,
+    },
+};
+use solana_instructions_sysvar::load_instruction_at_checked;
 use solana_program::{
-    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, msg, pubkey::Pubkey,
-    sysvar::Sysvar,
+    account_info::{next_account_info, AccountInfo},
+    clock::{Clock, DEFAULT_MS_PER_SLOT},
+    entrypoint::ProgramResult,
+    epoch_schedule::EpochSchedule,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::{Sysvar, SysvarSerialize},
 };
 
+/// SPL Token program
+const TOKEN_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// SPL Token-2022 program
+const TOKEN_2022_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// `TransferChecked` discriminator, shared by SPL Token and Token-2022
+const TRANSFER_CHECKED_DISCRIMINATOR: u8 = 12;
+
+/// Discriminator of `ValidateDeadline` in its original encoding, which
+/// keeps returning legacy error codes
+const LEGACY_DISCRIMINATOR: u8 = 0;
+
 /// Processes an instruction
 pub fn process_instruction(
-    _program_id: &Pubkey,
-    _accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    let instruction = DeadlineInstruction::unpack(instruction_data)?;
+    let result = DeadlineInstruction::unpack(instruction_data)
+        .and_then(|instruction| process(program_id, accounts, instruction));
+    if instruction_data.first() == Some(&LEGACY_DISCRIMINATOR) {
+        return result.map_err(error::to_legacy);
+    }
+    result
+}
 
+/// Processes an unpacked instruction
+fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction: DeadlineInstruction,
+) -> ProgramResult {
     match instruction {
         DeadlineInstruction::ValidateDeadline { deadline } => {
             msg!("Instruction: ValidateDeadline");
-            process_validate_deadline(deadline)
+            process_validate_deadline(accounts, deadline)
+        }
+        DeadlineInstruction::ValidateOracleClock {
+            max_drift,
+            max_staleness_slots,
+            feed_id,
+            min_publish_time,
+        } => {
+            msg!("Instruction: ValidateOracleClock");
+            process_validate_oracle_clock(
+                accounts,
+                max_drift,
+                max_staleness_slots,
+                &feed_id,
+                min_publish_time,
+            )
+        }
+        DeadlineInstruction::ValidateClockSanity { max_drift } => {
+            msg!("Instruction: ValidateClockSanity");
+            process_validate_clock_sanity(accounts, max_drift)
+        }
+        DeadlineInstruction::ValidateDutchAuction {
+            start_price,
+            floor_price,
+            start_time,
+            end_time,
+            mint,
+            destination,
+        } => {
+            msg!("Instruction: ValidateDutchAuction");
+            process_validate_dutch_auction(
+                accounts,
+                start_price,
+                floor_price,
+                start_time,
+                end_time,
+                &mint,
+                &destination,
+            )
+        }
+        DeadlineInstruction::ValidateSchedule { schedule } => {
+            msg!("Instruction: ValidateSchedule");
+            process_validate_schedule(accounts, &schedule)
+        }
+        DeadlineInstruction::ValidateExpression { ops } => {
+            msg!("Instruction: ValidateExpression");
+            process_validate_expression(accounts, &ops)
+        }
+        DeadlineInstruction::InitializeCooldown { min_interval, seed } => {
+            msg!("Instruction: InitializeCooldown");
+            process_initialize_cooldown(program_id, accounts, min_interval, seed)
+        }
+        DeadlineInstruction::CheckAndBumpCooldown => {
+            msg!("Instruction: CheckAndBumpCooldown");
+            process_check_and_bump_cooldown(program_id, accounts)
+        }
+        DeadlineInstruction::CreateSession {
+            delegate,
+            expires_at,
+            spend_cap,
+            seed,
+        } => {
+            msg!("Instruction: CreateSession");
+            process_create_session(program_id, accounts, delegate, expires_at, spend_cap, seed)
+        }
+        DeadlineInstruction::RevokeSession => {
+            msg!("Instruction: RevokeSession");
+            process_revoke_session(program_id, accounts)
+        }
+        DeadlineInstruction::ValidateSession { amount } => {
+            msg!("Instruction: ValidateSession");
+            process_validate_session(program_id, accounts, amount)
+        }
+        DeadlineInstruction::Versioned {
+            instruction,
+            constraints,
+        } => {
+            msg!("Instruction: Versioned");
+            // Read the clock the same way the wrapped instruction does
+            let clock = load_clock(accounts)?;
+            check_constraints(&constraints, &clock)?;
+            process(program_id, accounts, instruction.into_inner())
         }
     }
 }
 
-/// Validates that current time is before or equal to deadline
+/// Checks envelope constraints against the clock
 ///
 /// ## Behavior
-/// - Succeeds when: clock.unix_timestamp <= deadline (inclusive)
-/// - Fails when: clock.unix_timestamp > deadline (exclusive)
-///
-/// ## Special Cases
-/// - deadline = 0: Never expires (always succeeds)
-/// - deadline < 0: Always expired (current time is positive)
+/// - Fails when: current_time < not_before
+/// - Fails when: slot < min_slot or slot > max_slot
+fn check_constraints(constraints: &Constraints, clock: &Clock) -> ProgramResult {
+    if let Some(not_before) = constraints.not_before {
+        if clock.unix_timestamp < not_before {
+            msg!(
+                "Not yet valid: current={}, not_before={}",
+                clock.unix_timestamp,
+                not_before
+            );
+            return Err(DeadlineError::NotYetValid.into());
+        }
+    }
+    let min_slot = constraints.min_slot.unwrap_or(0);
+    let max_slot = constraints.max_slot.unwrap_or(u64::MAX);
+    if clock.slot < min_slot || clock.slot > max_slot {
+        msg!(
+            "Slot out of range: slot={}, min_slot={}, max_slot={}",
+            clock.slot,
+            min_slot,
+            max_slot
+        );
+        return Err(DeadlineError::SlotOutOfRange.into());
+    }
+    Ok(())
+}
+
+/// Validates that current time is before or equal to deadline
 ///
 /// ## Pattern
 /// Follows Solana Foundation attestation service pattern for time validation.
 /// Uses Clock::get() (modern Solana pattern, no account passing required).
-fn process_validate_deadline(deadline: i64) -> ProgramResult {
-    let clock = Clock::get()?;
+/// An explicit Clock sysvar account is accepted as well, see `load_clock`.
+fn process_validate_deadline(accounts: &[AccountInfo], deadline: i64) -> ProgramResult {
+    let clock = load_clock(accounts)?;
+    validate_deadline(deadline, clock.unix_timestamp)
+}
+
+/// Reads the Clock sysvar, see `load_sysvar`
+///
+/// Every instruction reads the clock through here.
+fn load_clock(accounts: &[AccountInfo]) -> Result<Clock, ProgramError> {
+    load_sysvar(accounts)
+}
+
+/// Reads a sysvar from the instruction's accounts or its syscall
+///
+/// Some SVM rollups and forks only support reading sysvars from accounts,
+/// so when the sysvar's account is passed, at any position, it is
+/// deserialized instead of calling the syscall. Other accounts are
+/// ignored, as they were before sysvar accounts were accepted; Anchor
+/// clients pass the program ID in place of an omitted optional account.
+fn load_sysvar<T: SysvarSerialize>(accounts: &[AccountInfo]) -> Result<T, ProgramError> {
+    match accounts.iter().find(|info| T::check_id(info.key)) {
+        Some(sysvar_info) => T::from_account_info(sysvar_info),
+        None => T::get(),
+    }
+}
 
+/// Checks a unix timestamp against a deadline
+///
+/// Shared with companion programs so every deadline in the workspace has
+/// exactly the same semantics as `ValidateDeadline`.
+///
+/// ## Behavior
+/// - Succeeds when: unix_timestamp <= deadline (inclusive)
+/// - Fails when: unix_timestamp > deadline (exclusive)
+///
+/// ## Special Cases
+/// - deadline = 0: Never expires (always succeeds)
+/// - deadline < 0: Always expired (current time is positive)
+pub fn validate_deadline(deadline: i64, unix_timestamp: i64) -> ProgramResult {
     // Special case: 0 means never expires
     if deadline == 0 {
         msg!("Deadline: never expires (deadline = 0)");
@@ -45,10 +231,10 @@ fn process_validate_deadline(deadline: i64) -> ProgramResult {
     }
 
     // Standard validation: current_time > deadline → expired
-    if clock.unix_timestamp > deadline {
+    if deadline_expired(deadline, unix_timestamp) {
         msg!(
             "Deadline expired: current={}, deadline={}",
-            clock.unix_timestamp,
+            unix_timestamp,
             deadline
         );
         return Err(DeadlineError::DeadlineExpired.into());
@@ -56,17 +242,769 @@ fn process_validate_deadline(deadline: i64) -> ProgramResult {
 
     msg!(
         "Deadline valid: current={}, deadline={}, remaining={}s",
-        clock.unix_timestamp,
+        unix_timestamp,
         deadline,
-        deadline - clock.unix_timestamp
+        deadline.saturating_sub(unix_timestamp)
+    );
+    Ok(())
+}
+
+/// Returns whether a deadline has passed at `unix_timestamp`
+///
+/// The predicate behind `validate_deadline`, for companion programs that
+/// need to act after expiry (e.g. refunds) rather than fail on it.
+pub fn deadline_expired(deadline: i64, unix_timestamp: i64) -> bool {
+    deadline != 0 && unix_timestamp > deadline
+}
+
+/// Validates the cluster clock against a Pyth price update
+///
+/// ## Behavior
+/// - Fails when: the account is not a fully verified `PriceUpdateV2`
+///   owned by the Pyth receiver program
+/// - Fails when: the update's feed_id != feed_id
+/// - Fails when: clock.slot - posted_slot > max_staleness_slots
+/// - Fails when: publish_time < min_publish_time
+/// - Fails when: |clock.unix_timestamp - publish_time| > max_drift
+///
+/// ## Pattern
+/// Neither staleness bound reads the clock being checked: the update's
+/// posting is bounded in slots, and its publish_time by the signer's own
+/// min_publish_time, since an update can be posted long after it was
+/// published.
+fn process_validate_oracle_clock(
+    accounts: &[AccountInfo],
+    max_drift: u64,
+    max_staleness_slots: u64,
+    feed_id: &[u8; 32],
+    min_publish_time: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let oracle_info = next_account_info(account_info_iter)?;
+
+    if oracle_info.owner != &PYTH_RECEIVER_PROGRAM_ID {
+        msg!("Oracle account not owned by the Pyth receiver program");
+        return Err(DeadlineError::InvalidOracleAccount.into());
+    }
+
+    let update = PriceUpdate::unpack(&oracle_info.try_borrow_data()?)?;
+    if update.verification_level != VerificationLevel::Full {
+        msg!("Oracle update is not fully verified");
+        return Err(DeadlineError::InvalidOracleAccount.into());
+    }
+    if &update.feed_id != feed_id {
+        msg!("Oracle update is for another price feed");
+        return Err(DeadlineError::InvalidOracleAccount.into());
+    }
+
+    let clock = load_clock(accounts)?;
+
+    let age_slots = clock.slot.saturating_sub(update.posted_slot);
+    if age_slots > max_staleness_slots {
+        msg!(
+            "Oracle stale: current_slot={}, posted_slot={}, max_staleness_slots={}",
+            clock.slot,
+            update.posted_slot,
+            max_staleness_slots
+        );
+        return Err(DeadlineError::OracleStale.into());
+    }
+    if update.publish_time < min_publish_time {
+        msg!(
+            "Oracle stale: publish_time={}, min_publish_time={}",
+            update.publish_time,
+            min_publish_time
+        );
+        return Err(DeadlineError::OracleStale.into());
+    }
+
+    let drift = clock.unix_timestamp.abs_diff(update.publish_time);
+    if drift > max_drift {
+        msg!(
+            "Clock drift: current={}, publish_time={}, drift={}s, max_drift={}s",
+            clock.unix_timestamp,
+            update.publish_time,
+            drift,
+            max_drift
+        );
+        return Err(DeadlineError::ClockDrift.into());
+    }
+
+    msg!(
+        "Clock consistent with oracle: current={}, publish_time={}, drift={}s",
+        clock.unix_timestamp,
+        update.publish_time,
+        drift
+    );
+    Ok(())
+}
+
+/// Validates the cluster clock against nominal slot progression
+///
+/// ## Behavior
+/// - Fails when: |clock.unix_timestamp - expected| > max_drift, where
+///   expected = epoch_start_timestamp + elapsed_slots * DEFAULT_MS_PER_SLOT
+fn process_validate_clock_sanity(accounts: &[AccountInfo], max_drift: u64) -> ProgramResult {
+    let clock = load_clock(accounts)?;
+    let epoch_schedule: EpochSchedule = load_sysvar(accounts)?;
+
+    let expected = slot_derived_unix_timestamp(&clock, &epoch_schedule);
+    let drift = clock.unix_timestamp.abs_diff(expected);
+    if drift > max_drift {
+        msg!(
+            "Clock drift: current={}, expected={}, drift={}s, max_drift={}s",
+            clock.unix_timestamp,
+            expected,
+            drift,
+            max_drift
+        );
+        return Err(DeadlineError::ClockDrift.into());
+    }
+
+    msg!(
+        "Clock consistent with slot progression: current={}, expected={}, drift={}s",
+        clock.unix_timestamp,
+        expected,
+        drift
+    );
+    Ok(())
+}
+
+/// Computes the unix timestamp implied by nominal slot duration since the
+/// start of the clock's epoch, saturating at the i64 range
+fn slot_derived_unix_timestamp(clock: &Clock, epoch_schedule: &EpochSchedule) -> i64 {
+    let first_slot = epoch_schedule.get_first_slot_in_epoch(clock.epoch);
+    let elapsed_slots = clock.slot.saturating_sub(first_slot);
+    let elapsed_secs = (elapsed_slots as i128 * DEFAULT_MS_PER_SLOT as i128) / 1_000;
+    (clock.epoch_start_timestamp as i128 + elapsed_secs).clamp(i64::MIN as i128, i64::MAX as i128)
+        as i64
+}
+
+/// Validates that the transaction pays at least the current auction price
+///
+/// ## Behavior
+/// - Fails when: no top-level `TransferChecked` to SPL Token or Token-2022
+///   of `mint` to `destination` has amount >= the price returned by
+///   `dutch_auction_price`
+///
+/// Transfers made via CPI are not visible in the Instructions sysvar and
+/// do not count.
+fn process_validate_dutch_auction(
+    accounts: &[AccountInfo],
+    start_price: u64,
+    floor_price: u64,
+    start_time: i64,
+    end_time: i64,
+    mint: &Pubkey,
+    destination: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let instructions_info = next_account_info(account_info_iter)?;
+
+    let clock = load_clock(accounts)?;
+    let required = dutch_auction_price(
+        start_price,
+        floor_price,
+        start_time,
+        end_time,
+        clock.unix_timestamp,
+    );
+
+    let mut best_paid = None;
+    for index in 0.. {
+        let instruction = match load_instruction_at_checked(index, instructions_info) {
+            Ok(instruction) => instruction,
+            Err(ProgramError::InvalidArgument) => break,
+            Err(e) => return Err(e),
+        };
+        if instruction.program_id != TOKEN_PROGRAM_ID
+            && instruction.program_id != TOKEN_2022_PROGRAM_ID
+        {
+            continue;
+        }
+        // TransferChecked accounts: source, mint, destination, authority
+        let pays = |position: usize, key: &Pubkey| {
+            instruction
+                .accounts
+                .get(position)
+                .is_some_and(|meta| &meta.pubkey == key)
+        };
+        if !pays(1, mint) || !pays(2, destination) {
+            continue;
+        }
+        if let Some(amount) = transfer_checked_amount(&instruction.data) {
+            best_paid = best_paid.max(Some(amount));
+        }
+    }
+
+    match best_paid {
+        Some(paid) if paid >= required => {
+            msg!(
+                "Auction price paid: current={}, required={}, paid={}",
+                clock.unix_timestamp,
+                required,
+                paid
+            );
+            Ok(())
+        }
+        _ => {
+            msg!(
+                "Insufficient payment: current={}, required={}, paid={}",
+                clock.unix_timestamp,
+                required,
+                best_paid.unwrap_or(0)
+            );
+            Err(DeadlineError::InsufficientPayment.into())
+        }
+    }
+}
+
+/// Decodes the amount of a `TransferChecked` instruction
+///
+/// Layout: discriminator (u8), amount (u64, little-endian), decimals (u8)
+fn transfer_checked_amount(data: &[u8]) -> Option<u64> {
+    match data.split_first_chunk::<1>()? {
+        ([TRANSFER_CHECKED_DISCRIMINATOR], rest) => match rest.split_first_chunk::<8>()? {
+            (amount, [_decimals]) => Some(u64::from_le_bytes(*amount)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Computes the Dutch-auction price at `unix_timestamp`
+///
+/// Falls linearly from `start_price` to `floor_price` between `start_time`
+/// and `end_time`, clamped to those prices outside the range. The price
+/// decrease is rounded down, so the required price is rounded up.
+/// Intermediate math is done in u128/i128 and cannot overflow.
+fn dutch_auction_price(
+    start_price: u64,
+    floor_price: u64,
+    start_time: i64,
+    end_time: i64,
+    unix_timestamp: i64,
+) -> u64 {
+    if unix_timestamp <= start_time {
+        return start_price;
+    }
+    if unix_timestamp >= end_time {
+        return floor_price;
+    }
+
+    // start_time < unix_timestamp < end_time, so both spans are positive
+    let elapsed = (unix_timestamp as i128 - start_time as i128) as u128;
+    let duration = (end_time as i128 - start_time as i128) as u128;
+    let drop = start_price.saturating_sub(floor_price) as u128;
+
+    // drop < 2^64 and elapsed < 2^64, so the product fits in u128;
+    // elapsed < duration keeps the quotient below drop
+    let decrease = drop * elapsed / duration;
+    start_price - decrease as u64
+}
+
+/// Validates that current time falls into an allowed hour of the week
+///
+/// ## Behavior
+/// - Succeeds when: the schedule bit for the current UTC hour of the week
+///   (0 = Monday 00:00-00:59) is set
+fn process_validate_schedule(accounts: &[AccountInfo], schedule: &WeeklySchedule) -> ProgramResult {
+    let clock = load_clock(accounts)?;
+    let hour = hour_of_week(clock.unix_timestamp);
+
+    if !schedule.allows_hour(hour) {
+        msg!(
+            "Outside schedule: current={}, hour_of_week={}",
+            clock.unix_timestamp,
+            hour
+        );
+        return Err(DeadlineError::OutsideSchedule.into());
+    }
+
+    msg!(
+        "Within schedule: current={}, hour_of_week={}",
+        clock.unix_timestamp,
+        hour
+    );
+    Ok(())
+}
+
+/// Validates that a time-predicate expression holds at the current clock
+fn process_validate_expression(accounts: &[AccountInfo], ops: &[Op]) -> ProgramResult {
+    let clock = load_clock(accounts)?;
+
+    if !expression::evaluate(ops, &clock)? {
+        msg!(
+            "Expression not satisfied: current={}, slot={}, epoch={}",
+            clock.unix_timestamp,
+            clock.slot,
+            clock.epoch
+        );
+        return Err(DeadlineError::ExpressionFalse.into());
+    }
+
+    msg!(
+        "Expression satisfied: current={}, slot={}, epoch={}",
+        clock.unix_timestamp,
+        clock.slot,
+        clock.epoch
+    );
+    Ok(())
+}
+
+/// Creates a cooldown that allows the first action immediately
+fn process_initialize_cooldown(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    min_interval: u64,
+    seed: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let cooldown_info = next_account_info(account_info_iter)?;
+    let _system_program_info = next_account_info(account_info_iter)?;
+
+    if !payer_info.is_signer || !authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (address, bump) = get_cooldown_address_and_bump_seed(authority_info.key, seed, program_id);
+    if cooldown_info.key != &address {
+        return Err(DeadlineError::InvalidCooldown.into());
+    }
+
+    let seed_bytes = seed.to_le_bytes();
+    let signer_seeds: &[&[u8]] = &[
+        COOLDOWN_SEED,
+        authority_info.key.as_ref(),
+        &seed_bytes,
+        &[bump],
+    ];
+    create_pda_account(
+        payer_info,
+        cooldown_info,
+        program_id,
+        Cooldown::LEN,
+        signer_seeds,
+    )?;
+
+    Cooldown {
+        authority: *authority_info.key,
+        last_executed: i64::MIN,
+        min_interval,
+        seed,
+        bump,
+    }
+    .pack(&mut cooldown_info.try_borrow_mut_data()?)?;
+
+    msg!(
+        "Cooldown initialized: authority={}, min_interval={}s",
+        authority_info.key,
+        min_interval
+    );
+    Ok(())
+}
+
+/// Records an action if the cooldown has elapsed
+///
+/// ## Behavior
+/// - Succeeds when: current_time - last_executed >= min_interval
+/// - Fails when: the authority did not sign or the interval has not elapsed
+fn process_check_and_bump_cooldown(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let cooldown_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+
+    if cooldown_info.owner != program_id {
+        return Err(DeadlineError::InvalidCooldown.into());
+    }
+    let mut cooldown = Cooldown::unpack(&cooldown_info.try_borrow_data()?)?;
+    if !authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if authority_info.key != &cooldown.authority {
+        return Err(DeadlineError::Unauthorized.into());
+    }
+
+    let clock = load_clock(accounts)?;
+    if !cooldown.is_ready(clock.unix_timestamp) {
+        msg!(
+            "Cooldown active: current={}, last_executed={}, min_interval={}s",
+            clock.unix_timestamp,
+            cooldown.last_executed,
+            cooldown.min_interval
+        );
+        return Err(DeadlineError::CooldownActive.into());
+    }
+
+    cooldown.last_executed = clock.unix_timestamp;
+    cooldown.pack(&mut cooldown_info.try_borrow_mut_data()?)?;
+
+    msg!("Cooldown bumped: current={}", clock.unix_timestamp);
+    Ok(())
+}
+
+/// Creates a session for `delegate`, signed by the owner
+fn process_create_session(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    delegate: Pubkey,
+    expires_at: i64,
+    spend_cap: Option<u64>,
+    seed: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let session_info = next_account_info(account_info_iter)?;
+    let _system_program_info = next_account_info(account_info_iter)?;
+
+    if !payer_info.is_signer || !owner_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (address, bump) = get_session_address_and_bump_seed(owner_info.key, seed, program_id);
+    if session_info.key != &address {
+        return Err(DeadlineError::InvalidSession.into());
+    }
+
+    let seed_bytes = seed.to_le_bytes();
+    let signer_seeds: &[&[u8]] = &[SESSION_SEED, owner_info.key.as_ref(), &seed_bytes, &[bump]];
+    create_pda_account(
+        payer_info,
+        session_info,
+        program_id,
+        Session::LEN,
+        signer_seeds,
+    )?;
+
+    Session {
+        owner: *owner_info.key,
+        delegate,
+        expires_at,
+        spend_cap,
+        spent: 0,
+        revoked: false,
+        seed,
+        bump,
+    }
+    .pack(&mut session_info.try_borrow_mut_data()?)?;
+
+    msg!(
+        "Session created: delegate={}, expires_at={}",
+        delegate,
+        expires_at
+    );
+    Ok(())
+}
+
+/// Marks a session as revoked
+fn process_revoke_session(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let session_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+
+    let mut session = load_session(program_id, session_info)?;
+    if !owner_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if owner_info.key != &session.owner {
+        return Err(DeadlineError::Unauthorized.into());
+    }
+
+    session.revoked = true;
+    session.pack(&mut session_info.try_borrow_mut_data()?)?;
+
+    msg!("Session revoked: delegate={}", session.delegate);
+    Ok(())
+}
+
+/// Validates a delegate's authorization against its session
+///
+/// ## Behavior
+/// - Fails when: the delegate did not sign or does not match the session
+/// - Fails when: the session is revoked
+/// - Fails when: current_time > expires_at (0 = never expires, like
+///   `ValidateDeadline`)
+/// - Fails when: amount is not the total of the delegate's transfers, see
+///   `delegate_transfer_total`
+/// - Fails when: spent + amount exceeds the spend cap
+fn process_validate_session(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let session_info = next_account_info(account_info_iter)?;
+    let delegate_info = next_account_info(account_info_iter)?;
+    let instructions_info = next_account_info(account_info_iter)?;
+
+    let mut session = load_session(program_id, session_info)?;
+    if !delegate_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if delegate_info.key != &session.delegate {
+        return Err(DeadlineError::Unauthorized.into());
+    }
+    if session.revoked {
+        msg!("Session revoked");
+        return Err(DeadlineError::SessionRevoked.into());
+    }
+
+    let clock = load_clock(accounts)?;
+    if deadline_expired(session.expires_at, clock.unix_timestamp) {
+        msg!(
+            "Session expired: current={}, expires_at={}",
+            clock.unix_timestamp,
+            session.expires_at
+        );
+        return Err(DeadlineError::SessionExpired.into());
+    }
+
+    let transferred = delegate_transfer_total(program_id, instructions_info, &session.delegate)?;
+    if transferred != amount {
+        msg!(
+            "Session amount mismatch: amount={}, transferred={}",
+            amount,
+            transferred
+        );
+        return Err(DeadlineError::SessionTransferMismatch.into());
+    }
+
+    let spent = session
+        .spent
+        .checked_add(amount)
+        .ok_or(DeadlineError::SpendCapExceeded)?;
+    if let Some(spend_cap) = session.spend_cap {
+        if spent > spend_cap {
+            msg!(
+                "Spend cap exceeded: spent={}, amount={}, cap={}",
+                session.spent,
+                amount,
+                spend_cap
+            );
+            return Err(DeadlineError::SpendCapExceeded.into());
+        }
+    }
+
+    if amount != 0 {
+        session.spent = spent;
+        session.pack(&mut session_info.try_borrow_mut_data()?)?;
+    }
+
+    msg!(
+        "Session valid: current={}, expires_at={}, spent={}",
+        clock.unix_timestamp,
+        session.expires_at,
+        session.spent
     );
     Ok(())
 }
 
+/// Sums the top-level `TransferChecked` instructions signed by `delegate`
+///
+/// Every top-level instruction the delegate signs, other than this
+/// program's, must be an SPL Token or Token-2022 `TransferChecked` with the
+/// delegate as authority, so its signature cannot move tokens the sum does
+/// not see (e.g. through a CPI). All of them must share one source and mint.
+fn delegate_transfer_total(
+    program_id: &Pubkey,
+    instructions_info: &AccountInfo,
+    delegate: &Pubkey,
+) -> Result<u64, ProgramError> {
+    let mut total = 0u64;
+    let mut source_and_mint = None;
+    for index in 0.. {
+        let instruction = match load_instruction_at_checked(index, instructions_info) {
+            Ok(instruction) => instruction,
+            Err(ProgramError::InvalidArgument) => break,
+            Err(e) => return Err(e),
+        };
+        let signed = instruction
+            .accounts
+            .iter()
+            .any(|meta| meta.is_signer && &meta.pubkey == delegate);
+        if instruction.program_id == *program_id || !signed {
+            continue;
+        }
+
+        let is_token_program = instruction.program_id == TOKEN_PROGRAM_ID
+            || instruction.program_id == TOKEN_2022_PROGRAM_ID;
+        // TransferChecked accounts: source, mint, destination, authority
+        let transfer = match instruction.accounts.as_slice() {
+            [source, mint, _, authority, ..]
+                if is_token_program && &authority.pubkey == delegate =>
+            {
+                transfer_checked_amount(&instruction.data)
+                    .map(|amount| (source.pubkey, mint.pubkey, amount))
+            }
+            _ => None,
+        };
+        let Some((source, mint, amount)) = transfer else {
+            msg!("Delegate signed an instruction other than TransferChecked");
+            return Err(DeadlineError::Unauthorized.into());
+        };
+
+        if *source_and_mint.get_or_insert((source, mint)) != (source, mint) {
+            msg!("Delegate transfers differ in source or mint");
+            return Err(DeadlineError::SessionTransferMismatch.into());
+        }
+        total = total
+            .checked_add(amount)
+            .ok_or(DeadlineError::SessionTransferMismatch)?;
+    }
+    Ok(total)
+}
+
+/// Loads a session owned by this program
+fn load_session(program_id: &Pubkey, session_info: &AccountInfo) -> Result<Session, ProgramError> {
+    if session_info.owner != program_id {
+        return Err(DeadlineError::InvalidSession.into());
+    }
+    Session::unpack(&session_info.try_borrow_data()?)
+}
+
+/// Creates a rent-exempt PDA owned by this program, funded by `payer_info`
+fn create_pda_account<'a>(
+    payer_info: &AccountInfo<'a>,
+    account_info: &AccountInfo<'a>,
+    program_id: &Pubkey,
+    space: usize,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    invoke_signed(
+        &solana_system_interface::instruction::create_account(
+            payer_info.key,
+            account_info.key,
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            program_id,
+        ),
+        &[payer_info.clone(), account_info.clone()],
+        &[signer_seeds],
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn clock_at(epoch: u64, slot: u64, epoch_start_timestamp: i64) -> Clock {
+        Clock {
+            slot,
+            epoch_start_timestamp,
+            epoch,
+            leader_schedule_epoch: epoch + 1,
+            unix_timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_validate_deadline_inclusive_boundary() {
+        assert!(validate_deadline(1700000000, 1700000000).is_ok());
+        assert!(validate_deadline(1700000001, 1700000000).is_ok());
+        assert_eq!(
+            validate_deadline(1699999999, 1700000000).unwrap_err(),
+            DeadlineError::DeadlineExpired.into()
+        );
+    }
+
+    #[test]
+    fn test_validate_deadline_special_cases() {
+        assert!(validate_deadline(0, i64::MAX).is_ok());
+        assert!(validate_deadline(i64::MAX, i64::MIN).is_ok());
+        assert!(validate_deadline(-1000, 1700000000).is_err());
+        assert!(validate_deadline(i64::MIN, 1700000000).is_err());
+    }
+
+    #[test]
+    fn test_deadline_expired_matches_validate_deadline() {
+        for (deadline, now) in [
+            (0, i64::MAX),
+            (1700000000, 1700000000),
+            (1700000000, 1700000001),
+            (-1, 0),
+            (i64::MAX, i64::MAX),
+        ] {
+            assert_eq!(
+                deadline_expired(deadline, now),
+                validate_deadline(deadline, now).is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_dutch_auction_price_boundaries() {
+        let price = |now| dutch_auction_price(1000, 100, 1700000000, 1700000900, now);
+
+        assert_eq!(price(i64::MIN), 1000);
+        assert_eq!(price(1700000000), 1000);
+        assert_eq!(price(1700000450), 550);
+        assert_eq!(price(1700000900), 100);
+        assert_eq!(price(i64::MAX), 100);
+    }
+
+    #[test]
+    fn test_dutch_auction_price_rounds_up() {
+        // 1 second into a 3 second auction dropping 10 units: 10/3 = 3.33
+        assert_eq!(dutch_auction_price(10, 0, 0, 3, 1), 7);
+        // One second before the end the price is still above the floor
+        assert_eq!(dutch_auction_price(10, 0, 0, 3, 2), 4);
+        // Tiny drop over a long auction stays at the start price
+        assert_eq!(dutch_auction_price(1, 0, 0, 1_000_000, 999_999), 1);
+    }
+
+    #[test]
+    fn test_dutch_auction_price_extremes_do_not_overflow() {
+        assert_eq!(
+            dutch_auction_price(u64::MAX, 0, i64::MIN, i64::MAX, 0),
+            u64::MAX / 2
+        );
+        assert_eq!(
+            dutch_auction_price(u64::MAX, 0, i64::MIN, i64::MAX, i64::MAX - 1),
+            1
+        );
+        assert_eq!(dutch_auction_price(u64::MAX, u64::MAX, 0, 1, 0), u64::MAX);
+    }
+
+    #[test]
+    fn test_slot_derived_timestamp_epoch_start() {
+        let schedule = EpochSchedule::without_warmup();
+        let first_slot = schedule.get_first_slot_in_epoch(10);
+        let clock = clock_at(10, first_slot, 1700000000);
+        assert_eq!(slot_derived_unix_timestamp(&clock, &schedule), 1700000000);
+    }
+
+    #[test]
+    fn test_slot_derived_timestamp_nominal_progression() {
+        let schedule = EpochSchedule::without_warmup();
+        let first_slot = schedule.get_first_slot_in_epoch(10);
+
+        // 1000 slots at 400ms = 400s
+        let clock = clock_at(10, first_slot + 1000, 1700000000);
+        assert_eq!(slot_derived_unix_timestamp(&clock, &schedule), 1700000400);
+
+        // Partial seconds round toward the epoch start
+        let clock = clock_at(10, first_slot + 3, 1700000000);
+        assert_eq!(slot_derived_unix_timestamp(&clock, &schedule), 1700000001);
+    }
+
+    #[test]
+    fn test_slot_derived_timestamp_with_warmup() {
+        let schedule = EpochSchedule::default();
+        let first_slot = schedule.get_first_slot_in_epoch(3);
+        let clock = clock_at(3, first_slot + 10, 1700000000);
+        assert_eq!(slot_derived_unix_timestamp(&clock, &schedule), 1700000004);
+    }
+
+    #[test]
+    fn test_slot_derived_timestamp_saturates() {
+        let schedule = EpochSchedule::without_warmup();
+        let clock = clock_at(0, u64::MAX, i64::MAX);
+        assert_eq!(slot_derived_unix_timestamp(&clock, &schedule), i64::MAX);
+    }
+
     // Note: These tests require the Clock sysvar which is only available
     // in on-chain execution. They are marked as #[ignore] for unit tests
     // and should be verified using integration tests with solana-program-test.
@@ -76,7 +1014,7 @@ mod tests {
     fn test_validate_deadline_future() {
         // Far future deadline should succeed
         let future_deadline = i64::MAX;
-        let result = process_validate_deadline(future_deadline);
+        let result = process_validate_deadline(&[], future_deadline);
         assert!(result.is_ok());
     }
 
@@ -86,7 +1024,7 @@ mod tests {
         // Past deadline should fail (Unix epoch)
         // Use 1 instead for actual past
         let past_deadline = 1i64;
-        let result = process_validate_deadline(past_deadline);
+        let result = process_validate_deadline(&[], past_deadline);
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), DeadlineError::DeadlineExpired.into());
     }
@@ -95,7 +1033,7 @@ mod tests {
     #[ignore]
     fn test_validate_deadline_never_expires() {
         // Zero deadline means never expires
-        let result = process_validate_deadline(0);
+        let result = process_validate_deadline(&[], 0);
         assert!(result.is_ok());
     }
 
@@ -103,11 +1041,11 @@ mod tests {
     #[ignore]
     fn test_validate_deadline_negative() {
         // Negative deadlines always expired (current time is positive)
-        let result = process_validate_deadline(-1000);
+        let result = process_validate_deadline(&[], -1000);
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), DeadlineError::DeadlineExpired.into());
 
-        let result = process_validate_deadline(i64::MIN);
+        let result = process_validate_deadline(&[], i64::MIN);
         assert!(result.is_err());
     }
 
@@ -116,7 +1054,7 @@ mod tests {
     fn test_validate_deadline_boundary() {
         // Test recent past (likely expired)
         let recent_past = 1000000000i64; // Year 2001
-        let result = process_validate_deadline(recent_past);
+        let result = process_validate_deadline(&[], recent_past);
         assert!(result.is_err());
     }
 }
@@ -0,0 +1,163 @@
+//! Recurring weekly time windows
+//!
+//! A schedule is a 168-bit bitmap with one bit per UTC hour of the week,
+//! starting at Monday 00:00. Bit `n` lives in byte `n / 8` at position
+//! `n % 8` (least significant bit first).
+
+/// Seconds in an hour
+const SECONDS_PER_HOUR: i64 = 3_600;
+
+/// Seconds in a day
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Hours in a week, and bits in a schedule
+pub const HOURS_PER_WEEK: usize = 168;
+
+/// Day of the week of the unix epoch (1970-01-01 was a Thursday)
+const EPOCH_WEEKDAY: i64 = Weekday::Thursday as i64;
+
+/// Day of the week, numbered from Monday as in ISO 8601
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Weekday {
+    Monday = 0,
+    Tuesday = 1,
+    Wednesday = 2,
+    Thursday = 3,
+    Friday = 4,
+    Saturday = 5,
+    Sunday = 6,
+}
+
+impl Weekday {
+    /// All days, Monday first
+    pub const ALL: [Weekday; 7] = [
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+        Weekday::Saturday,
+        Weekday::Sunday,
+    ];
+}
+
+/// Returns the UTC hour of the week (0 = Monday 00:00-00:59) for a unix
+/// timestamp, including timestamps before the epoch
+pub fn hour_of_week(unix_timestamp: i64) -> usize {
+    let days = unix_timestamp.div_euclid(SECONDS_PER_DAY);
+    let weekday = (days + EPOCH_WEEKDAY).rem_euclid(7);
+    let hour = unix_timestamp.rem_euclid(SECONDS_PER_DAY) / SECONDS_PER_HOUR;
+    (weekday * 24 + hour) as usize
+}
+
+/// Weekly UTC schedule of allowed hours
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WeeklySchedule(pub [u8; WeeklySchedule::LEN]);
+
+impl WeeklySchedule {
+    /// Serialized size of a schedule
+    pub const LEN: usize = HOURS_PER_WEEK / 8;
+
+    /// A schedule that allows every hour
+    pub const ALWAYS: Self = Self([0xff; Self::LEN]);
+
+    /// Allows `start_hour..end_hour` (UTC, end exclusive) on `weekday`
+    ///
+    /// Hours past 24 are clamped, so `allow(day, 0, 24)` allows the
+    /// whole day.
+    pub fn allow(mut self, weekday: Weekday, start_hour: u8, end_hour: u8) -> Self {
+        let day_start = weekday as usize * 24;
+        for hour in start_hour.min(24)..end_hour.min(24) {
+            self.set(day_start + hour as usize, true);
+        }
+        self
+    }
+
+    /// Blocks `start_hour..end_hour` (UTC, end exclusive) on `weekday`
+    pub fn block(mut self, weekday: Weekday, start_hour: u8, end_hour: u8) -> Self {
+        let day_start = weekday as usize * 24;
+        for hour in start_hour.min(24)..end_hour.min(24) {
+            self.set(day_start + hour as usize, false);
+        }
+        self
+    }
+
+    /// Returns whether the given hour of the week is allowed
+    pub fn allows_hour(&self, hour_of_week: usize) -> bool {
+        hour_of_week < HOURS_PER_WEEK && self.0[hour_of_week / 8] & (1 << (hour_of_week % 8)) != 0
+    }
+
+    /// Returns whether a unix timestamp falls into an allowed hour
+    pub fn allows(&self, unix_timestamp: i64) -> bool {
+        self.allows_hour(hour_of_week(unix_timestamp))
+    }
+
+    fn set(&mut self, hour_of_week: usize, allowed: bool) {
+        let mask = 1 << (hour_of_week % 8);
+        if allowed {
+            self.0[hour_of_week / 8] |= mask;
+        } else {
+            self.0[hour_of_week / 8] &= !mask;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hour_of_week_known_dates() {
+        // Thu Jan 1 1970 00:00:00 UTC
+        assert_eq!(hour_of_week(0), 3 * 24);
+        // Tue Nov 14 2023 22:13:20 UTC
+        assert_eq!(hour_of_week(1700000000), 24 + 22);
+        // Mon Jan 1 2024 00:00:00 UTC
+        assert_eq!(hour_of_week(1704067200), 0);
+        // Sun Dec 31 2023 23:59:59 UTC
+        assert_eq!(hour_of_week(1704067199), HOURS_PER_WEEK - 1);
+    }
+
+    #[test]
+    fn test_hour_of_week_before_epoch() {
+        // Wed Dec 31 1969 23:59:59 UTC
+        assert_eq!(hour_of_week(-1), 2 * 24 + 23);
+        // Extremes stay in range
+        assert!(hour_of_week(i64::MIN) < HOURS_PER_WEEK);
+        assert!(hour_of_week(i64::MAX) < HOURS_PER_WEEK);
+    }
+
+    #[test]
+    fn test_business_hours_schedule() {
+        let schedule = Weekday::ALL[..5]
+            .iter()
+            .fold(WeeklySchedule::default(), |s, &day| s.allow(day, 8, 20));
+
+        // Mon Jan 1 2024, 07:59:59 / 08:00:00 / 19:59:59 / 20:00:00 UTC
+        assert!(!schedule.allows(1704095999));
+        assert!(schedule.allows(1704096000));
+        assert!(schedule.allows(1704139199));
+        assert!(!schedule.allows(1704139200));
+
+        // Sat Jan 6 2024 12:00:00 UTC
+        assert!(!schedule.allows(1704542400));
+    }
+
+    #[test]
+    fn test_sunday_blackout_schedule() {
+        let schedule = WeeklySchedule::ALWAYS.block(Weekday::Sunday, 0, 24);
+
+        // Sat Jan 6 2024 23:59:59 UTC / Sun Jan 7 00:00:00 / Mon Jan 8 00:00:00
+        assert!(schedule.allows(1704585599));
+        assert!(!schedule.allows(1704585600));
+        assert!(schedule.allows(1704672000));
+    }
+
+    #[test]
+    fn test_allow_clamps_hours() {
+        let schedule = WeeklySchedule::default().allow(Weekday::Sunday, 22, 30);
+        assert!(schedule.allows_hour(HOURS_PER_WEEK - 1));
+        assert!(!schedule.allows_hour(0));
+        assert!(!schedule.allows_hour(HOURS_PER_WEEK));
+    }
+}
@@ -0,0 +1,273 @@
+//! Program state
+
+use crate::error::DeadlineError;
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+/// Seed prefix for cooldown PDAs
+pub const COOLDOWN_SEED: &[u8] = b"cooldown";
+
+/// Seed prefix for session PDAs
+pub const SESSION_SEED: &[u8] = b"session";
+
+/// Returns the cooldown address and bump seed for an authority and seed
+pub fn get_cooldown_address_and_bump_seed(
+    authority: &Pubkey,
+    seed: u64,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[COOLDOWN_SEED, authority.as_ref(), &seed.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Returns the session address and bump seed for an owner and seed
+pub fn get_session_address_and_bump_seed(
+    owner: &Pubkey,
+    seed: u64,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[SESSION_SEED, owner.as_ref(), &seed.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Rate-limit state enforcing a minimum interval between actions
+///
+/// Layout:
+/// - Bytes 0-31: authority (Pubkey)
+/// - Bytes 32-39: last_executed (i64, little-endian)
+/// - Bytes 40-47: min_interval (u64, little-endian)
+/// - Bytes 48-55: seed (u64, little-endian)
+/// - Byte 56: bump seed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cooldown {
+    /// Account that must sign every `CheckAndBumpCooldown`
+    pub authority: Pubkey,
+    /// Unix timestamp of the last allowed action
+    /// `i64::MIN` until the first action
+    pub last_executed: i64,
+    /// Minimum number of seconds between two actions
+    pub min_interval: u64,
+    /// Seed distinguishing cooldowns of the same authority
+    pub seed: u64,
+    /// Bump seed of the cooldown PDA
+    pub bump: u8,
+}
+
+impl Cooldown {
+    /// Serialized size of the cooldown account
+    pub const LEN: usize = 57;
+
+    /// Returns whether an action is allowed at `unix_timestamp`
+    ///
+    /// Allowed once `unix_timestamp - last_executed >= min_interval`. A
+    /// clock that moved backwards never satisfies a non-zero interval.
+    pub fn is_ready(&self, unix_timestamp: i64) -> bool {
+        let elapsed = i128::from(unix_timestamp) - i128::from(self.last_executed);
+        elapsed >= i128::from(self.min_interval)
+    }
+
+    /// Unpacks the cooldown from account data
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let input: &[u8; Self::LEN] = input
+            .try_into()
+            .map_err(|_| DeadlineError::InvalidCooldown)?;
+        let (authority, rest) = input.split_first_chunk::<32>().unwrap();
+        let (last_executed, rest) = rest.split_first_chunk::<8>().unwrap();
+        let (min_interval, rest) = rest.split_first_chunk::<8>().unwrap();
+        let (seed, rest) = rest.split_first_chunk::<8>().unwrap();
+
+        Ok(Self {
+            authority: Pubkey::new_from_array(*authority),
+            last_executed: i64::from_le_bytes(*last_executed),
+            min_interval: u64::from_le_bytes(*min_interval),
+            seed: u64::from_le_bytes(*seed),
+            bump: rest[0],
+        })
+    }
+
+    /// Packs the cooldown into account data
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        if dst.len() != Self::LEN {
+            return Err(DeadlineError::InvalidCooldown.into());
+        }
+        dst[0..32].copy_from_slice(self.authority.as_ref());
+        dst[32..40].copy_from_slice(&self.last_executed.to_le_bytes());
+        dst[40..48].copy_from_slice(&self.min_interval.to_le_bytes());
+        dst[48..56].copy_from_slice(&self.seed.to_le_bytes());
+        dst[56] = self.bump;
+        Ok(())
+    }
+}
+
+/// Delegated session key state
+///
+/// Layout:
+/// - Bytes 0-31: owner (Pubkey)
+/// - Bytes 32-63: delegate (Pubkey)
+/// - Bytes 64-71: expires_at (i64, little-endian)
+/// - Byte 72: spend cap flag (0 = uncapped, 1 = capped)
+/// - Bytes 73-80: spend cap (u64, little-endian, zero when uncapped)
+/// - Bytes 81-88: spent (u64, little-endian)
+/// - Byte 89: revoked (0 or 1)
+/// - Bytes 90-97: seed (u64, little-endian)
+/// - Byte 98: bump seed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Session {
+    /// Wallet that created the session and may revoke it
+    pub owner: Pubkey,
+    /// Session key that must sign every `ValidateSession`
+    pub delegate: Pubkey,
+    /// Unix timestamp after which the session is expired
+    /// Use 0 for "never expires"
+    pub expires_at: i64,
+    /// Maximum total amount the delegate may authorize
+    pub spend_cap: Option<u64>,
+    /// Total amount authorized so far
+    pub spent: u64,
+    /// Whether the owner has revoked the session
+    pub revoked: bool,
+    /// Seed distinguishing sessions of the same owner
+    pub seed: u64,
+    /// Bump seed of the session PDA
+    pub bump: u8,
+}
+
+impl Session {
+    /// Serialized size of the session account
+    pub const LEN: usize = 99;
+
+    /// Unpacks the session from account data
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let input: &[u8; Self::LEN] = input
+            .try_into()
+            .map_err(|_| DeadlineError::InvalidSession)?;
+        let (owner, rest) = input.split_first_chunk::<32>().unwrap();
+        let (delegate, rest) = rest.split_first_chunk::<32>().unwrap();
+        let (expires_at, rest) = rest.split_first_chunk::<8>().unwrap();
+        let (&has_spend_cap, rest) = rest.split_first().unwrap();
+        let (spend_cap, rest) = rest.split_first_chunk::<8>().unwrap();
+        let (spent, rest) = rest.split_first_chunk::<8>().unwrap();
+        let (&revoked, rest) = rest.split_first().unwrap();
+        let (seed, rest) = rest.split_first_chunk::<8>().unwrap();
+
+        let spend_cap = match has_spend_cap {
+            0 => None,
+            1 => Some(u64::from_le_bytes(*spend_cap)),
+            _ => return Err(DeadlineError::InvalidSession.into()),
+        };
+        let revoked = match revoked {
+            0 => false,
+            1 => true,
+            _ => return Err(DeadlineError::InvalidSession.into()),
+        };
+
+        Ok(Self {
+            owner: Pubkey::new_from_array(*owner),
+            delegate: Pubkey::new_from_array(*delegate),
+            expires_at: i64::from_le_bytes(*expires_at),
+            spend_cap,
+            spent: u64::from_le_bytes(*spent),
+            revoked,
+            seed: u64::from_le_bytes(*seed),
+            bump: rest[0],
+        })
+    }
+
+    /// Packs the session into account data
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        if dst.len() != Self::LEN {
+            return Err(DeadlineError::InvalidSession.into());
+        }
+        dst[0..32].copy_from_slice(self.owner.as_ref());
+        dst[32..64].copy_from_slice(self.delegate.as_ref());
+        dst[64..72].copy_from_slice(&self.expires_at.to_le_bytes());
+        dst[72] = self.spend_cap.is_some() as u8;
+        dst[73..81].copy_from_slice(&self.spend_cap.unwrap_or(0).to_le_bytes());
+        dst[81..89].copy_from_slice(&self.spent.to_le_bytes());
+        dst[89] = self.revoked as u8;
+        dst[90..98].copy_from_slice(&self.seed.to_le_bytes());
+        dst[98] = self.bump;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cooldown_roundtrip() {
+        let cooldown = Cooldown {
+            authority: Pubkey::new_from_array([1; 32]),
+            last_executed: 1700000000,
+            min_interval: 30 * 86_400,
+            seed: 7,
+            bump: 254,
+        };
+        let mut data = [0u8; Cooldown::LEN];
+        cooldown.pack(&mut data).unwrap();
+        assert_eq!(Cooldown::unpack(&data).unwrap(), cooldown);
+    }
+
+    #[test]
+    fn test_cooldown_wrong_length() {
+        assert!(Cooldown::unpack(&[0u8; Cooldown::LEN - 1]).is_err());
+        assert!(Cooldown::unpack(&[0u8; Cooldown::LEN + 1]).is_err());
+    }
+
+    #[test]
+    fn test_cooldown_is_ready() {
+        let mut cooldown = Cooldown {
+            authority: Pubkey::default(),
+            last_executed: i64::MIN,
+            min_interval: u64::MAX,
+            seed: 0,
+            bump: 0,
+        };
+        // Never executed
+        assert!(cooldown.is_ready(i64::MAX));
+
+        cooldown.last_executed = 1700000000;
+        cooldown.min_interval = 100;
+        assert!(!cooldown.is_ready(1700000099));
+        assert!(cooldown.is_ready(1700000100));
+        // Clock moved backwards
+        assert!(!cooldown.is_ready(1699999999));
+    }
+
+    #[test]
+    fn test_session_roundtrip() {
+        for spend_cap in [None, Some(0), Some(u64::MAX)] {
+            let session = Session {
+                owner: Pubkey::new_from_array([1; 32]),
+                delegate: Pubkey::new_from_array([2; 32]),
+                expires_at: 1800000000,
+                spend_cap,
+                spent: 5,
+                revoked: true,
+                seed: 7,
+                bump: 254,
+            };
+            let mut data = [0u8; Session::LEN];
+            session.pack(&mut data).unwrap();
+            assert_eq!(Session::unpack(&data).unwrap(), session);
+        }
+    }
+
+    #[test]
+    fn test_session_invalid_data() {
+        assert!(Session::unpack(&[0u8; Session::LEN - 1]).is_err());
+        assert!(Session::unpack(&[0u8; Session::LEN + 1]).is_err());
+
+        let mut data = [0u8; Session::LEN];
+        data[72] = 2;
+        assert!(Session::unpack(&data).is_err());
+
+        let mut data = [0u8; Session::LEN];
+        data[89] = 2;
+        assert!(Session::unpack(&data).is_err());
+    }
+}
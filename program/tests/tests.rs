@@ -11,11 +11,28 @@
 
 use {
     cascade_protocol_deadline_validator::{
-        error::DeadlineError, id, instruction::DeadlineInstruction,
+        error::DeadlineError,
+        id,
+        instruction::DeadlineInstruction,
+        oracle::{PRICE_UPDATE_V2_DISCRIMINATOR, PYTH_RECEIVER_PROGRAM_ID},
+        schedule::{Weekday, WeeklySchedule},
+        solana_program::epoch_schedule::EpochSchedule,
+        state::{
+            get_cooldown_address_and_bump_seed, get_session_address_and_bump_seed, Cooldown,
+            Session,
+        },
     },
-    mollusk_svm::{result::Check, Mollusk},
-    solana_instruction::Instruction,
+    cascade_protocol_deadline_validator_client::instruction::{
+        check_and_bump_cooldown, create_session, initialize_cooldown, revoke_session,
+        validate_clock_sanity, validate_deadline, validate_dutch_auction, validate_expression,
+        validate_oracle_clock, validate_schedule, validate_session, versioned, with_clock_sysvar,
+        Constraints, Op,
+    },
+    mollusk_svm::{program, result::Check, Mollusk},
+    solana_account::Account,
+    solana_instruction::{AccountMeta, BorrowedAccountMeta, BorrowedInstruction, Instruction},
     solana_program_error::ProgramError,
+    solana_pubkey::Pubkey,
 };
 
 /// Helper function to create a Mollusk instance with the deadline validator program
@@ -26,8 +43,7 @@ fn setup_mollusk() -> Mollusk {
 
 /// Helper to create a ValidateDeadline instruction
 fn create_validate_deadline_instruction(deadline: i64) -> Instruction {
-    let instruction_data = DeadlineInstruction::ValidateDeadline { deadline }.pack();
-    Instruction::new_with_bytes(id(), &instruction_data, vec![])
+    validate_deadline(deadline)
 }
 
 #[test]
@@ -204,16 +220,49 @@ fn test_extreme_past_deadline() {
     // Test with minimum i64 value (far past)
     let far_past_deadline = i64::MIN;
     let instruction = create_validate_deadline_instruction(far_past_deadline);
+    // Only the legacy encoding fits i64::MIN
+    assert_eq!(instruction.data[0], 0);
 
     mollusk.process_and_validate_instruction(
         &instruction,
         &[],
-        &[Check::err(ProgramError::from(
-            DeadlineError::DeadlineExpired,
+        &[Check::err(ProgramError::Custom(
+            DeadlineError::DeadlineExpired.legacy_code(),
         ))],
     );
 }
 
+#[test]
+fn test_legacy_discriminator_keeps_legacy_codes() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+
+    let mut data = vec![0];
+    data.extend_from_slice(&1600000000i64.to_le_bytes());
+    let instruction = Instruction::new_with_bytes(id(), &data, vec![]);
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[],
+        &[Check::err(ProgramError::Custom(0))],
+    );
+
+    data.push(0);
+    let instruction = Instruction::new_with_bytes(id(), &data, vec![]);
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[],
+        &[Check::err(ProgramError::Custom(1))],
+    );
+
+    // Same deadline in a compact encoding
+    let instruction = create_validate_deadline_instruction(1600000000);
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[],
+        &[Check::err(ProgramError::Custom(0xDEAD_0000))],
+    );
+}
+
 #[test]
 fn test_multiple_transactions_same_deadline() {
     let mut mollusk = setup_mollusk();
@@ -271,3 +320,1543 @@ fn test_recent_past_deadline() {
         ))],
     );
 }
+
+/// Price feed held by `create_price_update_account`
+const FEED_ID: [u8; 32] = [2; 32];
+
+/// Helper to create a ValidateOracleClock instruction for `FEED_ID`
+fn create_validate_oracle_clock_instruction(
+    oracle: Pubkey,
+    max_drift: u64,
+    max_staleness_slots: u64,
+    min_publish_time: i64,
+) -> Instruction {
+    validate_oracle_clock(
+        &oracle,
+        max_drift,
+        max_staleness_slots,
+        &FEED_ID,
+        min_publish_time,
+    )
+}
+
+/// Helper to fabricate a fully verified Pyth `PriceUpdateV2` account
+fn create_price_update_account(publish_time: i64, posted_slot: u64) -> Account {
+    let mut data = PRICE_UPDATE_V2_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&[1u8; 32]); // write_authority
+    data.push(1); // VerificationLevel::Full
+    data.extend_from_slice(&FEED_ID);
+    data.extend_from_slice(&6_000_000_000i64.to_le_bytes()); // price
+    data.extend_from_slice(&1_000_000u64.to_le_bytes()); // conf
+    data.extend_from_slice(&(-8i32).to_le_bytes()); // exponent
+    data.extend_from_slice(&publish_time.to_le_bytes());
+    data.extend_from_slice(&(publish_time - 1).to_le_bytes()); // prev_publish_time
+    data.extend_from_slice(&6_000_000_000i64.to_le_bytes()); // ema_price
+    data.extend_from_slice(&1_000_000u64.to_le_bytes()); // ema_conf
+    data.extend_from_slice(&posted_slot.to_le_bytes());
+    data.push(0); // padding: receiver allocates for VerificationLevel::Partial
+
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: PYTH_RECEIVER_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+#[test]
+fn test_oracle_clock_within_tolerance_succeeds() {
+    let mut mollusk = setup_mollusk();
+
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    mollusk.sysvars.clock.slot = 1000;
+
+    // Oracle published 10 seconds earlier, 5 slots ago
+    let oracle = Pubkey::new_unique();
+    let account = create_price_update_account(1700000000 - 10, 995);
+    let instruction = create_validate_oracle_clock_instruction(oracle, 30, 25, 1700000000 - 60);
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[(oracle, account)],
+        &[Check::success()],
+    );
+}
+
+#[test]
+fn test_oracle_clock_exact_tolerance_succeeds() {
+    let mut mollusk = setup_mollusk();
+
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    mollusk.sysvars.clock.slot = 1000;
+
+    // Drift and staleness exactly at their limits (inclusive behavior)
+    let oracle = Pubkey::new_unique();
+    let account = create_price_update_account(1700000000 + 30, 975);
+    let instruction = create_validate_oracle_clock_instruction(oracle, 30, 25, 1700000000 - 60);
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[(oracle, account)],
+        &[Check::success()],
+    );
+}
+
+#[test]
+fn test_oracle_clock_behind_oracle_fails() {
+    let mut mollusk = setup_mollusk();
+
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    mollusk.sysvars.clock.slot = 1000;
+
+    // Cluster clock lags the oracle by 31 seconds
+    let oracle = Pubkey::new_unique();
+    let account = create_price_update_account(1700000000 + 31, 1000);
+    let instruction = create_validate_oracle_clock_instruction(oracle, 30, 25, 1700000000 - 60);
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[(oracle, account)],
+        &[Check::err(ProgramError::from(DeadlineError::ClockDrift))],
+    );
+}
+
+#[test]
+fn test_oracle_clock_ahead_of_oracle_fails() {
+    let mut mollusk = setup_mollusk();
+
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    mollusk.sysvars.clock.slot = 1000;
+
+    // Cluster clock runs 31 seconds ahead of a fresh oracle update
+    let oracle = Pubkey::new_unique();
+    let account = create_price_update_account(1700000000 - 31, 1000);
+    let instruction = create_validate_oracle_clock_instruction(oracle, 30, 25, 1700000000 - 60);
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[(oracle, account)],
+        &[Check::err(ProgramError::from(DeadlineError::ClockDrift))],
+    );
+}
+
+#[test]
+fn test_oracle_clock_stale_update_fails() {
+    let mut mollusk = setup_mollusk();
+
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    mollusk.sysvars.clock.slot = 1000;
+
+    // Update posted 26 slots ago, one more than allowed
+    let oracle = Pubkey::new_unique();
+    let account = create_price_update_account(1700000000, 974);
+    let instruction = create_validate_oracle_clock_instruction(oracle, 30, 25, 1700000000 - 60);
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[(oracle, account)],
+        &[Check::err(ProgramError::from(DeadlineError::OracleStale))],
+    );
+}
+
+#[test]
+fn test_oracle_clock_old_publish_time_fails() {
+    let mut mollusk = setup_mollusk();
+
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    mollusk.sysvars.clock.slot = 1000;
+
+    // Freshly posted and within drift, but published before the signer's
+    // lower bound
+    let oracle = Pubkey::new_unique();
+    let account = create_price_update_account(1700000000 - 10, 1000);
+    let instruction = create_validate_oracle_clock_instruction(oracle, 30, 25, 1700000000 - 9);
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[(oracle, account)],
+        &[Check::err(ProgramError::from(DeadlineError::OracleStale))],
+    );
+}
+
+#[test]
+fn test_oracle_clock_wrong_feed_fails() {
+    let mut mollusk = setup_mollusk();
+
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    mollusk.sysvars.clock.slot = 1000;
+
+    // A fresh, fully verified update for another price feed
+    let oracle = Pubkey::new_unique();
+    let account = create_price_update_account(1700000000, 1000);
+    let instruction = validate_oracle_clock(&oracle, 30, 25, &[9; 32], 1700000000 - 60);
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[(oracle, account)],
+        &[Check::err(ProgramError::from(
+            DeadlineError::InvalidOracleAccount,
+        ))],
+    );
+}
+
+#[test]
+fn test_oracle_clock_wrong_owner_fails() {
+    let mut mollusk = setup_mollusk();
+
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    mollusk.sysvars.clock.slot = 1000;
+
+    // Well-formed data, but not written by the Pyth receiver
+    let oracle = Pubkey::new_unique();
+    let mut account = create_price_update_account(1700000000, 1000);
+    account.owner = Pubkey::new_unique();
+    let instruction = create_validate_oracle_clock_instruction(oracle, 30, 25, 1700000000 - 60);
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[(oracle, account)],
+        &[Check::err(ProgramError::from(
+            DeadlineError::InvalidOracleAccount,
+        ))],
+    );
+}
+
+#[test]
+fn test_oracle_clock_partial_verification_fails() {
+    let mut mollusk = setup_mollusk();
+
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    mollusk.sysvars.clock.slot = 1000;
+
+    // Rewrite the verification level as Partial { num_signatures: 5 }
+    let oracle = Pubkey::new_unique();
+    let mut account = create_price_update_account(1700000000, 1000);
+    account.data.insert(40, 0);
+    account.data[41] = 5;
+    account.data.pop();
+    let instruction = create_validate_oracle_clock_instruction(oracle, 30, 25, 1700000000 - 60);
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[(oracle, account)],
+        &[Check::err(ProgramError::from(
+            DeadlineError::InvalidOracleAccount,
+        ))],
+    );
+}
+
+#[test]
+fn test_oracle_clock_missing_account_fails() {
+    let mut mollusk = setup_mollusk();
+
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+
+    let instruction = Instruction::new_with_bytes(
+        id(),
+        &DeadlineInstruction::ValidateOracleClock {
+            max_drift: 30,
+            max_staleness_slots: 25,
+            feed_id: FEED_ID,
+            min_publish_time: 0,
+        }
+        .pack(),
+        vec![],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[],
+        &[Check::err(ProgramError::NotEnoughAccountKeys)],
+    );
+}
+
+/// Helper to create a ValidateClockSanity instruction
+fn create_validate_clock_sanity_instruction(max_drift: u64) -> Instruction {
+    validate_clock_sanity(max_drift)
+}
+
+/// Helper to place the Mollusk clock `elapsed_slots` into epoch 10
+fn setup_mollusk_mid_epoch(elapsed_slots: u64, unix_timestamp: i64) -> Mollusk {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.epoch_schedule = EpochSchedule::without_warmup();
+
+    let first_slot = mollusk.sysvars.epoch_schedule.get_first_slot_in_epoch(10);
+    mollusk.sysvars.clock.epoch = 10;
+    mollusk.sysvars.clock.slot = first_slot + elapsed_slots;
+    mollusk.sysvars.clock.epoch_start_timestamp = 1700000000;
+    mollusk.sysvars.clock.unix_timestamp = unix_timestamp;
+    mollusk
+}
+
+#[test]
+fn test_clock_sanity_nominal_progression_succeeds() {
+    // 1000 slots at 400ms = 400s after the epoch start
+    let mollusk = setup_mollusk_mid_epoch(1000, 1700000400);
+
+    let instruction = create_validate_clock_sanity_instruction(0);
+
+    mollusk.process_and_validate_instruction(&instruction, &[], &[Check::success()]);
+}
+
+#[test]
+fn test_clock_sanity_exact_bound_succeeds() {
+    // Clock 60s ahead of slot-derived time, bound of 60s (inclusive behavior)
+    let mollusk = setup_mollusk_mid_epoch(1000, 1700000460);
+
+    let instruction = create_validate_clock_sanity_instruction(60);
+
+    mollusk.process_and_validate_instruction(&instruction, &[], &[Check::success()]);
+}
+
+#[test]
+fn test_clock_sanity_clock_ahead_fails() {
+    // Clock 61s ahead of slot-derived time
+    let mollusk = setup_mollusk_mid_epoch(1000, 1700000461);
+
+    let instruction = create_validate_clock_sanity_instruction(60);
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[],
+        &[Check::err(ProgramError::from(DeadlineError::ClockDrift))],
+    );
+}
+
+#[test]
+fn test_clock_sanity_clock_behind_fails() {
+    // Clock 61s behind slot-derived time
+    let mollusk = setup_mollusk_mid_epoch(1000, 1700000339);
+
+    let instruction = create_validate_clock_sanity_instruction(60);
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[],
+        &[Check::err(ProgramError::from(DeadlineError::ClockDrift))],
+    );
+}
+
+#[test]
+fn test_clock_sanity_before_deadline_in_same_transaction() {
+    let mollusk = setup_mollusk_mid_epoch(1000, 1700000400);
+
+    let instructions = [
+        create_validate_clock_sanity_instruction(60),
+        create_validate_deadline_instruction(1700000400 + 300),
+    ];
+
+    mollusk.process_and_validate_instruction_chain(
+        &[
+            (&instructions[0], &[Check::success()]),
+            (&instructions[1], &[Check::success()]),
+        ],
+        &[],
+    );
+}
+
+const TOKEN_PROGRAM_ID: Pubkey =
+    solana_pubkey::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+const TOKEN_2022_PROGRAM_ID: Pubkey =
+    solana_pubkey::pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+const AUCTION_MINT: Pubkey = Pubkey::new_from_array([4; 32]);
+const AUCTION_DESTINATION: Pubkey = Pubkey::new_from_array([5; 32]);
+
+/// Helper to create a ValidateDutchAuction instruction: 10 USDC falling to
+/// 1 USDC between t=1700000000 and t=1700000900
+fn create_validate_dutch_auction_instruction() -> Instruction {
+    validate_dutch_auction(
+        &AUCTION_MINT,
+        &AUCTION_DESTINATION,
+        10_000_000,
+        1_000_000,
+        1700000000,
+        1700000900,
+    )
+}
+
+/// Helper to create a TransferChecked instruction for a token program,
+/// paying the auction's mint to its destination
+fn create_transfer_checked_instruction(token_program: Pubkey, amount: u64) -> Instruction {
+    create_transfer_checked_instruction_to(token_program, AUCTION_MINT, AUCTION_DESTINATION, amount)
+}
+
+/// Helper to create a TransferChecked instruction of `mint` to
+/// `destination`
+fn create_transfer_checked_instruction_to(
+    token_program: Pubkey,
+    mint: Pubkey,
+    destination: Pubkey,
+    amount: u64,
+) -> Instruction {
+    let mut data = vec![12u8];
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.push(6); // decimals
+    Instruction::new_with_bytes(
+        token_program,
+        &data,
+        vec![
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(destination, false),
+            AccountMeta::new_readonly(Pubkey::new_unique(), true),
+        ],
+    )
+}
+
+/// Helper to build the Instructions sysvar account for a transaction
+fn create_instructions_sysvar_account(instructions: &[Instruction]) -> Account {
+    let borrowed: Vec<BorrowedInstruction> = instructions
+        .iter()
+        .map(|instruction| BorrowedInstruction {
+            program_id: &instruction.program_id,
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|meta| BorrowedAccountMeta {
+                    pubkey: &meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: &instruction.data,
+        })
+        .collect();
+
+    Account {
+        lamports: 1_000_000_000,
+        data: solana_instructions_sysvar::construct_instructions_data(&borrowed),
+        owner: solana_pubkey::pubkey!("Sysvar1111111111111111111111111111111111111"),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Runs ValidateDutchAuction at `unix_timestamp` in a transaction that
+/// also contains `transfers`
+fn process_dutch_auction(unix_timestamp: i64, transfers: &[Instruction], checks: &[Check]) {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = unix_timestamp;
+
+    let instruction = create_validate_dutch_auction_instruction();
+    let mut transaction = transfers.to_vec();
+    transaction.push(instruction.clone());
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[(
+            solana_instructions_sysvar::ID,
+            create_instructions_sysvar_account(&transaction),
+        )],
+        checks,
+    );
+}
+
+#[test]
+fn test_dutch_auction_start_price_paid_succeeds() {
+    process_dutch_auction(
+        1700000000,
+        &[create_transfer_checked_instruction(
+            TOKEN_PROGRAM_ID,
+            10_000_000,
+        )],
+        &[Check::success()],
+    );
+}
+
+#[test]
+fn test_dutch_auction_before_start_requires_start_price() {
+    process_dutch_auction(
+        1600000000,
+        &[create_transfer_checked_instruction(
+            TOKEN_PROGRAM_ID,
+            9_999_999,
+        )],
+        &[Check::err(ProgramError::from(
+            DeadlineError::InsufficientPayment,
+        ))],
+    );
+}
+
+#[test]
+fn test_dutch_auction_midpoint_exact_price_succeeds() {
+    // Halfway through: 10 - (9 * 450 / 900) = 5.5 USDC
+    process_dutch_auction(
+        1700000450,
+        &[create_transfer_checked_instruction(
+            TOKEN_PROGRAM_ID,
+            5_500_000,
+        )],
+        &[Check::success()],
+    );
+}
+
+#[test]
+fn test_dutch_auction_midpoint_underpaid_fails() {
+    process_dutch_auction(
+        1700000450,
+        &[create_transfer_checked_instruction(
+            TOKEN_PROGRAM_ID,
+            5_499_999,
+        )],
+        &[Check::err(ProgramError::from(
+            DeadlineError::InsufficientPayment,
+        ))],
+    );
+}
+
+#[test]
+fn test_dutch_auction_floor_price_after_end_succeeds() {
+    process_dutch_auction(
+        1700000900,
+        &[create_transfer_checked_instruction(
+            TOKEN_2022_PROGRAM_ID,
+            1_000_000,
+        )],
+        &[Check::success()],
+    );
+    process_dutch_auction(
+        1800000000,
+        &[create_transfer_checked_instruction(
+            TOKEN_2022_PROGRAM_ID,
+            1_000_000,
+        )],
+        &[Check::success()],
+    );
+}
+
+#[test]
+fn test_dutch_auction_without_transfer_fails() {
+    process_dutch_auction(
+        1800000000,
+        &[],
+        &[Check::err(ProgramError::from(
+            DeadlineError::InsufficientPayment,
+        ))],
+    );
+}
+
+#[test]
+fn test_dutch_auction_ignores_other_programs() {
+    // Same bytes as a TransferChecked, but not sent to a token program
+    process_dutch_auction(
+        1800000000,
+        &[create_transfer_checked_instruction(
+            Pubkey::new_unique(),
+            10_000_000,
+        )],
+        &[Check::err(ProgramError::from(
+            DeadlineError::InsufficientPayment,
+        ))],
+    );
+}
+
+#[test]
+fn test_dutch_auction_wrong_mint_fails() {
+    // Enough of another token
+    process_dutch_auction(
+        1800000000,
+        &[create_transfer_checked_instruction_to(
+            TOKEN_PROGRAM_ID,
+            Pubkey::new_unique(),
+            AUCTION_DESTINATION,
+            10_000_000,
+        )],
+        &[Check::err(ProgramError::from(
+            DeadlineError::InsufficientPayment,
+        ))],
+    );
+}
+
+#[test]
+fn test_dutch_auction_wrong_destination_fails() {
+    // Enough of the auction's token, paid to the buyer's own account
+    process_dutch_auction(
+        1800000000,
+        &[create_transfer_checked_instruction_to(
+            TOKEN_PROGRAM_ID,
+            AUCTION_MINT,
+            Pubkey::new_unique(),
+            10_000_000,
+        )],
+        &[Check::err(ProgramError::from(
+            DeadlineError::InsufficientPayment,
+        ))],
+    );
+}
+
+#[test]
+fn test_dutch_auction_any_sufficient_transfer_succeeds() {
+    process_dutch_auction(
+        1700000450,
+        &[
+            create_transfer_checked_instruction(TOKEN_PROGRAM_ID, 1),
+            create_transfer_checked_instruction(TOKEN_PROGRAM_ID, 5_500_000),
+        ],
+        &[Check::success()],
+    );
+}
+
+#[test]
+fn test_dutch_auction_wrong_sysvar_fails() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+
+    let fake_sysvar = Pubkey::new_unique();
+    let mut instruction = create_validate_dutch_auction_instruction();
+    instruction.accounts[0].pubkey = fake_sysvar;
+    let transfer = create_transfer_checked_instruction(TOKEN_PROGRAM_ID, 10_000_000);
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[(
+            fake_sysvar,
+            create_instructions_sysvar_account(&[transfer, instruction.clone()]),
+        )],
+        &[Check::err(ProgramError::UnsupportedSysvar)],
+    );
+}
+
+/// Helper to create a ValidateSchedule instruction
+fn create_validate_schedule_instruction(schedule: WeeklySchedule) -> Instruction {
+    validate_schedule(&schedule.0)
+}
+
+/// Weekdays 08:00-20:00 UTC
+fn business_hours() -> WeeklySchedule {
+    Weekday::ALL[..5]
+        .iter()
+        .fold(WeeklySchedule::default(), |schedule, &day| {
+            schedule.allow(day, 8, 20)
+        })
+}
+
+#[test]
+fn test_schedule_business_hours_opening_boundary() {
+    let mut mollusk = setup_mollusk();
+    let instruction = create_validate_schedule_instruction(business_hours());
+
+    // Mon Jan 1 2024 08:00:00 UTC
+    mollusk.sysvars.clock.unix_timestamp = 1704096000;
+    mollusk.process_and_validate_instruction(&instruction, &[], &[Check::success()]);
+
+    // One second earlier
+    mollusk.sysvars.clock.unix_timestamp = 1704095999;
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[],
+        &[Check::err(ProgramError::from(
+            DeadlineError::OutsideSchedule,
+        ))],
+    );
+}
+
+#[test]
+fn test_schedule_business_hours_closing_boundary() {
+    let mut mollusk = setup_mollusk();
+    let instruction = create_validate_schedule_instruction(business_hours());
+
+    // Fri Jan 5 2024 19:59:59 UTC
+    mollusk.sysvars.clock.unix_timestamp = 1704484799;
+    mollusk.process_and_validate_instruction(&instruction, &[], &[Check::success()]);
+
+    // Fri Jan 5 2024 20:00:00 UTC
+    mollusk.sysvars.clock.unix_timestamp = 1704484800;
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[],
+        &[Check::err(ProgramError::from(
+            DeadlineError::OutsideSchedule,
+        ))],
+    );
+}
+
+#[test]
+fn test_schedule_business_hours_weekend_fails() {
+    let mut mollusk = setup_mollusk();
+
+    // Sat Jan 6 2024 12:00:00 UTC
+    mollusk.sysvars.clock.unix_timestamp = 1704542400;
+
+    let instruction = create_validate_schedule_instruction(business_hours());
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[],
+        &[Check::err(ProgramError::from(
+            DeadlineError::OutsideSchedule,
+        ))],
+    );
+}
+
+#[test]
+fn test_schedule_sunday_blackout() {
+    let mut mollusk = setup_mollusk();
+    let instruction =
+        create_validate_schedule_instruction(WeeklySchedule::ALWAYS.block(Weekday::Sunday, 0, 24));
+
+    // Sat Jan 6 2024 23:59:59 UTC
+    mollusk.sysvars.clock.unix_timestamp = 1704585599;
+    mollusk.process_and_validate_instruction(&instruction, &[], &[Check::success()]);
+
+    // Sun Jan 7 2024 00:00:00 UTC
+    mollusk.sysvars.clock.unix_timestamp = 1704585600;
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[],
+        &[Check::err(ProgramError::from(
+            DeadlineError::OutsideSchedule,
+        ))],
+    );
+
+    // Mon Jan 8 2024 00:00:00 UTC
+    mollusk.sysvars.clock.unix_timestamp = 1704672000;
+    mollusk.process_and_validate_instruction(&instruction, &[], &[Check::success()]);
+}
+
+#[test]
+fn test_schedule_empty_always_fails() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+
+    let instruction = create_validate_schedule_instruction(WeeklySchedule::default());
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[],
+        &[Check::err(ProgramError::from(
+            DeadlineError::OutsideSchedule,
+        ))],
+    );
+}
+
+/// Helper to create a ValidateExpression instruction
+fn create_validate_expression_instruction(ops: Vec<Op>) -> Instruction {
+    validate_expression(&ops)
+}
+
+/// (t <= 1700000000 AND slot <= 1000) OR t <= 1700003600
+fn slot_bounded_or_grace() -> Vec<Op> {
+    vec![
+        Op::TimeLe(1700000000),
+        Op::SlotLe(1000),
+        Op::And,
+        Op::TimeLe(1700003600),
+        Op::Or,
+    ]
+}
+
+#[test]
+fn test_expression_first_branch_succeeds() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    mollusk.sysvars.clock.slot = 1000;
+
+    let instruction = create_validate_expression_instruction(slot_bounded_or_grace());
+
+    mollusk.process_and_validate_instruction(&instruction, &[], &[Check::success()]);
+}
+
+#[test]
+fn test_expression_second_branch_succeeds() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700003600;
+    mollusk.sysvars.clock.slot = 5000;
+
+    let instruction = create_validate_expression_instruction(slot_bounded_or_grace());
+
+    mollusk.process_and_validate_instruction(&instruction, &[], &[Check::success()]);
+}
+
+#[test]
+fn test_expression_false_fails() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700003601;
+    mollusk.sysvars.clock.slot = 0;
+
+    let instruction = create_validate_expression_instruction(slot_bounded_or_grace());
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[],
+        &[Check::err(ProgramError::from(
+            DeadlineError::ExpressionFalse,
+        ))],
+    );
+}
+
+#[test]
+fn test_expression_epoch_window() {
+    let mut mollusk = setup_mollusk();
+    let instruction =
+        create_validate_expression_instruction(vec![Op::EpochGe(10), Op::EpochLe(12), Op::And]);
+
+    mollusk.sysvars.clock.epoch = 11;
+    mollusk.process_and_validate_instruction(&instruction, &[], &[Check::success()]);
+
+    mollusk.sysvars.clock.epoch = 13;
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[],
+        &[Check::err(ProgramError::from(
+            DeadlineError::ExpressionFalse,
+        ))],
+    );
+}
+
+#[test]
+fn test_expression_malformed_fails() {
+    let mollusk = setup_mollusk();
+
+    // Leaves two values on the stack
+    let instruction = create_validate_expression_instruction(vec![Op::TimeLe(0), Op::TimeLe(0)]);
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[],
+        &[Check::err(ProgramError::from(
+            DeadlineError::InvalidInstructionData,
+        ))],
+    );
+}
+
+const THIRTY_DAYS: u64 = 30 * 86_400;
+
+/// Initializes a cooldown for `authority` and returns the resulting accounts
+fn setup_cooldown(
+    mollusk: &Mollusk,
+    authority: &Pubkey,
+    min_interval: u64,
+) -> (Pubkey, Vec<(Pubkey, Account)>) {
+    let payer = Pubkey::new_unique();
+    let (cooldown, _) = get_cooldown_address_and_bump_seed(authority, 0, &id());
+    let accounts = vec![
+        (
+            payer,
+            Account::new(1_000_000_000, 0, &solana_system_interface::program::ID),
+        ),
+        (*authority, Account::default()),
+        (cooldown, Account::default()),
+        program::keyed_account_for_system_program(),
+    ];
+
+    let result = mollusk.process_and_validate_instruction(
+        &initialize_cooldown(&payer, authority, min_interval, 0),
+        &accounts,
+        &[Check::success()],
+    );
+    (cooldown, result.resulting_accounts)
+}
+
+fn cooldown_state(accounts: &[(Pubkey, Account)], key: &Pubkey) -> Cooldown {
+    let (_, account) = accounts.iter().find(|(k, _)| k == key).unwrap();
+    Cooldown::unpack(&account.data).unwrap()
+}
+
+#[test]
+fn test_cooldown_first_action_succeeds() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    let authority = Pubkey::new_unique();
+    let (cooldown, accounts) = setup_cooldown(&mollusk, &authority, THIRTY_DAYS);
+
+    let result = mollusk.process_and_validate_instruction(
+        &check_and_bump_cooldown(&cooldown, &authority),
+        &accounts,
+        &[Check::success()],
+    );
+
+    let state = cooldown_state(&result.resulting_accounts, &cooldown);
+    assert_eq!(state.last_executed, 1700000000);
+    assert_eq!(state.min_interval, THIRTY_DAYS);
+}
+
+#[test]
+fn test_cooldown_interval_boundary() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    let authority = Pubkey::new_unique();
+    let (cooldown, accounts) = setup_cooldown(&mollusk, &authority, THIRTY_DAYS);
+    let instruction = check_and_bump_cooldown(&cooldown, &authority);
+
+    let accounts = mollusk
+        .process_and_validate_instruction(&instruction, &accounts, &[Check::success()])
+        .resulting_accounts;
+
+    // One second before the interval elapses
+    mollusk.sysvars.clock.unix_timestamp = 1700000000 + THIRTY_DAYS as i64 - 1;
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &accounts,
+        &[Check::err(ProgramError::from(
+            DeadlineError::CooldownActive,
+        ))],
+    );
+
+    // Exactly at the interval
+    mollusk.sysvars.clock.unix_timestamp = 1700000000 + THIRTY_DAYS as i64;
+    mollusk.process_and_validate_instruction(&instruction, &accounts, &[Check::success()]);
+}
+
+#[test]
+fn test_cooldown_missing_signature_fails() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    let authority = Pubkey::new_unique();
+    let (cooldown, accounts) = setup_cooldown(&mollusk, &authority, THIRTY_DAYS);
+
+    let mut instruction = check_and_bump_cooldown(&cooldown, &authority);
+    instruction.accounts[1].is_signer = false;
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &accounts,
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_cooldown_wrong_authority_fails() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    let authority = Pubkey::new_unique();
+    let impostor = Pubkey::new_unique();
+    let (cooldown, mut accounts) = setup_cooldown(&mollusk, &authority, THIRTY_DAYS);
+    accounts.push((impostor, Account::default()));
+
+    mollusk.process_and_validate_instruction(
+        &check_and_bump_cooldown(&cooldown, &impostor),
+        &accounts,
+        &[Check::err(ProgramError::from(DeadlineError::Unauthorized))],
+    );
+}
+
+#[test]
+fn test_cooldown_not_owned_by_program_fails() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    let authority = Pubkey::new_unique();
+    let fake = Pubkey::new_unique();
+
+    let mut data = vec![0u8; Cooldown::LEN];
+    Cooldown {
+        authority,
+        last_executed: i64::MIN,
+        min_interval: 0,
+        seed: 0,
+        bump: 0,
+    }
+    .pack(&mut data)
+    .unwrap();
+
+    mollusk.process_and_validate_instruction(
+        &check_and_bump_cooldown(&fake, &authority),
+        &[
+            (
+                fake,
+                Account {
+                    lamports: 1_000_000,
+                    data,
+                    owner: Pubkey::new_unique(),
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            ),
+            (authority, Account::default()),
+        ],
+        &[Check::err(ProgramError::from(
+            DeadlineError::InvalidCooldown,
+        ))],
+    );
+}
+
+/// Keys involved in a delegated session
+struct SessionFixture {
+    owner: Pubkey,
+    delegate: Pubkey,
+    session: Pubkey,
+}
+
+/// Creates a session and returns its keys and the resulting accounts
+fn setup_session(
+    mollusk: &Mollusk,
+    expires_at: i64,
+    spend_cap: Option<u64>,
+) -> (SessionFixture, Vec<(Pubkey, Account)>) {
+    let payer = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let delegate = Pubkey::new_unique();
+    let (session, _) = get_session_address_and_bump_seed(&owner, 0, &id());
+    let accounts = vec![
+        (
+            payer,
+            Account::new(1_000_000_000, 0, &solana_system_interface::program::ID),
+        ),
+        (owner, Account::default()),
+        (delegate, Account::default()),
+        (session, Account::default()),
+        program::keyed_account_for_system_program(),
+    ];
+
+    let result = mollusk.process_and_validate_instruction(
+        &create_session(&payer, &owner, &delegate, expires_at, spend_cap, 0),
+        &accounts,
+        &[Check::success()],
+    );
+    (
+        SessionFixture {
+            owner,
+            delegate,
+            session,
+        },
+        result.resulting_accounts,
+    )
+}
+
+fn session_state(accounts: &[(Pubkey, Account)], key: &Pubkey) -> Session {
+    let (_, account) = accounts.iter().find(|(k, _)| k == key).unwrap();
+    Session::unpack(&account.data).unwrap()
+}
+
+const SESSION_SOURCE: Pubkey = Pubkey::new_from_array([6; 32]);
+const SESSION_MINT: Pubkey = Pubkey::new_from_array([7; 32]);
+
+/// Helper to create a TransferChecked instruction signed by the session
+/// delegate, from `SESSION_SOURCE`
+fn create_delegate_transfer_instruction(
+    delegate: &Pubkey,
+    mint: Pubkey,
+    amount: u64,
+) -> Instruction {
+    let mut data = vec![12u8];
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.push(6); // decimals
+    Instruction::new_with_bytes(
+        TOKEN_PROGRAM_ID,
+        &data,
+        vec![
+            AccountMeta::new(SESSION_SOURCE, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new_readonly(*delegate, true),
+        ],
+    )
+}
+
+/// Runs `instruction` in a transaction that also contains `transfers`
+fn process_session(
+    mollusk: &Mollusk,
+    accounts: &[(Pubkey, Account)],
+    transfers: &[Instruction],
+    instruction: &Instruction,
+    checks: &[Check],
+) -> Vec<(Pubkey, Account)> {
+    let mut transaction = transfers.to_vec();
+    transaction.push(instruction.clone());
+    let mut accounts = accounts.to_vec();
+    accounts.push((
+        solana_instructions_sysvar::ID,
+        create_instructions_sysvar_account(&transaction),
+    ));
+    mollusk
+        .process_and_validate_instruction(instruction, &accounts, checks)
+        .resulting_accounts
+}
+
+#[test]
+fn test_session_before_expiry_succeeds() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    let (fixture, accounts) = setup_session(&mollusk, 1700003600, None);
+
+    // Composes with the deadline check in the same transaction
+    let deadline = create_validate_deadline_instruction(1700000060);
+    let session = validate_session(&fixture.session, &fixture.delegate, 0);
+    let mut accounts = accounts;
+    accounts.push((
+        solana_instructions_sysvar::ID,
+        create_instructions_sysvar_account(&[deadline.clone(), session.clone()]),
+    ));
+
+    mollusk.process_and_validate_instruction_chain(
+        &[
+            (&deadline, &[Check::success()]),
+            (&session, &[Check::success()]),
+        ],
+        &accounts,
+    );
+}
+
+#[test]
+fn test_session_expiry_boundary() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    let (fixture, accounts) = setup_session(&mollusk, 1700003600, None);
+    let instruction = validate_session(&fixture.session, &fixture.delegate, 0);
+
+    // expires_at is inclusive, like ValidateDeadline
+    mollusk.sysvars.clock.unix_timestamp = 1700003600;
+    process_session(&mollusk, &accounts, &[], &instruction, &[Check::success()]);
+
+    mollusk.sysvars.clock.unix_timestamp = 1700003601;
+    process_session(
+        &mollusk,
+        &accounts,
+        &[],
+        &instruction,
+        &[Check::err(ProgramError::from(
+            DeadlineError::SessionExpired,
+        ))],
+    );
+}
+
+#[test]
+fn test_session_revoked_fails() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    let (fixture, accounts) = setup_session(&mollusk, 0, None);
+
+    let accounts = mollusk
+        .process_and_validate_instruction(
+            &revoke_session(&fixture.session, &fixture.owner),
+            &accounts,
+            &[Check::success()],
+        )
+        .resulting_accounts;
+    assert!(session_state(&accounts, &fixture.session).revoked);
+
+    process_session(
+        &mollusk,
+        &accounts,
+        &[],
+        &validate_session(&fixture.session, &fixture.delegate, 0),
+        &[Check::err(ProgramError::from(
+            DeadlineError::SessionRevoked,
+        ))],
+    );
+}
+
+#[test]
+fn test_session_revoke_by_delegate_fails() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    let (fixture, accounts) = setup_session(&mollusk, 0, None);
+
+    mollusk.process_and_validate_instruction(
+        &revoke_session(&fixture.session, &fixture.delegate),
+        &accounts,
+        &[Check::err(ProgramError::from(DeadlineError::Unauthorized))],
+    );
+}
+
+#[test]
+fn test_session_wrong_delegate_fails() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    let (fixture, accounts) = setup_session(&mollusk, 0, None);
+
+    // The owner's own wallet is not the session key
+    process_session(
+        &mollusk,
+        &accounts,
+        &[],
+        &validate_session(&fixture.session, &fixture.owner, 0),
+        &[Check::err(ProgramError::from(DeadlineError::Unauthorized))],
+    );
+}
+
+#[test]
+fn test_session_missing_signature_fails() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    let (fixture, accounts) = setup_session(&mollusk, 0, None);
+
+    let mut instruction = validate_session(&fixture.session, &fixture.delegate, 0);
+    instruction.accounts[1].is_signer = false;
+
+    process_session(
+        &mollusk,
+        &accounts,
+        &[],
+        &instruction,
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_session_spend_cap() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    let (fixture, accounts) = setup_session(&mollusk, 0, Some(100));
+    let transfer =
+        |amount| create_delegate_transfer_instruction(&fixture.delegate, SESSION_MINT, amount);
+
+    // Two transfers in one transaction count together
+    let accounts = process_session(
+        &mollusk,
+        &accounts,
+        &[transfer(20), transfer(40)],
+        &validate_session(&fixture.session, &fixture.delegate, 60),
+        &[Check::success()],
+    );
+    assert_eq!(session_state(&accounts, &fixture.session).spent, 60);
+
+    process_session(
+        &mollusk,
+        &accounts,
+        &[transfer(41)],
+        &validate_session(&fixture.session, &fixture.delegate, 41),
+        &[Check::err(ProgramError::from(
+            DeadlineError::SpendCapExceeded,
+        ))],
+    );
+
+    let accounts = process_session(
+        &mollusk,
+        &accounts,
+        &[transfer(40)],
+        &validate_session(&fixture.session, &fixture.delegate, 40),
+        &[Check::success()],
+    );
+    assert_eq!(session_state(&accounts, &fixture.session).spent, 100);
+}
+
+#[test]
+fn test_session_amount_below_transfers_fails() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    let (fixture, accounts) = setup_session(&mollusk, 0, Some(100));
+
+    // Claims nothing while the delegate moves 1,000
+    process_session(
+        &mollusk,
+        &accounts,
+        &[create_delegate_transfer_instruction(
+            &fixture.delegate,
+            SESSION_MINT,
+            1_000,
+        )],
+        &validate_session(&fixture.session, &fixture.delegate, 0),
+        &[Check::err(ProgramError::from(
+            DeadlineError::SessionTransferMismatch,
+        ))],
+    );
+}
+
+#[test]
+fn test_session_transfers_of_two_mints_fail() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    let (fixture, accounts) = setup_session(&mollusk, 0, Some(100));
+
+    process_session(
+        &mollusk,
+        &accounts,
+        &[
+            create_delegate_transfer_instruction(&fixture.delegate, SESSION_MINT, 10),
+            create_delegate_transfer_instruction(&fixture.delegate, Pubkey::new_unique(), 10),
+        ],
+        &validate_session(&fixture.session, &fixture.delegate, 20),
+        &[Check::err(ProgramError::from(
+            DeadlineError::SessionTransferMismatch,
+        ))],
+    );
+}
+
+#[test]
+fn test_session_delegate_signing_other_instruction_fails() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    let (fixture, accounts) = setup_session(&mollusk, 0, Some(100));
+
+    // A program that could transfer through a CPI the sysvar does not show
+    let other = Instruction::new_with_bytes(
+        Pubkey::new_unique(),
+        &[],
+        vec![AccountMeta::new_readonly(fixture.delegate, true)],
+    );
+
+    process_session(
+        &mollusk,
+        &accounts,
+        &[other],
+        &validate_session(&fixture.session, &fixture.delegate, 0),
+        &[Check::err(ProgramError::from(DeadlineError::Unauthorized))],
+    );
+}
+
+/// Helper to create a ValidateDeadline instruction reading the Clock
+/// sysvar from an account
+fn create_validate_deadline_with_clock_instruction(deadline: i64, clock: Pubkey) -> Instruction {
+    let mut instruction = validate_deadline(deadline);
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(clock, false));
+    instruction
+}
+
+#[test]
+fn test_clock_account_future_deadline_succeeds() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    let (clock, clock_account) = mollusk.sysvars.keyed_account_for_clock_sysvar();
+
+    let instruction = create_validate_deadline_with_clock_instruction(1700000000, clock);
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[(clock, clock_account)],
+        &[Check::success()],
+    );
+}
+
+#[test]
+fn test_clock_account_expired_deadline_fails() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000001;
+    let (clock, clock_account) = mollusk.sysvars.keyed_account_for_clock_sysvar();
+
+    let instruction = create_validate_deadline_with_clock_instruction(1700000000, clock);
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[(clock, clock_account)],
+        &[Check::err(ProgramError::from(
+            DeadlineError::DeadlineExpired,
+        ))],
+    );
+}
+
+#[test]
+fn test_clock_account_wrong_address_ignored() {
+    let mut mollusk = setup_mollusk();
+
+    // Expired clock data at an address other than the Clock sysvar
+    mollusk.sysvars.clock.unix_timestamp = 1900000000;
+    let (_, clock_account) = mollusk.sysvars.keyed_account_for_clock_sysvar();
+    let fake_clock = Pubkey::new_unique();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+
+    // Other accounts are ignored, as before the clock account was accepted
+    let instruction = create_validate_deadline_with_clock_instruction(1800000000, fake_clock);
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[(fake_clock, clock_account)],
+        &[Check::success()],
+    );
+}
+
+#[test]
+fn test_clock_account_after_positional_accounts() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.slot = 1000;
+
+    // The syscall clock drifts from the oracle, the Clock account does not
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    let (clock, clock_account) = mollusk.sysvars.keyed_account_for_clock_sysvar();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000 + 3600;
+
+    let oracle = Pubkey::new_unique();
+    let account = create_price_update_account(1700000000, 1000);
+    let instruction = with_clock_sysvar(create_validate_oracle_clock_instruction(
+        oracle,
+        30,
+        25,
+        1700000000 - 60,
+    ));
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[(oracle, account), (clock, clock_account)],
+        &[Check::success()],
+    );
+}
+
+#[test]
+fn test_clock_account_schedule_and_expression() {
+    let mut mollusk = setup_mollusk();
+    // Mon Jan 1 2024 08:00:00 UTC
+    mollusk.sysvars.clock.unix_timestamp = 1704096000;
+    let (clock, clock_account) = mollusk.sysvars.keyed_account_for_clock_sysvar();
+
+    let schedule = with_clock_sysvar(create_validate_schedule_instruction(business_hours()));
+    let expression = with_clock_sysvar(create_validate_expression_instruction(vec![
+        Op::TimeGe(1704096000),
+        Op::Not,
+    ]));
+
+    mollusk.process_and_validate_instruction(
+        &schedule,
+        &[(clock, clock_account.clone())],
+        &[Check::success()],
+    );
+    mollusk.process_and_validate_instruction(
+        &expression,
+        &[(clock, clock_account)],
+        &[Check::err(ProgramError::from(
+            DeadlineError::ExpressionFalse,
+        ))],
+    );
+}
+
+/// Helper to create a ValidateDeadline instruction wrapped in an envelope
+fn create_versioned_deadline_instruction(deadline: i64, constraints: Constraints) -> Instruction {
+    versioned(validate_deadline(deadline), &constraints)
+}
+
+#[test]
+fn test_versioned_not_before_window() {
+    let mut mollusk = setup_mollusk();
+    let instruction = create_versioned_deadline_instruction(
+        1700003600,
+        Constraints {
+            not_before: Some(1700000000),
+            ..Constraints::default()
+        },
+    );
+
+    mollusk.sysvars.clock.unix_timestamp = 1699999999;
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[],
+        &[Check::err(ProgramError::from(DeadlineError::NotYetValid))],
+    );
+
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    mollusk.process_and_validate_instruction(&instruction, &[], &[Check::success()]);
+
+    // The wrapped deadline still applies
+    mollusk.sysvars.clock.unix_timestamp = 1700003601;
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[],
+        &[Check::err(ProgramError::from(
+            DeadlineError::DeadlineExpired,
+        ))],
+    );
+}
+
+#[test]
+fn test_versioned_slot_range() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    let instruction = create_versioned_deadline_instruction(
+        0,
+        Constraints {
+            not_before: None,
+            min_slot: Some(100),
+            max_slot: Some(200),
+        },
+    );
+
+    for (slot, ok) in [(99, false), (100, true), (200, true), (201, false)] {
+        mollusk.sysvars.clock.slot = slot;
+        let check = if ok {
+            Check::success()
+        } else {
+            Check::err(ProgramError::from(DeadlineError::SlotOutOfRange))
+        };
+        mollusk.process_and_validate_instruction(&instruction, &[], &[check]);
+    }
+}
+
+#[test]
+fn test_versioned_with_clock_account() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    mollusk.sysvars.clock.slot = 150;
+    let (clock, clock_account) = mollusk.sysvars.keyed_account_for_clock_sysvar();
+
+    let instruction = with_clock_sysvar(create_versioned_deadline_instruction(
+        1700000000,
+        Constraints {
+            not_before: Some(1700000000),
+            min_slot: Some(150),
+            max_slot: Some(150),
+        },
+    ));
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[(clock, clock_account)],
+        &[Check::success()],
+    );
+}
+
+#[test]
+fn test_versioned_unknown_version_fails() {
+    let mollusk = setup_mollusk();
+    let mut instruction = create_versioned_deadline_instruction(
+        0,
+        Constraints {
+            min_slot: Some(0),
+            ..Constraints::default()
+        },
+    );
+    instruction.data[1] = 2;
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[],
+        &[Check::err(ProgramError::from(
+            DeadlineError::InvalidInstructionData,
+        ))],
+    );
+}
+
+#[test]
+fn test_anchor_alias_validate_deadline() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+
+    let data = DeadlineInstruction::ValidateDeadline {
+        deadline: 1700000000,
+    }
+    .pack_anchor()
+    .unwrap();
+    // Anchor clients pass the program ID for the omitted optional clock
+    let instruction =
+        Instruction::new_with_bytes(id(), &data, vec![AccountMeta::new_readonly(id(), false)]);
+
+    let accounts = [(id(), program::create_program_account_loader_v3(&id()))];
+
+    mollusk.process_and_validate_instruction(&instruction, &accounts, &[Check::success()]);
+
+    mollusk.sysvars.clock.unix_timestamp = 1700000001;
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &accounts,
+        &[Check::err(ProgramError::from(
+            DeadlineError::DeadlineExpired,
+        ))],
+    );
+}
+
+#[test]
+fn test_anchor_alias_validate_session() {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 1700000000;
+    let (fixture, accounts) = setup_session(&mollusk, 0, Some(10));
+
+    let mut instruction = validate_session(&fixture.session, &fixture.delegate, 11);
+    instruction.data = DeadlineInstruction::ValidateSession { amount: 11 }
+        .pack_anchor()
+        .unwrap();
+
+    process_session(
+        &mollusk,
+        &accounts,
+        &[create_delegate_transfer_instruction(
+            &fixture.delegate,
+            SESSION_MINT,
+            11,
+        )],
+        &instruction,
+        &[Check::err(ProgramError::from(
+            DeadlineError::SpendCapExceeded,
+        ))],
+    );
+}
@@ -0,0 +1,45 @@
+//! Program error types
+
+use solana_program::program_error::ProgramError;
+use thiserror::Error;
+
+/// Errors that may be returned by the deadline transfer hook program
+///
+/// Expired transfers fail with the deadline validator's `DeadlineExpired`
+/// so clients decode expiry the same way for both programs.
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TransferHookError {
+    /// The instruction data is invalid or malformed
+    #[error("Invalid instruction data")]
+    InvalidInstructionData,
+
+    /// The mint is not a Token-2022 mint
+    #[error("Invalid mint")]
+    InvalidMint,
+
+    /// The signer is not the mint authority
+    #[error("Signer is not the mint authority")]
+    IncorrectMintAuthority,
+
+    /// An account does not match its expected program address
+    #[error("Account does not match the expected program address")]
+    IncorrectAccountAddress,
+
+    /// The account has already been initialized
+    #[error("Account already initialized")]
+    AlreadyInitialized,
+
+    /// The mint deadline config account is missing or malformed
+    #[error("Invalid mint deadline config")]
+    InvalidConfig,
+
+    /// The extra account metas do not match the ones this hook requires
+    #[error("Unexpected extra account metas")]
+    UnexpectedExtraAccountMetas,
+}
+
+impl From<TransferHookError> for ProgramError {
+    fn from(e: TransferHookError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
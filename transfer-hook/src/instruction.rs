@@ -0,0 +1,131 @@
+//! Program instructions
+//!
+//! `Execute` and `InitializeExtraAccountMetaList` come from the transfer
+//! hook interface; this module only defines the hook's own instructions.
+
+use crate::{error::TransferHookError, state::get_mint_deadline_address};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use spl_discriminator::{ArrayDiscriminator, SplDiscriminate};
+
+/// Instructions supported by the deadline transfer hook program, in
+/// addition to the transfer hook interface
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum DeadlineHookInstruction {
+    /// Sets the deadline after which transfers of a mint fail
+    ///
+    /// The config account must be pre-funded with enough lamports to be
+    /// rent-exempt for `MintDeadline::LEN` bytes.
+    ///
+    /// Accounts expected:
+    /// 0. `[w]` Mint deadline config (PDA `["deadline", mint]`)
+    /// 1. `[]` Token-2022 mint
+    /// 2. `[s]` Mint authority
+    /// 3. `[]` System program
+    ///
+    /// Instruction data layout:
+    /// - Bytes 0-7: Discriminator
+    /// - Bytes 8-15: deadline (i64, little-endian)
+    InitializeMintDeadline {
+        /// Unix timestamp deadline (seconds since epoch)
+        /// Use 0 for "never expires"
+        deadline: i64,
+    },
+}
+
+/// TLV instruction type only used to define the discriminator
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("cascade-protocol-deadline-transfer-hook:initialize-mint-deadline")]
+pub struct InitializeMintDeadlineInstruction;
+
+impl DeadlineHookInstruction {
+    /// Unpacks instruction from byte buffer
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let (discriminator, rest) = input
+            .split_at_checked(ArrayDiscriminator::LENGTH)
+            .ok_or(TransferHookError::InvalidInstructionData)?;
+
+        Ok(match discriminator {
+            InitializeMintDeadlineInstruction::SPL_DISCRIMINATOR_SLICE => {
+                let deadline = i64::from_le_bytes(
+                    rest.try_into()
+                        .map_err(|_| TransferHookError::InvalidInstructionData)?,
+                );
+                Self::InitializeMintDeadline { deadline }
+            }
+            _ => return Err(TransferHookError::InvalidInstructionData.into()),
+        })
+    }
+
+    /// Packs instruction into byte buffer
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(16);
+        match self {
+            Self::InitializeMintDeadline { deadline } => {
+                buf.extend_from_slice(InitializeMintDeadlineInstruction::SPL_DISCRIMINATOR_SLICE);
+                buf.extend_from_slice(&deadline.to_le_bytes());
+            }
+        }
+        buf
+    }
+}
+
+/// Creates an `InitializeMintDeadline` instruction
+pub fn initialize_mint_deadline(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    mint_authority: &Pubkey,
+    deadline: i64,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(get_mint_deadline_address(mint, program_id), false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*mint_authority, true),
+            AccountMeta::new_readonly(solana_system_interface::program::ID, false),
+        ],
+        data: DeadlineHookInstruction::InitializeMintDeadline { deadline }.pack(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spl_transfer_hook_interface::instruction::TransferHookInstruction;
+
+    #[test]
+    fn test_instruction_packing_roundtrip() {
+        let instruction = DeadlineHookInstruction::InitializeMintDeadline {
+            deadline: 1800000000,
+        };
+        let packed = instruction.pack();
+        assert_eq!(packed.len(), 16);
+        assert_eq!(
+            DeadlineHookInstruction::unpack(&packed).unwrap(),
+            instruction
+        );
+    }
+
+    #[test]
+    fn test_invalid_instruction_wrong_length() {
+        let mut data = DeadlineHookInstruction::InitializeMintDeadline { deadline: 1 }.pack();
+        data.push(0);
+        assert!(DeadlineHookInstruction::unpack(&data).is_err());
+        assert!(DeadlineHookInstruction::unpack(&data[..12]).is_err());
+        assert!(DeadlineHookInstruction::unpack(&[]).is_err());
+    }
+
+    #[test]
+    fn test_discriminator_disjoint_from_interface() {
+        let data = DeadlineHookInstruction::InitializeMintDeadline { deadline: 1 }.pack();
+        assert!(TransferHookInstruction::unpack(&data).is_err());
+
+        let data = TransferHookInstruction::Execute { amount: 1 }.pack();
+        assert!(DeadlineHookInstruction::unpack(&data).is_err());
+    }
+}
@@ -0,0 +1,46 @@
+//! Token-2022 transfer hook enforcing a mint-wide expiry
+//!
+//! Vouchers and tickets issued as Token-2022 mints can point their
+//! transfer hook at this program to become non-transferable after a
+//! deadline. Expiry uses exactly the same rules as the deadline
+//! validator's `ValidateDeadline` instruction.
+//!
+//! ## Accounts
+//! - Mint deadline config: PDA `["deadline", mint]` holding the deadline
+//! - Extra account metas: PDA `["extra-account-metas", mint]` resolving
+//!   the config account for Token-2022 during transfers
+//!
+//! ## Usage
+//! 1. Create the mint with the TransferHook extension pointing here
+//! 2. `InitializeMintDeadline` with the mint authority
+//! 3. `InitializeExtraAccountMetaList` with the mint authority
+//!
+//! Transfers fail atomically once the current time exceeds the deadline.
+
+pub mod error;
+pub mod instruction;
+pub mod processor;
+pub mod state;
+
+#[cfg(not(feature = "no-entrypoint"))]
+mod entrypoint {
+    use super::*;
+    use solana_program::{
+        account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, pubkey::Pubkey,
+    };
+
+    entrypoint!(process_instruction);
+
+    pub fn process_instruction(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        instruction_data: &[u8],
+    ) -> ProgramResult {
+        processor::process_instruction(program_id, accounts, instruction_data)
+    }
+}
+
+// Re-export for downstream users
+pub use solana_program;
+
+solana_program::declare_id!("5bJw6UW7FZLUV4ehBmmdRQb1jWGs1YjDjmQ6J9pher1N");
@@ -0,0 +1,211 @@
+//! Program instruction processor
+
+use crate::{
+    error::TransferHookError,
+    instruction::DeadlineHookInstruction,
+    state::{self, get_mint_deadline_address_and_bump_seed, MintDeadline, MINT_DEADLINE_SEED},
+};
+use cascade_protocol_deadline_validator::processor::validate_deadline;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_option::COption,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, state::ExtraAccountMetaList};
+use spl_token_2022_interface::{extension::StateWithExtensions, state::Mint};
+use spl_transfer_hook_interface::{
+    collect_extra_account_metas_signer_seeds, get_extra_account_metas_address_and_bump_seed,
+    instruction::{ExecuteInstruction, TransferHookInstruction},
+};
+
+/// Processes an instruction
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    if let Ok(instruction) = TransferHookInstruction::unpack(instruction_data) {
+        return match instruction {
+            TransferHookInstruction::Execute { .. } => {
+                msg!("Instruction: Execute");
+                process_execute(program_id, accounts)
+            }
+            TransferHookInstruction::InitializeExtraAccountMetaList {
+                extra_account_metas,
+            } => {
+                msg!("Instruction: InitializeExtraAccountMetaList");
+                process_initialize_extra_account_meta_list(
+                    program_id,
+                    accounts,
+                    &extra_account_metas,
+                )
+            }
+            // The config PDA is the only extra account this hook ever needs
+            TransferHookInstruction::UpdateExtraAccountMetaList { .. } => {
+                Err(ProgramError::InvalidInstructionData)
+            }
+        };
+    }
+
+    match DeadlineHookInstruction::unpack(instruction_data)? {
+        DeadlineHookInstruction::InitializeMintDeadline { deadline } => {
+            msg!("Instruction: InitializeMintDeadline");
+            process_initialize_mint_deadline(program_id, accounts, deadline)
+        }
+    }
+}
+
+/// Fails the transfer once the mint's deadline has passed
+///
+/// ## Behavior
+/// Same as `ValidateDeadline`, with the deadline read from the mint's
+/// config account instead of instruction data.
+fn process_execute(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let _source_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let _destination_info = next_account_info(account_info_iter)?;
+    let _authority_info = next_account_info(account_info_iter)?;
+    let extra_account_metas_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+
+    let (extra_account_metas_address, _) =
+        get_extra_account_metas_address_and_bump_seed(mint_info.key, program_id);
+    let (config_address, _) = get_mint_deadline_address_and_bump_seed(mint_info.key, program_id);
+    if extra_account_metas_info.key != &extra_account_metas_address
+        || config_info.key != &config_address
+    {
+        return Err(TransferHookError::IncorrectAccountAddress.into());
+    }
+    if config_info.owner != program_id {
+        return Err(TransferHookError::InvalidConfig.into());
+    }
+
+    let config = MintDeadline::unpack(&config_info.try_borrow_data()?)?;
+    let clock = Clock::get()?;
+    validate_deadline(config.deadline, clock.unix_timestamp)
+}
+
+/// Writes the extra account metas Token-2022 resolves for `Execute`
+///
+/// Only the metas returned by `state::extra_account_metas` are accepted,
+/// so a mint authority cannot point the hook at an arbitrary config.
+fn process_initialize_extra_account_meta_list(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    extra_account_metas: &[ExtraAccountMeta],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let extra_account_metas_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let _system_program_info = next_account_info(account_info_iter)?;
+
+    check_mint_authority(mint_info, authority_info)?;
+
+    if extra_account_metas != state::extra_account_metas()?.as_slice() {
+        return Err(TransferHookError::UnexpectedExtraAccountMetas.into());
+    }
+
+    let (address, bump_seed) =
+        get_extra_account_metas_address_and_bump_seed(mint_info.key, program_id);
+    if extra_account_metas_info.key != &address {
+        return Err(TransferHookError::IncorrectAccountAddress.into());
+    }
+
+    let bump_seed = [bump_seed];
+    let signer_seeds = collect_extra_account_metas_signer_seeds(mint_info.key, &bump_seed);
+    let space = ExtraAccountMetaList::size_of(extra_account_metas.len())?;
+    create_pda_account(extra_account_metas_info, program_id, space, &signer_seeds)?;
+
+    let mut data = extra_account_metas_info.try_borrow_mut_data()?;
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, extra_account_metas)?;
+    Ok(())
+}
+
+/// Creates the per-mint deadline config
+fn process_initialize_mint_deadline(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deadline: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let _system_program_info = next_account_info(account_info_iter)?;
+
+    check_mint_authority(mint_info, authority_info)?;
+
+    let (address, bump_seed) = get_mint_deadline_address_and_bump_seed(mint_info.key, program_id);
+    if config_info.key != &address {
+        return Err(TransferHookError::IncorrectAccountAddress.into());
+    }
+
+    let signer_seeds: &[&[u8]] = &[MINT_DEADLINE_SEED, mint_info.key.as_ref(), &[bump_seed]];
+    create_pda_account(config_info, program_id, MintDeadline::LEN, signer_seeds)?;
+
+    MintDeadline { deadline }.pack(&mut config_info.try_borrow_mut_data()?)?;
+    msg!(
+        "Mint deadline set: mint={}, deadline={}",
+        mint_info.key,
+        deadline
+    );
+    Ok(())
+}
+
+/// Checks that `authority_info` signed and is the mint authority of a
+/// Token-2022 mint
+fn check_mint_authority(mint_info: &AccountInfo, authority_info: &AccountInfo) -> ProgramResult {
+    if !spl_token_2022_interface::check_id(mint_info.owner) {
+        return Err(TransferHookError::InvalidMint.into());
+    }
+    if !authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&mint_data)
+        .map_err(|_| TransferHookError::InvalidMint)?;
+    if mint.base.mint_authority != COption::Some(*authority_info.key) {
+        return Err(TransferHookError::IncorrectMintAuthority.into());
+    }
+    Ok(())
+}
+
+/// Allocates and assigns a pre-funded PDA to this program
+///
+/// Follows the transfer hook interface pattern: the client transfers the
+/// rent-exempt balance first, so the mint authority need not be writable.
+fn create_pda_account(
+    account_info: &AccountInfo,
+    program_id: &Pubkey,
+    space: usize,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    if account_info.owner != &solana_system_interface::program::ID || !account_info.data_is_empty()
+    {
+        return Err(TransferHookError::AlreadyInitialized.into());
+    }
+    if account_info.lamports() < Rent::get()?.minimum_balance(space) {
+        return Err(ProgramError::AccountNotRentExempt);
+    }
+
+    invoke_signed(
+        &solana_system_interface::instruction::allocate(account_info.key, space as u64),
+        std::slice::from_ref(account_info),
+        &[signer_seeds],
+    )?;
+    invoke_signed(
+        &solana_system_interface::instruction::assign(account_info.key, program_id),
+        std::slice::from_ref(account_info),
+        &[signer_seeds],
+    )
+}
@@ -0,0 +1,92 @@
+//! Program state
+
+use crate::error::TransferHookError;
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, seeds::Seed};
+
+/// Seed prefix for the per-mint deadline config PDA
+pub const MINT_DEADLINE_SEED: &[u8] = b"deadline";
+
+/// Returns the mint deadline config address and bump seed for a mint
+pub fn get_mint_deadline_address_and_bump_seed(mint: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MINT_DEADLINE_SEED, mint.as_ref()], program_id)
+}
+
+/// Returns the mint deadline config address for a mint
+pub fn get_mint_deadline_address(mint: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    get_mint_deadline_address_and_bump_seed(mint, program_id).0
+}
+
+/// Extra accounts Token-2022 must pass to `Execute`
+///
+/// Resolves to the mint deadline config PDA, derived from the mint at
+/// index 1 of the `Execute` accounts.
+pub fn extra_account_metas() -> Result<Vec<ExtraAccountMeta>, ProgramError> {
+    Ok(vec![ExtraAccountMeta::new_with_seeds(
+        &[
+            Seed::Literal {
+                bytes: MINT_DEADLINE_SEED.to_vec(),
+            },
+            Seed::AccountKey { index: 1 },
+        ],
+        false,
+        false,
+    )?])
+}
+
+/// Per-mint deadline config
+///
+/// Layout:
+/// - Bytes 0-7: deadline (i64, little-endian)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MintDeadline {
+    /// Unix timestamp after which transfers of the mint fail
+    /// Use 0 for "never expires"
+    pub deadline: i64,
+}
+
+impl MintDeadline {
+    /// Serialized size of the config account
+    pub const LEN: usize = 8;
+
+    /// Unpacks the config from account data
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let bytes: [u8; Self::LEN] = input
+            .try_into()
+            .map_err(|_| TransferHookError::InvalidConfig)?;
+        Ok(Self {
+            deadline: i64::from_le_bytes(bytes),
+        })
+    }
+
+    /// Packs the config into account data
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        if dst.len() != Self::LEN {
+            return Err(TransferHookError::InvalidConfig.into());
+        }
+        dst.copy_from_slice(&self.deadline.to_le_bytes());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mint_deadline_roundtrip() {
+        let config = MintDeadline {
+            deadline: 1800000000,
+        };
+        let mut data = [0u8; MintDeadline::LEN];
+        config.pack(&mut data).unwrap();
+        assert_eq!(MintDeadline::unpack(&data).unwrap(), config);
+    }
+
+    #[test]
+    fn test_mint_deadline_wrong_length() {
+        assert!(MintDeadline::unpack(&[0u8; 7]).is_err());
+        assert!(MintDeadline::unpack(&[0u8; 9]).is_err());
+        assert!(MintDeadline { deadline: 1 }.pack(&mut [0u8; 9]).is_err());
+    }
+}
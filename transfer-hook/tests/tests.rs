@@ -0,0 +1,367 @@
+//! Integration tests for the deadline transfer hook program
+//!
+//! These tests run the hook end-to-end under Mollusk with the Token-2022
+//! program loaded, so transfers reach the hook through a real CPI.
+//!
+//! ## Running Tests
+//! For clean output without verbose DEBUG logs, use:
+//! ```bash
+//! RUST_LOG=off cargo test --test tests
+//! ```
+
+use {
+    cascade_protocol_deadline_transfer_hook::{
+        error::TransferHookError,
+        id,
+        instruction::initialize_mint_deadline,
+        solana_program::program_option::COption,
+        state::{extra_account_metas, get_mint_deadline_address, MintDeadline},
+    },
+    cascade_protocol_deadline_validator::error::DeadlineError,
+    mollusk_svm::{program, result::Check, Mollusk},
+    mollusk_svm_programs_token::token2022,
+    solana_account::Account,
+    solana_instruction::{AccountMeta, Instruction},
+    solana_program_error::ProgramError,
+    solana_pubkey::Pubkey,
+    spl_pod::optional_keys::OptionalNonZeroPubkey,
+    spl_token_2022_interface::{
+        extension::{
+            transfer_hook::{TransferHook, TransferHookAccount},
+            BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut,
+        },
+        state::{Account as TokenAccount, AccountState, Mint},
+    },
+    spl_transfer_hook_interface::{
+        get_extra_account_metas_address, instruction::initialize_extra_account_meta_list,
+    },
+};
+
+const DEADLINE: i64 = 1800000000; // Jan 15, 2027
+const DECIMALS: u8 = 0;
+
+/// Helper function to create a Mollusk instance with the hook and Token-2022
+fn setup_mollusk(unix_timestamp: i64) -> Mollusk {
+    std::env::set_var("SBF_OUT_DIR", "../target/deploy");
+    let mut mollusk = Mollusk::new(&id(), "cascade_protocol_deadline_transfer_hook");
+    token2022::add_program(&mut mollusk);
+    mollusk.sysvars.clock.unix_timestamp = unix_timestamp;
+    mollusk
+}
+
+/// Keys involved in a hooked transfer
+struct Fixture {
+    mint: Pubkey,
+    mint_authority: Pubkey,
+    source: Pubkey,
+    destination: Pubkey,
+    owner: Pubkey,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        Self {
+            mint: Pubkey::new_unique(),
+            mint_authority: Pubkey::new_unique(),
+            source: Pubkey::new_unique(),
+            destination: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+        }
+    }
+
+    fn config(&self) -> Pubkey {
+        get_mint_deadline_address(&self.mint, &id())
+    }
+
+    fn extra_account_metas(&self) -> Pubkey {
+        get_extra_account_metas_address(&self.mint, &id())
+    }
+
+    /// Accounts before the hook is configured
+    fn accounts(&self, mollusk: &Mollusk) -> Vec<(Pubkey, Account)> {
+        let rent = &mollusk.sysvars.rent;
+        let prefunded = |space: usize| {
+            Account::new(
+                rent.minimum_balance(space),
+                0,
+                &solana_system_interface::program::ID,
+            )
+        };
+        let extra_metas_space =
+            spl_tlv_account_resolution::state::ExtraAccountMetaList::size_of(1).unwrap();
+
+        vec![
+            (self.mint, self.mint_account(mollusk)),
+            (self.source, self.token_account(mollusk, self.owner, 100)),
+            (
+                self.destination,
+                self.token_account(mollusk, Pubkey::new_unique(), 0),
+            ),
+            (
+                self.mint_authority,
+                Account::new(1_000_000_000, 0, &solana_system_interface::program::ID),
+            ),
+            (
+                self.owner,
+                Account::new(1_000_000_000, 0, &solana_system_interface::program::ID),
+            ),
+            (self.config(), prefunded(MintDeadline::LEN)),
+            (self.extra_account_metas(), prefunded(extra_metas_space)),
+            program::keyed_account_for_system_program(),
+            token2022::keyed_account(),
+            (id(), program::create_program_account_loader_v3(&id())),
+        ]
+    }
+
+    fn mint_account(&self, mollusk: &Mollusk) -> Account {
+        let space =
+            ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferHook])
+                .unwrap();
+        let mut data = vec![0u8; space];
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        let extension = state.init_extension::<TransferHook>(true).unwrap();
+        extension.authority = OptionalNonZeroPubkey::try_from(Some(self.mint_authority)).unwrap();
+        extension.program_id = OptionalNonZeroPubkey::try_from(Some(id())).unwrap();
+        state.base = Mint {
+            mint_authority: COption::Some(self.mint_authority),
+            supply: 100,
+            decimals: DECIMALS,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+
+        Account {
+            lamports: mollusk.sysvars.rent.minimum_balance(space),
+            data,
+            owner: token2022::ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    fn token_account(&self, mollusk: &Mollusk, owner: Pubkey, amount: u64) -> Account {
+        let space = ExtensionType::try_calculate_account_len::<TokenAccount>(&[
+            ExtensionType::TransferHookAccount,
+        ])
+        .unwrap();
+        let mut data = vec![0u8; space];
+        let mut state =
+            StateWithExtensionsMut::<TokenAccount>::unpack_uninitialized(&mut data).unwrap();
+        state.init_extension::<TransferHookAccount>(true).unwrap();
+        state.base = TokenAccount {
+            mint: self.mint,
+            owner,
+            amount,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+
+        Account {
+            lamports: mollusk.sysvars.rent.minimum_balance(space),
+            data,
+            owner: token2022::ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    /// Instructions the mint authority sends once to configure the hook
+    fn setup_instructions(&self, deadline: i64) -> [Instruction; 2] {
+        [
+            initialize_mint_deadline(&id(), &self.mint, &self.mint_authority, deadline),
+            initialize_extra_account_meta_list(
+                &id(),
+                &self.extra_account_metas(),
+                &self.mint,
+                &self.mint_authority,
+                &extra_account_metas().unwrap(),
+            ),
+        ]
+    }
+
+    /// A Token-2022 `TransferChecked` with the hook's extra accounts appended
+    fn transfer_instruction(&self, amount: u64) -> Instruction {
+        let mut instruction = spl_token_2022_interface::instruction::transfer_checked(
+            &token2022::ID,
+            &self.source,
+            &self.mint,
+            &self.destination,
+            &self.owner,
+            &[],
+            amount,
+            DECIMALS,
+        )
+        .unwrap();
+        instruction.accounts.extend([
+            AccountMeta::new_readonly(self.config(), false),
+            AccountMeta::new_readonly(id(), false),
+            AccountMeta::new_readonly(self.extra_account_metas(), false),
+        ]);
+        instruction
+    }
+}
+
+/// Configures the hook and returns the resulting accounts
+fn configure(mollusk: &Mollusk, fixture: &Fixture, deadline: i64) -> Vec<(Pubkey, Account)> {
+    let setup = fixture.setup_instructions(deadline);
+    mollusk
+        .process_and_validate_instruction_chain(
+            &[
+                (&setup[0], &[Check::success()]),
+                (&setup[1], &[Check::success()]),
+            ],
+            &fixture.accounts(mollusk),
+        )
+        .resulting_accounts
+}
+
+fn token_amount(accounts: &[(Pubkey, Account)], key: &Pubkey) -> u64 {
+    let (_, account) = accounts.iter().find(|(k, _)| k == key).unwrap();
+    let mut data = account.data.clone();
+    StateWithExtensionsMut::<TokenAccount>::unpack(&mut data)
+        .unwrap()
+        .base
+        .amount
+}
+
+#[test]
+fn test_transfer_before_deadline_succeeds() {
+    let mollusk = setup_mollusk(DEADLINE - 3600);
+    let fixture = Fixture::new();
+    let accounts = configure(&mollusk, &fixture, DEADLINE);
+
+    let result = mollusk.process_and_validate_instruction(
+        &fixture.transfer_instruction(40),
+        &accounts,
+        &[Check::success()],
+    );
+
+    assert_eq!(
+        token_amount(&result.resulting_accounts, &fixture.source),
+        60
+    );
+    assert_eq!(
+        token_amount(&result.resulting_accounts, &fixture.destination),
+        40
+    );
+}
+
+#[test]
+fn test_transfer_at_exact_deadline_succeeds() {
+    let mollusk = setup_mollusk(DEADLINE);
+    let fixture = Fixture::new();
+    let accounts = configure(&mollusk, &fixture, DEADLINE);
+
+    // Deadline exactly matching current time should succeed (inclusive behavior)
+    mollusk.process_and_validate_instruction(
+        &fixture.transfer_instruction(1),
+        &accounts,
+        &[Check::success()],
+    );
+}
+
+#[test]
+fn test_transfer_after_deadline_fails() {
+    let mut mollusk = setup_mollusk(DEADLINE - 3600);
+    let fixture = Fixture::new();
+    let accounts = configure(&mollusk, &fixture, DEADLINE);
+
+    // One second past the deadline the mint becomes non-transferable
+    mollusk.sysvars.clock.unix_timestamp = DEADLINE + 1;
+
+    mollusk.process_and_validate_instruction(
+        &fixture.transfer_instruction(1),
+        &accounts,
+        &[Check::err(ProgramError::from(
+            DeadlineError::DeadlineExpired,
+        ))],
+    );
+}
+
+#[test]
+fn test_zero_deadline_never_expires() {
+    let mut mollusk = setup_mollusk(1700000000);
+    let fixture = Fixture::new();
+    let accounts = configure(&mollusk, &fixture, 0);
+
+    mollusk.sysvars.clock.unix_timestamp = i64::MAX;
+
+    mollusk.process_and_validate_instruction(
+        &fixture.transfer_instruction(1),
+        &accounts,
+        &[Check::success()],
+    );
+}
+
+#[test]
+fn test_initialize_mint_deadline_wrong_authority_fails() {
+    let mollusk = setup_mollusk(1700000000);
+    let fixture = Fixture::new();
+    let impostor = Pubkey::new_unique();
+
+    let mut accounts = fixture.accounts(&mollusk);
+    accounts.push((
+        impostor,
+        Account::new(1_000_000_000, 0, &solana_system_interface::program::ID),
+    ));
+
+    mollusk.process_and_validate_instruction(
+        &initialize_mint_deadline(&id(), &fixture.mint, &impostor, DEADLINE),
+        &accounts,
+        &[Check::err(ProgramError::from(
+            TransferHookError::IncorrectMintAuthority,
+        ))],
+    );
+}
+
+#[test]
+fn test_initialize_mint_deadline_twice_fails() {
+    let mollusk = setup_mollusk(1700000000);
+    let fixture = Fixture::new();
+    let accounts = configure(&mollusk, &fixture, DEADLINE);
+
+    mollusk.process_and_validate_instruction(
+        &initialize_mint_deadline(&id(), &fixture.mint, &fixture.mint_authority, 0),
+        &accounts,
+        &[Check::err(ProgramError::from(
+            TransferHookError::AlreadyInitialized,
+        ))],
+    );
+}
+
+#[test]
+fn test_initialize_extra_account_metas_rejects_other_metas() {
+    let mollusk = setup_mollusk(1700000000);
+    let fixture = Fixture::new();
+
+    let other_metas = [
+        spl_tlv_account_resolution::account::ExtraAccountMeta::new_with_pubkey(
+            &Pubkey::new_unique(),
+            false,
+            false,
+        )
+        .unwrap(),
+    ];
+    let instruction = initialize_extra_account_meta_list(
+        &id(),
+        &fixture.extra_account_metas(),
+        &fixture.mint,
+        &fixture.mint_authority,
+        &other_metas,
+    );
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &fixture.accounts(&mollusk),
+        &[Check::err(ProgramError::from(
+            TransferHookError::UnexpectedExtraAccountMetas,
+        ))],
+    );
+}
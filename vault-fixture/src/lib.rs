@@ -0,0 +1,164 @@
+//! Squads-style vault test fixture
+//!
+//! A minimal smart wallet for testing facilitators' simulation-based
+//! verification: like Squads v4 `vault_transaction_execute`, `Execute`
+//! runs an embedded instruction through `invoke_signed` with a vault PDA as
+//! signer, so the payment's `TransferChecked` only happens as a CPI.
+//!
+//! Test-only: there are no proposals or thresholds, and any member of the
+//! multisig can execute. Never deploy it.
+//!
+//! ## Accounts
+//! - Multisig: owned by this program, its data is the members' addresses,
+//!   32 bytes each
+//! - Vault: PDA `["multisig", multisig, "vault", [vault_index]]`
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+#[cfg(not(feature = "no-entrypoint"))]
+mod entrypoint {
+    use super::process_instruction;
+    use solana_program::entrypoint;
+
+    entrypoint!(process_instruction);
+}
+
+// Re-export for downstream users
+pub use solana_program;
+
+solana_program::declare_id!("4NjE29dL59BnnjmPZ9bEosNb4J8uyhg1pXFL62MR856j");
+
+/// Seed prefix of the vault PDA
+pub const SEED_PREFIX: &[u8] = b"multisig";
+
+/// Seed of the vault PDA after the multisig
+pub const SEED_VAULT: &[u8] = b"vault";
+
+/// Discriminator of `Execute`
+const EXECUTE: u8 = 0;
+
+/// Returns the vault of a multisig and its bump seed
+pub fn get_vault_address_and_bump_seed(
+    multisig: &Pubkey,
+    vault_index: u8,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[SEED_PREFIX, multisig.as_ref(), SEED_VAULT, &[vault_index]],
+        program_id,
+    )
+}
+
+/// Creates an `Execute` instruction running `instruction` as the vault
+///
+/// Accounts expected:
+/// 0. `[]` Multisig
+/// 1. `[s]` Member
+/// 2. `[w]` Vault
+/// 3. `[]` Program of the embedded instruction
+/// 4. .. Accounts of the embedded instruction, with the vault as signer
+///
+/// Instruction data layout:
+/// - Byte 0: Discriminator (0 = Execute)
+/// - Byte 1: vault index
+/// - Bytes 2..: data of the embedded instruction
+pub fn execute(
+    multisig: &Pubkey,
+    member: &Pubkey,
+    vault_index: u8,
+    instruction: &Instruction,
+) -> Instruction {
+    let vault = get_vault_address_and_bump_seed(multisig, vault_index, &id()).0;
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*multisig, false),
+        AccountMeta::new_readonly(*member, true),
+        AccountMeta::new(vault, false),
+        AccountMeta::new_readonly(instruction.program_id, false),
+    ];
+    // The vault signs through `invoke_signed`, not the transaction
+    accounts.extend(instruction.accounts.iter().map(|meta| AccountMeta {
+        is_signer: meta.is_signer && meta.pubkey != vault,
+        ..meta.clone()
+    }));
+    let mut data = vec![EXECUTE, vault_index];
+    data.extend_from_slice(&instruction.data);
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Processes an instruction
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let Some((&EXECUTE, rest)) = instruction_data.split_first() else {
+        return Err(ProgramError::InvalidInstructionData);
+    };
+    let (&vault_index, data) = rest
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    msg!("Instruction: Execute");
+
+    let account_info_iter = &mut accounts.iter();
+    let multisig_info = next_account_info(account_info_iter)?;
+    let member_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let program_info = next_account_info(account_info_iter)?;
+    let remaining = account_info_iter.as_slice();
+
+    if multisig_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if !member_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let is_member = multisig_info
+        .try_borrow_data()?
+        .chunks_exact(32)
+        .any(|member| member == member_info.key.as_ref());
+    if !is_member {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let (vault, bump) = get_vault_address_and_bump_seed(multisig_info.key, vault_index, program_id);
+    if vault_info.key != &vault {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let instruction = Instruction {
+        program_id: *program_info.key,
+        accounts: remaining
+            .iter()
+            .map(|info| AccountMeta {
+                pubkey: *info.key,
+                is_signer: info.is_signer || info.key == &vault,
+                is_writable: info.is_writable,
+            })
+            .collect(),
+        data: data.to_vec(),
+    };
+    let mut infos = vec![vault_info.clone(), program_info.clone()];
+    infos.extend(remaining.iter().cloned());
+    invoke_signed(
+        &instruction,
+        &infos,
+        &[&[
+            SEED_PREFIX,
+            multisig_info.key.as_ref(),
+            SEED_VAULT,
+            &[vault_index],
+            &[bump],
+        ]],
+    )
+}
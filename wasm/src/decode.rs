@@ -0,0 +1,166 @@
+//! Decoding of raw instruction data into plain JavaScript objects
+
+use cascade_protocol_deadline_codec::{Constraints, DeadlineInstruction, Envelope, Op};
+use js_sys::{Array, BigInt, Object, Reflect, Uint8Array};
+use solana_pubkey::Pubkey;
+use wasm_bindgen::prelude::*;
+
+/// Decodes instruction data of the deadline validator program
+///
+/// Accepts every encoding the program accepts, including the compact
+/// deadline forms, Anchor aliases and the versioned envelope. Returns
+/// `{ type, info }`, plus `constraints` for a versioned envelope with any
+/// constraint set, e.g.
+/// `{ type: "validateDeadline", info: { deadline: 1800000000n } }`.
+/// Throws on data the program would reject as `InvalidInstructionData`.
+#[wasm_bindgen(js_name = decodeInstruction)]
+pub fn decode_instruction(data: &[u8]) -> Result<JsValue, JsError> {
+    let envelope = Envelope::unpack(data).map_err(JsError::from)?;
+    let (name, info) = describe(&envelope.instruction);
+    let decoded = object(&[("type", name.into()), ("info", info)]);
+    if !envelope.constraints.is_empty() {
+        set(&decoded, "constraints", constraints(&envelope.constraints));
+    }
+    Ok(decoded)
+}
+
+/// Returns the camelCase instruction name and its fields
+fn describe(instruction: &DeadlineInstruction) -> (&'static str, JsValue) {
+    match *instruction {
+        DeadlineInstruction::ValidateDeadline { deadline } => (
+            "validateDeadline",
+            object(&[("deadline", BigInt::from(deadline).into())]),
+        ),
+        DeadlineInstruction::ValidateOracleClock {
+            max_drift,
+            max_staleness_slots,
+            feed_id,
+            min_publish_time,
+        } => (
+            "validateOracleClock",
+            object(&[
+                ("maxDrift", BigInt::from(max_drift).into()),
+                (
+                    "maxStalenessSlots",
+                    BigInt::from(max_staleness_slots).into(),
+                ),
+                ("feedId", Uint8Array::from(&feed_id[..]).into()),
+                ("minPublishTime", BigInt::from(min_publish_time).into()),
+            ]),
+        ),
+        DeadlineInstruction::ValidateClockSanity { max_drift } => (
+            "validateClockSanity",
+            object(&[("maxDrift", BigInt::from(max_drift).into())]),
+        ),
+        DeadlineInstruction::ValidateDutchAuction {
+            start_price,
+            floor_price,
+            start_time,
+            end_time,
+            mint,
+            destination,
+        } => (
+            "validateDutchAuction",
+            object(&[
+                ("startPrice", BigInt::from(start_price).into()),
+                ("floorPrice", BigInt::from(floor_price).into()),
+                ("startTime", BigInt::from(start_time).into()),
+                ("endTime", BigInt::from(end_time).into()),
+                ("mint", Pubkey::new_from_array(mint).to_string().into()),
+                (
+                    "destination",
+                    Pubkey::new_from_array(destination).to_string().into(),
+                ),
+            ]),
+        ),
+        DeadlineInstruction::ValidateSchedule { schedule } => (
+            "validateSchedule",
+            object(&[("schedule", Uint8Array::from(&schedule[..]).into())]),
+        ),
+        DeadlineInstruction::ValidateExpression { ops } => (
+            "validateExpression",
+            object(&[("ops", ops.ops().map(op).collect::<Array>().into())]),
+        ),
+        DeadlineInstruction::InitializeCooldown { min_interval, seed } => (
+            "initializeCooldown",
+            object(&[
+                ("minInterval", BigInt::from(min_interval).into()),
+                ("seed", BigInt::from(seed).into()),
+            ]),
+        ),
+        DeadlineInstruction::CheckAndBumpCooldown => ("checkAndBumpCooldown", object(&[])),
+        DeadlineInstruction::CreateSession {
+            delegate,
+            expires_at,
+            spend_cap,
+            seed,
+        } => (
+            "createSession",
+            object(&[
+                (
+                    "delegate",
+                    Pubkey::new_from_array(delegate).to_string().into(),
+                ),
+                ("expiresAt", BigInt::from(expires_at).into()),
+                (
+                    "spendCap",
+                    spend_cap.map_or(JsValue::NULL, |cap| BigInt::from(cap).into()),
+                ),
+                ("seed", BigInt::from(seed).into()),
+            ]),
+        ),
+        DeadlineInstruction::RevokeSession => ("revokeSession", object(&[])),
+        DeadlineInstruction::ValidateSession { amount } => (
+            "validateSession",
+            object(&[("amount", BigInt::from(amount).into())]),
+        ),
+    }
+}
+
+/// Returns `{ op, operand }`, without `operand` for logical ops
+fn op(op: Op) -> JsValue {
+    let (name, operand): (&str, Option<BigInt>) = match op {
+        Op::TimeLe(t) => ("timeLe", Some(t.into())),
+        Op::TimeGe(t) => ("timeGe", Some(t.into())),
+        Op::SlotLe(s) => ("slotLe", Some(s.into())),
+        Op::SlotGe(s) => ("slotGe", Some(s.into())),
+        Op::EpochLe(e) => ("epochLe", Some(e.into())),
+        Op::EpochGe(e) => ("epochGe", Some(e.into())),
+        Op::And => ("and", None),
+        Op::Or => ("or", None),
+        Op::Not => ("not", None),
+    };
+    let value = object(&[("op", name.into())]);
+    if let Some(operand) = operand {
+        set(&value, "operand", operand.into());
+    }
+    value
+}
+
+/// Returns the set constraints as `{ notBefore?, minSlot?, maxSlot? }`
+fn constraints(constraints: &Constraints) -> JsValue {
+    let value = object(&[]);
+    if let Some(not_before) = constraints.not_before {
+        set(&value, "notBefore", BigInt::from(not_before).into());
+    }
+    if let Some(min_slot) = constraints.min_slot {
+        set(&value, "minSlot", BigInt::from(min_slot).into());
+    }
+    if let Some(max_slot) = constraints.max_slot {
+        set(&value, "maxSlot", BigInt::from(max_slot).into());
+    }
+    value
+}
+
+fn object(entries: &[(&str, JsValue)]) -> JsValue {
+    let value: JsValue = Object::new().into();
+    for (key, entry) in entries {
+        set(&value, key, entry.clone());
+    }
+    value
+}
+
+fn set(object: &JsValue, key: &str, value: JsValue) {
+    // Setting a property on a fresh plain object cannot fail
+    let _ = Reflect::set(object, &key.into(), &value);
+}
@@ -0,0 +1,299 @@
+//! Instruction builders
+//!
+//! Thin wrappers over `cascade_protocol_deadline_validator_client::instruction`
+//! taking JavaScript-friendly arguments.
+
+use crate::parse_pubkey;
+use cascade_protocol_deadline_validator_client::instruction::{self as client, Constraints};
+use wasm_bindgen::prelude::*;
+
+/// Account passed to an instruction, shaped like web3.js `AccountMeta`
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountMeta {
+    /// Base58 address
+    pub pubkey: String,
+    /// Whether the account must sign
+    #[wasm_bindgen(js_name = isSigner)]
+    pub is_signer: bool,
+    /// Whether the account is writable
+    #[wasm_bindgen(js_name = isWritable)]
+    pub is_writable: bool,
+}
+
+/// Instruction for the deadline validator program
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instruction(solana_instruction::Instruction);
+
+#[wasm_bindgen]
+impl Instruction {
+    /// Base58 program ID
+    #[wasm_bindgen(getter, js_name = programId)]
+    pub fn program_id(&self) -> String {
+        self.0.program_id.to_string()
+    }
+
+    /// Accounts, in order
+    #[wasm_bindgen(getter)]
+    pub fn accounts(&self) -> Vec<AccountMeta> {
+        self.0
+            .accounts
+            .iter()
+            .map(|meta| AccountMeta {
+                pubkey: meta.pubkey.to_string(),
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect()
+    }
+
+    /// Instruction data
+    #[wasm_bindgen(getter)]
+    pub fn data(&self) -> Vec<u8> {
+        self.0.data.clone()
+    }
+}
+
+impl From<solana_instruction::Instruction> for Instruction {
+    fn from(instruction: solana_instruction::Instruction) -> Self {
+        Self(instruction)
+    }
+}
+
+impl From<Instruction> for solana_instruction::Instruction {
+    fn from(instruction: Instruction) -> Self {
+        instruction.0
+    }
+}
+
+/// A single time-predicate expression op
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Op(client::Op);
+
+#[wasm_bindgen]
+impl Op {
+    /// Pushes unix_timestamp <= operand
+    #[wasm_bindgen(js_name = timeLe)]
+    pub fn time_le(unix_timestamp: i64) -> Self {
+        Self(client::Op::TimeLe(unix_timestamp))
+    }
+
+    /// Pushes unix_timestamp >= operand
+    #[wasm_bindgen(js_name = timeGe)]
+    pub fn time_ge(unix_timestamp: i64) -> Self {
+        Self(client::Op::TimeGe(unix_timestamp))
+    }
+
+    /// Pushes slot <= operand
+    #[wasm_bindgen(js_name = slotLe)]
+    pub fn slot_le(slot: u64) -> Self {
+        Self(client::Op::SlotLe(slot))
+    }
+
+    /// Pushes slot >= operand
+    #[wasm_bindgen(js_name = slotGe)]
+    pub fn slot_ge(slot: u64) -> Self {
+        Self(client::Op::SlotGe(slot))
+    }
+
+    /// Pushes epoch <= operand
+    #[wasm_bindgen(js_name = epochLe)]
+    pub fn epoch_le(epoch: u64) -> Self {
+        Self(client::Op::EpochLe(epoch))
+    }
+
+    /// Pushes epoch >= operand
+    #[wasm_bindgen(js_name = epochGe)]
+    pub fn epoch_ge(epoch: u64) -> Self {
+        Self(client::Op::EpochGe(epoch))
+    }
+
+    /// Pops two values and pushes their conjunction
+    pub fn and() -> Self {
+        Self(client::Op::And)
+    }
+
+    /// Pops two values and pushes their disjunction
+    pub fn or() -> Self {
+        Self(client::Op::Or)
+    }
+
+    /// Pops one value and pushes its negation
+    pub fn not() -> Self {
+        Self(client::Op::Not)
+    }
+}
+
+/// Creates a `ValidateDeadline` instruction, using the shortest encoding
+#[wasm_bindgen(js_name = validateDeadline)]
+pub fn validate_deadline(deadline: i64) -> Instruction {
+    client::validate_deadline(deadline).into()
+}
+
+/// Creates a `ValidateOracleClock` instruction for the price feed with the
+/// 32-byte `feed_id`
+#[wasm_bindgen(js_name = validateOracleClock)]
+pub fn validate_oracle_clock(
+    price_update: &str,
+    max_drift: u64,
+    max_staleness_slots: u64,
+    feed_id: &[u8],
+    min_publish_time: i64,
+) -> Result<Instruction, JsError> {
+    let price_update = parse_pubkey("priceUpdate", price_update)?;
+    let feed_id = feed_id
+        .try_into()
+        .map_err(|_| JsError::new("feedId must be 32 bytes"))?;
+    Ok(client::validate_oracle_clock(
+        &price_update,
+        max_drift,
+        max_staleness_slots,
+        feed_id,
+        min_publish_time,
+    )
+    .into())
+}
+
+/// Creates a `ValidateClockSanity` instruction
+#[wasm_bindgen(js_name = validateClockSanity)]
+pub fn validate_clock_sanity(max_drift: u64) -> Instruction {
+    client::validate_clock_sanity(max_drift).into()
+}
+
+/// Creates a `ValidateDutchAuction` instruction paid in `mint` to the
+/// `destination` token account
+#[wasm_bindgen(js_name = validateDutchAuction)]
+pub fn validate_dutch_auction(
+    mint: &str,
+    destination: &str,
+    start_price: u64,
+    floor_price: u64,
+    start_time: i64,
+    end_time: i64,
+) -> Result<Instruction, JsError> {
+    let mint = parse_pubkey("mint", mint)?;
+    let destination = parse_pubkey("destination", destination)?;
+    Ok(client::validate_dutch_auction(
+        &mint,
+        &destination,
+        start_price,
+        floor_price,
+        start_time,
+        end_time,
+    )
+    .into())
+}
+
+/// Creates a `ValidateSchedule` instruction from a 21-byte weekly bitmap
+#[wasm_bindgen(js_name = validateSchedule)]
+pub fn validate_schedule(schedule: &[u8]) -> Result<Instruction, JsError> {
+    let schedule = schedule
+        .try_into()
+        .map_err(|_| JsError::new("schedule must be 21 bytes"))?;
+    Ok(client::validate_schedule(schedule).into())
+}
+
+/// Creates a `ValidateExpression` instruction
+#[wasm_bindgen(js_name = validateExpression)]
+pub fn validate_expression(ops: Vec<Op>) -> Instruction {
+    let ops: Vec<client::Op> = ops.into_iter().map(|op| op.0).collect();
+    client::validate_expression(&ops).into()
+}
+
+/// Creates an `InitializeCooldown` instruction
+#[wasm_bindgen(js_name = initializeCooldown)]
+pub fn initialize_cooldown(
+    payer: &str,
+    authority: &str,
+    min_interval: u64,
+    seed: u64,
+) -> Result<Instruction, JsError> {
+    let payer = parse_pubkey("payer", payer)?;
+    let authority = parse_pubkey("authority", authority)?;
+    Ok(client::initialize_cooldown(&payer, &authority, min_interval, seed).into())
+}
+
+/// Creates a `CheckAndBumpCooldown` instruction
+#[wasm_bindgen(js_name = checkAndBumpCooldown)]
+pub fn check_and_bump_cooldown(cooldown: &str, authority: &str) -> Result<Instruction, JsError> {
+    let cooldown = parse_pubkey("cooldown", cooldown)?;
+    let authority = parse_pubkey("authority", authority)?;
+    Ok(client::check_and_bump_cooldown(&cooldown, &authority).into())
+}
+
+/// Creates a `CreateSession` instruction
+#[wasm_bindgen(js_name = createSession)]
+pub fn create_session(
+    payer: &str,
+    owner: &str,
+    delegate: &str,
+    expires_at: i64,
+    spend_cap: Option<u64>,
+    seed: u64,
+) -> Result<Instruction, JsError> {
+    let payer = parse_pubkey("payer", payer)?;
+    let owner = parse_pubkey("owner", owner)?;
+    let delegate = parse_pubkey("delegate", delegate)?;
+    Ok(client::create_session(&payer, &owner, &delegate, expires_at, spend_cap, seed).into())
+}
+
+/// Creates a `RevokeSession` instruction
+#[wasm_bindgen(js_name = revokeSession)]
+pub fn revoke_session(session: &str, owner: &str) -> Result<Instruction, JsError> {
+    let session = parse_pubkey("session", session)?;
+    let owner = parse_pubkey("owner", owner)?;
+    Ok(client::revoke_session(&session, &owner).into())
+}
+
+/// Creates a `ValidateSession` instruction
+#[wasm_bindgen(js_name = validateSession)]
+pub fn validate_session(
+    session: &str,
+    delegate: &str,
+    amount: u64,
+) -> Result<Instruction, JsError> {
+    let session = parse_pubkey("session", session)?;
+    let delegate = parse_pubkey("delegate", delegate)?;
+    Ok(client::validate_session(&session, &delegate, amount).into())
+}
+
+/// Returns the cooldown address for an authority and seed
+#[wasm_bindgen(js_name = cooldownAddress)]
+pub fn cooldown_address(authority: &str, seed: u64) -> Result<String, JsError> {
+    let authority = parse_pubkey("authority", authority)?;
+    Ok(client::cooldown_address(&authority, seed).to_string())
+}
+
+/// Returns the session address for an owner and seed
+#[wasm_bindgen(js_name = sessionAddress)]
+pub fn session_address(owner: &str, seed: u64) -> Result<String, JsError> {
+    let owner = parse_pubkey("owner", owner)?;
+    Ok(client::session_address(&owner, seed).to_string())
+}
+
+/// Wraps an instruction in a versioned envelope with the given constraints
+///
+/// Returns the instruction unchanged when no constraint is set.
+#[wasm_bindgen]
+pub fn versioned(
+    instruction: &Instruction,
+    not_before: Option<i64>,
+    min_slot: Option<u64>,
+    max_slot: Option<u64>,
+) -> Instruction {
+    let constraints = Constraints {
+        not_before,
+        min_slot,
+        max_slot,
+    };
+    client::versioned(instruction.0.clone(), &constraints).into()
+}
+
+/// Passes the Clock sysvar as an account, for SVM forks without the clock
+/// syscall
+#[wasm_bindgen(js_name = withClockSysvar)]
+pub fn with_clock_sysvar(instruction: &Instruction) -> Instruction {
+    client::with_clock_sysvar(instruction.0.clone()).into()
+}
@@ -0,0 +1,37 @@
+//! WebAssembly bindings for the deadline validator program
+//!
+//! Exposes the instruction builders of the client crate, a decoder for raw
+//! instruction data and a transaction-level deadline lookup to JavaScript.
+//! All encoding goes through `cascade_protocol_deadline_codec`, the codec
+//! the program itself unpacks with, so browser checkouts no longer need to
+//! re-implement the byte layout.
+//!
+//! Build with `wasm-pack build wasm`. Public keys are passed and returned as
+//! base58 strings, and 64-bit integers as `bigint`.
+//!
+//! ```js
+//! import { validateDeadline, findDeadline } from "cascade-protocol-deadline-validator-wasm";
+//!
+//! const ix = validateDeadline(BigInt(Math.floor(Date.now() / 1000) + 300));
+//! // new TransactionInstruction({ programId: new PublicKey(ix.programId), ... })
+//! ```
+
+pub mod decode;
+pub mod instruction;
+pub mod transaction;
+
+use solana_pubkey::Pubkey;
+use wasm_bindgen::prelude::*;
+
+/// Returns the program ID as a base58 string
+#[wasm_bindgen(js_name = programId)]
+pub fn program_id() -> String {
+    cascade_protocol_deadline_validator_client::ID.to_string()
+}
+
+/// Parses a base58 public key argument
+fn parse_pubkey(name: &str, value: &str) -> Result<Pubkey, JsError> {
+    value
+        .parse()
+        .map_err(|_| JsError::new(&format!("{name} is not a valid public key")))
+}
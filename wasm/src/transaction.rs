@@ -0,0 +1,22 @@
+//! Transaction-level lookups
+
+use cascade_protocol_deadline_validator_client::scan_message;
+use solana_transaction::versioned::VersionedTransaction;
+use wasm_bindgen::prelude::*;
+
+/// Returns the earliest deadline enforced by a serialized transaction
+///
+/// Accepts legacy and v0 transactions in wire format, as returned by
+/// web3.js `serialize()`. Looks at every top-level `ValidateDeadline`
+/// instruction, plain or versioned, and ignores deadline 0, which never
+/// expires. Returns `undefined` when the transaction carries no deadline.
+/// Throws if the transaction cannot be deserialized or an instruction of
+/// the program is malformed.
+#[wasm_bindgen(js_name = findDeadline)]
+pub fn find_deadline(transaction: &[u8]) -> Result<Option<i64>, JsError> {
+    let transaction: VersionedTransaction =
+        bincode::deserialize(transaction).map_err(|_| JsError::new("invalid transaction"))?;
+    // Program IDs are always static keys, even in v0 messages
+    let constraints = scan_message(&transaction.message, None).map_err(JsError::from)?;
+    Ok(constraints.deadline)
+}
@@ -0,0 +1,198 @@
+//! Run with `wasm-pack test --node wasm`
+
+#![cfg(target_arch = "wasm32")]
+
+use cascade_protocol_deadline_validator_client::{instruction as client, ID};
+use cascade_protocol_deadline_validator_wasm::{
+    decode::decode_instruction,
+    instruction::{self as wasm, Op},
+    program_id,
+    transaction::find_deadline,
+};
+use js_sys::{BigInt, Reflect};
+use solana_hash::Hash;
+use solana_instruction::Instruction;
+use solana_message::{v0, Message, VersionedMessage};
+use solana_pubkey::Pubkey;
+use solana_transaction::versioned::VersionedTransaction;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn get(value: &JsValue, path: &[&str]) -> JsValue {
+    path.iter().fold(value.clone(), |value, key| {
+        Reflect::get(&value, &(*key).into()).unwrap()
+    })
+}
+
+fn bigint(value: impl Into<BigInt>) -> JsValue {
+    value.into().into()
+}
+
+fn serialize(message: VersionedMessage) -> Vec<u8> {
+    let transaction = VersionedTransaction {
+        signatures: vec![Default::default(); message.header().num_required_signatures.into()],
+        message,
+    };
+    bincode::serialize(&transaction).unwrap()
+}
+
+fn legacy(instructions: &[Instruction]) -> Vec<u8> {
+    let payer = Pubkey::new_unique();
+    serialize(VersionedMessage::Legacy(Message::new(
+        instructions,
+        Some(&payer),
+    )))
+}
+
+#[wasm_bindgen_test]
+fn test_builders_match_client() {
+    assert_eq!(program_id(), ID.to_string());
+
+    let instruction = wasm::validate_deadline(1800000000);
+    assert_eq!(instruction.program_id(), ID.to_string());
+    assert!(instruction.accounts().is_empty());
+    assert_eq!(
+        instruction.data(),
+        client::validate_deadline(1800000000).data
+    );
+
+    let payer = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let delegate = Pubkey::new_unique();
+    let instruction = wasm::create_session(
+        &payer.to_string(),
+        &owner.to_string(),
+        &delegate.to_string(),
+        1800000000,
+        Some(5),
+        7,
+    )
+    .unwrap();
+    assert_eq!(
+        Instruction::from(instruction.clone()),
+        client::create_session(&payer, &owner, &delegate, 1800000000, Some(5), 7)
+    );
+    let accounts = instruction.accounts();
+    assert_eq!(accounts[0].pubkey, payer.to_string());
+    assert!(accounts[0].is_signer && accounts[0].is_writable);
+    assert_eq!(
+        accounts[2].pubkey,
+        wasm::session_address(&owner.to_string(), 7).unwrap()
+    );
+
+    let ops = [
+        Op::time_le(1_000),
+        Op::slot_le(500),
+        Op::and(),
+        Op::time_le(2_000),
+        Op::or(),
+    ];
+    assert_eq!(
+        wasm::validate_expression(ops.to_vec()).data(),
+        client::validate_expression(&[
+            client::Op::TimeLe(1_000),
+            client::Op::SlotLe(500),
+            client::Op::And,
+            client::Op::TimeLe(2_000),
+            client::Op::Or,
+        ])
+        .data
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_invalid_arguments() {
+    assert!(wasm::revoke_session("not a key", &Pubkey::new_unique().to_string()).is_err());
+    assert!(wasm::validate_schedule(&[0; 20]).is_err());
+    assert!(wasm::validate_schedule(&[0; 21]).is_ok());
+}
+
+#[wasm_bindgen_test]
+fn test_decode_instruction() {
+    let decoded = decode_instruction(&wasm::validate_deadline(1800000000).data()).unwrap();
+    assert_eq!(get(&decoded, &["type"]), "validateDeadline");
+    assert_eq!(get(&decoded, &["info", "deadline"]), bigint(1800000000i64));
+    assert!(get(&decoded, &["constraints"]).is_undefined());
+
+    let versioned = wasm::versioned(
+        &wasm::validate_clock_sanity(30),
+        Some(1700000000),
+        None,
+        Some(1000),
+    );
+    let decoded = decode_instruction(&versioned.data()).unwrap();
+    assert_eq!(get(&decoded, &["type"]), "validateClockSanity");
+    assert_eq!(get(&decoded, &["info", "maxDrift"]), bigint(30u64));
+    assert_eq!(
+        get(&decoded, &["constraints", "notBefore"]),
+        bigint(1700000000i64)
+    );
+    assert!(get(&decoded, &["constraints", "minSlot"]).is_undefined());
+    assert_eq!(get(&decoded, &["constraints", "maxSlot"]), bigint(1000u64));
+
+    let session = Pubkey::new_unique().to_string();
+    let decoded = decode_instruction(
+        &wasm::create_session(&session, &session, &session, 5, None, 0)
+            .unwrap()
+            .data(),
+    )
+    .unwrap();
+    assert_eq!(get(&decoded, &["info", "delegate"]), session.as_str());
+    assert!(get(&decoded, &["info", "spendCap"]).is_null());
+
+    let decoded =
+        decode_instruction(&wasm::validate_expression(vec![Op::time_le(5)]).data()).unwrap();
+    assert_eq!(get(&decoded, &["info", "ops", "0", "op"]), "timeLe");
+    assert_eq!(
+        get(&decoded, &["info", "ops", "0", "operand"]),
+        bigint(5i64)
+    );
+
+    assert!(decode_instruction(&[]).is_err());
+    assert!(decode_instruction(&[0, 1, 2]).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_find_deadline_legacy() {
+    let other = Instruction::new_with_bytes(Pubkey::new_unique(), &[0, 1], vec![]);
+    assert_eq!(
+        find_deadline(&legacy(std::slice::from_ref(&other))).unwrap(),
+        None
+    );
+    assert_eq!(
+        find_deadline(&legacy(&[client::validate_deadline(0)])).unwrap(),
+        None
+    );
+
+    let transaction = legacy(&[
+        client::validate_deadline(1800000300),
+        other,
+        client::versioned(
+            client::validate_deadline(1800000000),
+            &client::Constraints {
+                min_slot: Some(1),
+                ..Default::default()
+            },
+        ),
+    ]);
+    assert_eq!(find_deadline(&transaction).unwrap(), Some(1800000000));
+
+    let mut malformed = client::validate_deadline(1800000000);
+    malformed.data.push(0);
+    assert!(find_deadline(&legacy(&[malformed])).is_err());
+    assert!(find_deadline(&[1, 2, 3]).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_find_deadline_v0() {
+    let payer = Pubkey::new_unique();
+    let message = v0::Message::try_compile(
+        &payer,
+        &[client::validate_deadline(1800000000)],
+        &[],
+        Hash::default(),
+    )
+    .unwrap();
+    let transaction = serialize(VersionedMessage::V0(message));
+    assert_eq!(find_deadline(&transaction).unwrap(), Some(1800000000));
+}
@@ -0,0 +1,18 @@
+//! Prints the JSON Schema of an x402 type
+//!
+//! Usage: `cargo run -p cascade-protocol-deadline-x402 --example schema <name> > x402/schema/<name>.schema.json`,
+//! where `<name>` is `payment-requirements` or `payment-payload`, or `make schema` for both
+
+use cascade_protocol_deadline_x402::schema;
+
+fn main() {
+    let schema = match std::env::args().nth(1).as_deref() {
+        Some("payment-requirements") => schema::payment_requirements(),
+        Some("payment-payload") => schema::payment_payload(),
+        _ => {
+            eprintln!("usage: schema <payment-requirements|payment-payload>");
+            std::process::exit(2);
+        }
+    };
+    println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+}
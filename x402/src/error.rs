@@ -0,0 +1,49 @@
+//! Error types
+
+use thiserror::Error;
+
+/// Errors that may be returned when decoding or validating x402 messages
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum X402Error {
+    /// The message is not valid JSON of the expected shape
+    #[error("Invalid JSON: {0}")]
+    InvalidJson(String),
+
+    /// The header or transaction is not valid base64
+    #[error("Invalid base64")]
+    InvalidBase64,
+
+    /// The payload uses an x402 version other than `X402_VERSION`
+    #[error("Unsupported x402 version {0}")]
+    UnsupportedVersion(u8),
+
+    /// `maxAmountRequired` is zero
+    #[error("Amount must be positive")]
+    ZeroAmount,
+
+    /// `maxTimeoutSeconds` is zero
+    #[error("Timeout must be positive")]
+    ZeroTimeout,
+
+    /// `extra.deadline` is not between 1 and `MAX_DEADLINE`
+    #[error("Deadline {0} is out of range")]
+    DeadlineOutOfRange(i64),
+
+    /// `extra.deadline` is set without `extra.validatorProgram`
+    #[error("Deadline requires a validator program")]
+    MissingValidatorProgram,
+
+    /// `extra.validatorProgram` is set without `extra.deadline`
+    #[error("Validator program requires a deadline")]
+    MissingDeadline,
+
+    /// The payload carries no transaction
+    #[error("Empty transaction")]
+    EmptyTransaction,
+}
+
+impl From<serde_json::Error> for X402Error {
+    fn from(error: serde_json::Error) -> Self {
+        Self::InvalidJson(error.to_string())
+    }
+}
@@ -0,0 +1,67 @@
+//! x402 types for the exact SVM scheme with the deadline validator
+//! extensions
+//!
+//! Serde types for [`PaymentRequirements`] and [`PaymentPayload`], as
+//! exchanged between resource servers, clients and facilitators, including
+//! the extensions of `docs/X402_EXACT_SVM_EXTENSION_RFC.md`:
+//!
+//! - `extra.validatorProgram` and `extra.deadline`, enforced by a
+//!   `ValidateDeadline` instruction
+//! - `extra.nonce { account, authority, value }`, for durable-nonce
+//!   transactions that outlive the ~90 second blockhash expiry
+//!
+//! Addresses deserialize only from valid base58 public keys and amounts
+//! only from decimal strings. `validate` checks the rules that span
+//! fields, and [`PaymentRequirements::from_json`] and
+//! [`PaymentPayload::from_header`] decode and validate in one step.
+//! [`schema`] exports JSON Schemas for both types.
+//!
+//! Facilitators check a payment transaction against the requirements with
+//! [`verify_layout`], and its durable nonce with [`verify_nonce`].
+//! With the `simulate` feature, smart-wallet transfers are verified by
+//! running the transaction in a `Simulator` and checking the result with
+//! `verify_simulation`.
+//!
+//! ```
+//! use cascade_protocol_deadline_x402::PaymentRequirements;
+//!
+//! let requirements = PaymentRequirements::from_json(r#"{
+//!     "scheme": "exact",
+//!     "network": "solana",
+//!     "maxAmountRequired": "1000000",
+//!     "resource": "https://api.example.com/report",
+//!     "description": "Report",
+//!     "mimeType": "application/json",
+//!     "payTo": "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin",
+//!     "maxTimeoutSeconds": 60,
+//!     "asset": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+//!     "extra": {
+//!         "feePayer": "2wmVCSfPxGPjrnMMn7rchp4uaeoTqN39mXFC2zhPdri9",
+//!         "validatorProgram": "DEADaT1auZ8JjUMWUhhPWjQqFk9HSgHBkt5KaGMVnp1H",
+//!         "deadline": 1800000000
+//!     }
+//! }"#).unwrap();
+//! assert_eq!(requirements.extra.deadline, Some(1800000000));
+//! ```
+
+pub mod error;
+pub mod nonce;
+pub mod payload;
+pub mod requirements;
+#[cfg(test)]
+mod requirements_fixture;
+pub mod schema;
+mod serde_string;
+#[cfg(feature = "simulate")]
+pub mod simulate;
+pub mod verify;
+
+pub use error::X402Error;
+pub use nonce::{verify_nonce, AccountFetcher, NonceRejection, MAX_BLOCKHASH_TIMEOUT};
+pub use payload::{ExactSvmPayload, PaymentPayload, X402_VERSION};
+pub use requirements::{
+    ExactSvmExtra, Network, NonceInfo, PaymentRequirements, Scheme, MAX_DEADLINE,
+};
+#[cfg(feature = "simulate")]
+pub use simulate::{verify_simulation, Simulation, SimulationRejection, Simulator};
+pub use verify::{verify_layout, Layout, Rejection, Transfer};
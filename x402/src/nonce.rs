@@ -0,0 +1,503 @@
+//! Durable-nonce safety checks
+//!
+//! The RFC's "Nonce-Specific Security Checks" for `extra.nonce`:
+//!
+//! - The nonce authority is not the fee payer, or a client could make the
+//!   facilitator advance, and so burn, its own nonces
+//! - The nonce account exists, is owned by the System Program, is
+//!   initialized and rent-exempt, and its authority and current value are
+//!   `extra.nonce.authority` and `extra.nonce.value`
+//! - `AdvanceNonceAccount` of that account and authority is the first
+//!   instruction, and the transaction's blockhash is the nonce value
+//!
+//! Requirements with `maxTimeoutSeconds` above [`MAX_BLOCKHASH_TIMEOUT`]
+//! need both a nonce and a deadline: the nonce keeps the transaction valid
+//! past blockhash expiry, and the deadline still bounds it.
+//!
+//! Accounts are read through [`AccountFetcher`], implemented by facilitators
+//! over RPC and by `HashMap` for tests with fabricated nonce accounts.
+
+use crate::{
+    requirements::PaymentRequirements,
+    verify::{ADVANCE_NONCE_ACCOUNT, SYSTEM_PROGRAM_ID},
+};
+use solana_account::Account;
+use solana_hash::Hash;
+use solana_message::{v0::LoadedAddresses, AccountKeys, VersionedMessage};
+use solana_nonce::{state::State, versions::Versions};
+use solana_pubkey::Pubkey;
+use solana_rent::Rent;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Longest `maxTimeoutSeconds` a recent blockhash can honor; longer
+/// timeouts need a durable nonce
+pub const MAX_BLOCKHASH_TIMEOUT: u64 = 90;
+
+/// Source of account state
+pub trait AccountFetcher {
+    /// Returns the account at `address`
+    ///
+    /// Return `None` when the account does not exist or cannot be fetched;
+    /// either way the payment is rejected.
+    fn get_account(&self, address: &Pubkey) -> Option<Account>;
+
+    /// Returns the minimum balance of a rent-exempt account with `data_len`
+    /// bytes of data
+    ///
+    /// Defaults to the mainnet rent parameters.
+    fn minimum_balance_for_rent_exemption(&self, data_len: usize) -> u64 {
+        Rent::default().minimum_balance(data_len)
+    }
+}
+
+impl AccountFetcher for HashMap<Pubkey, Account> {
+    fn get_account(&self, address: &Pubkey) -> Option<Account> {
+        self.get(address).cloned()
+    }
+}
+
+/// Reasons a durable-nonce payment is unsafe
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum NonceRejection {
+    /// `maxTimeoutSeconds` exceeds the blockhash lifetime without
+    /// `extra.nonce`
+    #[error("Timeouts above 90 seconds require a durable nonce")]
+    TimeoutRequiresNonce,
+
+    /// `maxTimeoutSeconds` exceeds the blockhash lifetime without
+    /// `extra.deadline`
+    #[error("Timeouts above 90 seconds require a deadline")]
+    TimeoutRequiresDeadline,
+
+    /// The transaction advances a nonce the requirements do not announce
+    #[error("Transaction uses a nonce that was not announced")]
+    UnannouncedNonce,
+
+    /// The fee payer is the nonce authority
+    #[error("Fee payer cannot be the nonce authority")]
+    FeePayerIsAuthority,
+
+    /// The nonce account does not exist
+    #[error("Nonce account {0} not found")]
+    AccountNotFound(Pubkey),
+
+    /// The nonce account is not owned by the System Program
+    #[error("Nonce account is owned by {0}, not the System Program")]
+    InvalidOwner(Pubkey),
+
+    /// The account data is not a nonce account
+    #[error("Invalid nonce account data")]
+    InvalidAccountData,
+
+    /// The nonce account is not initialized, or has the legacy version
+    /// that cannot be used for durable transactions
+    #[error("Nonce account is not initialized")]
+    Uninitialized,
+
+    /// The nonce account is not rent-exempt
+    #[error("Nonce account is not rent-exempt")]
+    NotRentExempt,
+
+    /// The on-chain authority differs from `extra.nonce.authority`
+    #[error("Nonce authority {0} does not match the announced authority")]
+    AuthorityMismatch(Pubkey),
+
+    /// The current nonce value differs from `extra.nonce.value`
+    #[error("Nonce value {actual} does not match the announced value {expected}")]
+    ValueMismatch {
+        /// `extra.nonce.value`
+        expected: Hash,
+        /// Current value of the nonce account
+        actual: Hash,
+    },
+
+    /// The first instruction does not advance the announced nonce
+    #[error("First instruction must advance the announced nonce")]
+    MissingAdvanceNonce,
+
+    /// The transaction's blockhash is not the nonce value
+    #[error("Transaction blockhash {0} is not the nonce value")]
+    BlockhashMismatch(Hash),
+}
+
+/// Checks the durable-nonce rules of a payment transaction
+///
+/// `loaded_addresses` are the keys a v0 message loads from address lookup
+/// tables, if any. Without `extra.nonce`, only checks that none is needed
+/// or used. Whether a required deadline is enforced is up to
+/// `verify_layout`.
+pub fn verify_nonce(
+    message: &VersionedMessage,
+    loaded_addresses: Option<&LoadedAddresses>,
+    requirements: &PaymentRequirements,
+    accounts: &impl AccountFetcher,
+) -> Result<(), NonceRejection> {
+    let extra = &requirements.extra;
+    if requirements.max_timeout_seconds > MAX_BLOCKHASH_TIMEOUT {
+        if extra.nonce.is_none() {
+            return Err(NonceRejection::TimeoutRequiresNonce);
+        }
+        if extra.deadline.is_none() {
+            return Err(NonceRejection::TimeoutRequiresDeadline);
+        }
+    }
+
+    let keys = AccountKeys::new(message.static_account_keys(), loaded_addresses);
+    let first = message.instructions().first();
+    let advance = first.filter(|ix| {
+        keys.get(usize::from(ix.program_id_index)) == Some(&SYSTEM_PROGRAM_ID)
+            && ix.data == ADVANCE_NONCE_ACCOUNT
+    });
+    let Some(nonce) = &extra.nonce else {
+        return match advance {
+            Some(_) => Err(NonceRejection::UnannouncedNonce),
+            None => Ok(()),
+        };
+    };
+
+    if nonce.authority == extra.fee_payer {
+        return Err(NonceRejection::FeePayerIsAuthority);
+    }
+
+    // Nonce account, recent blockhashes sysvar, authority
+    let account = |position: usize| {
+        let index = *advance?.accounts.get(position)?;
+        keys.get(usize::from(index))
+    };
+    if account(0) != Some(&nonce.account) || account(2) != Some(&nonce.authority) {
+        return Err(NonceRejection::MissingAdvanceNonce);
+    }
+    let blockhash = *message.recent_blockhash();
+    if blockhash != nonce.value {
+        return Err(NonceRejection::BlockhashMismatch(blockhash));
+    }
+
+    let account = accounts
+        .get_account(&nonce.account)
+        .ok_or(NonceRejection::AccountNotFound(nonce.account))?;
+    if account.owner != SYSTEM_PROGRAM_ID {
+        return Err(NonceRejection::InvalidOwner(account.owner));
+    }
+    if account.data.len() != State::size() {
+        return Err(NonceRejection::InvalidAccountData);
+    }
+    let versions: Versions =
+        bincode::deserialize(&account.data).map_err(|_| NonceRejection::InvalidAccountData)?;
+    let Versions::Current(state) = versions else {
+        return Err(NonceRejection::Uninitialized);
+    };
+    let State::Initialized(data) = *state else {
+        return Err(NonceRejection::Uninitialized);
+    };
+    if account.lamports < accounts.minimum_balance_for_rent_exemption(account.data.len()) {
+        return Err(NonceRejection::NotRentExempt);
+    }
+    if data.authority != nonce.authority {
+        return Err(NonceRejection::AuthorityMismatch(data.authority));
+    }
+    if data.blockhash() != nonce.value {
+        return Err(NonceRejection::ValueMismatch {
+            expected: nonce.value,
+            actual: data.blockhash(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        requirements::{ExactSvmExtra, NonceInfo},
+        requirements_fixture::requirements,
+    };
+    use solana_instruction::{AccountMeta, Instruction};
+    use solana_message::{v0, Message};
+    use solana_nonce::state::{Data, DurableNonce};
+    use solana_pubkey::pubkey;
+
+    const RECENT_BLOCKHASHES: Pubkey = pubkey!("SysvarRecentB1ockHashes11111111111111111111");
+
+    struct Fixture {
+        fee_payer: Pubkey,
+        nonce_account: Pubkey,
+        authority: Pubkey,
+        value: Hash,
+        requirements: PaymentRequirements,
+        accounts: HashMap<Pubkey, Account>,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let fee_payer = Pubkey::new_unique();
+            let nonce_account = Pubkey::new_unique();
+            let authority = Pubkey::new_unique();
+            let durable_nonce = DurableNonce::from_blockhash(&Hash::new_unique());
+            let value = *durable_nonce.as_hash();
+            let state = State::Initialized(Data::new(authority, durable_nonce, 5_000));
+            let accounts = HashMap::from([(nonce_account, nonce_account_with(state))]);
+            let base = requirements(
+                fee_payer,
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                1_000_000,
+            );
+            Self {
+                fee_payer,
+                nonce_account,
+                authority,
+                value,
+                requirements: PaymentRequirements {
+                    max_timeout_seconds: 300,
+                    extra: ExactSvmExtra {
+                        nonce: Some(NonceInfo {
+                            account: nonce_account,
+                            authority,
+                            value,
+                        }),
+                        ..base.extra
+                    },
+                    ..base
+                },
+                accounts,
+            }
+        }
+
+        fn advance_nonce(&self) -> Instruction {
+            advance_nonce(&self.nonce_account, &self.authority)
+        }
+
+        fn message(&self, instructions: &[Instruction], blockhash: Hash) -> VersionedMessage {
+            VersionedMessage::Legacy(Message::new_with_blockhash(
+                instructions,
+                Some(&self.fee_payer),
+                &blockhash,
+            ))
+        }
+
+        fn verify(&self, message: &VersionedMessage) -> Result<(), NonceRejection> {
+            verify_nonce(message, None, &self.requirements, &self.accounts)
+        }
+
+        fn verify_account(&self, account: Account) -> Result<(), NonceRejection> {
+            let accounts = HashMap::from([(self.nonce_account, account)]);
+            let message = self.message(&[self.advance_nonce(), transfer()], self.value);
+            verify_nonce(&message, None, &self.requirements, &accounts)
+        }
+    }
+
+    fn nonce_account_with(state: State) -> Account {
+        nonce_account_with_versions(Versions::new(state))
+    }
+
+    fn nonce_account_with_versions(versions: Versions) -> Account {
+        // Nonce accounts are allocated at the size of an initialized state
+        let mut data = bincode::serialize(&versions).unwrap();
+        data.resize(State::size(), 0);
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: SYSTEM_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    fn advance_nonce(nonce: &Pubkey, authority: &Pubkey) -> Instruction {
+        Instruction::new_with_bytes(
+            SYSTEM_PROGRAM_ID,
+            &ADVANCE_NONCE_ACCOUNT,
+            vec![
+                AccountMeta::new(*nonce, false),
+                AccountMeta::new_readonly(RECENT_BLOCKHASHES, false),
+                AccountMeta::new_readonly(*authority, true),
+            ],
+        )
+    }
+
+    fn transfer() -> Instruction {
+        Instruction::new_with_bytes(Pubkey::new_unique(), &[12], vec![])
+    }
+
+    #[test]
+    fn test_valid_nonce() {
+        let fixture = Fixture::new();
+        let message = fixture.message(&[fixture.advance_nonce(), transfer()], fixture.value);
+        assert_eq!(fixture.verify(&message), Ok(()));
+    }
+
+    #[test]
+    fn test_valid_nonce_v0() {
+        let fixture = Fixture::new();
+        let message = v0::Message::try_compile(
+            &fixture.fee_payer,
+            &[fixture.advance_nonce(), transfer()],
+            &[],
+            fixture.value,
+        )
+        .unwrap();
+        assert_eq!(fixture.verify(&VersionedMessage::V0(message)), Ok(()));
+    }
+
+    #[test]
+    fn test_timeout_requires_nonce_and_deadline() {
+        let mut fixture = Fixture::new();
+        fixture.requirements.extra.nonce = None;
+        let message = fixture.message(&[transfer()], Hash::new_unique());
+        assert_eq!(
+            fixture.verify(&message),
+            Err(NonceRejection::TimeoutRequiresNonce)
+        );
+
+        // Short timeouts need neither
+        fixture.requirements.max_timeout_seconds = MAX_BLOCKHASH_TIMEOUT;
+        fixture.requirements.extra.deadline = None;
+        assert_eq!(fixture.verify(&message), Ok(()));
+        // But must not advance a nonce nobody announced
+        let message = fixture.message(&[fixture.advance_nonce(), transfer()], fixture.value);
+        assert_eq!(
+            fixture.verify(&message),
+            Err(NonceRejection::UnannouncedNonce)
+        );
+
+        let mut fixture = Fixture::new();
+        fixture.requirements.extra.deadline = None;
+        let message = fixture.message(&[fixture.advance_nonce(), transfer()], fixture.value);
+        assert_eq!(
+            fixture.verify(&message),
+            Err(NonceRejection::TimeoutRequiresDeadline)
+        );
+    }
+
+    #[test]
+    fn test_fee_payer_is_authority() {
+        let mut fixture = Fixture::new();
+        fixture.requirements.extra.fee_payer = fixture.authority;
+        fixture.fee_payer = fixture.authority;
+        let message = fixture.message(&[fixture.advance_nonce(), transfer()], fixture.value);
+        assert_eq!(
+            fixture.verify(&message),
+            Err(NonceRejection::FeePayerIsAuthority)
+        );
+    }
+
+    #[test]
+    fn test_advance_nonce_instruction() {
+        let fixture = Fixture::new();
+        let cases = [
+            // Not first
+            vec![transfer(), fixture.advance_nonce()],
+            // Missing
+            vec![transfer()],
+            // Another nonce account
+            vec![
+                advance_nonce(&Pubkey::new_unique(), &fixture.authority),
+                transfer(),
+            ],
+            // Another authority
+            vec![
+                advance_nonce(&fixture.nonce_account, &Pubkey::new_unique()),
+                transfer(),
+            ],
+        ];
+        for instructions in cases {
+            let message = fixture.message(&instructions, fixture.value);
+            assert_eq!(
+                fixture.verify(&message),
+                Err(NonceRejection::MissingAdvanceNonce)
+            );
+        }
+
+        let blockhash = Hash::new_unique();
+        let message = fixture.message(&[fixture.advance_nonce(), transfer()], blockhash);
+        assert_eq!(
+            fixture.verify(&message),
+            Err(NonceRejection::BlockhashMismatch(blockhash))
+        );
+    }
+
+    #[test]
+    fn test_nonce_account() {
+        let fixture = Fixture::new();
+        let valid = fixture.accounts[&fixture.nonce_account].clone();
+
+        let message = fixture.message(&[fixture.advance_nonce(), transfer()], fixture.value);
+        assert_eq!(
+            verify_nonce(&message, None, &fixture.requirements, &HashMap::new()),
+            Err(NonceRejection::AccountNotFound(fixture.nonce_account))
+        );
+
+        let owner = Pubkey::new_unique();
+        let account = Account {
+            owner,
+            ..valid.clone()
+        };
+        assert_eq!(
+            fixture.verify_account(account),
+            Err(NonceRejection::InvalidOwner(owner))
+        );
+
+        let account = Account {
+            data: vec![0; 12],
+            ..valid.clone()
+        };
+        assert_eq!(
+            fixture.verify_account(account),
+            Err(NonceRejection::InvalidAccountData)
+        );
+
+        let account = Account {
+            data: vec![0xFF; State::size()],
+            ..valid.clone()
+        };
+        assert_eq!(
+            fixture.verify_account(account),
+            Err(NonceRejection::InvalidAccountData)
+        );
+
+        assert_eq!(
+            fixture.verify_account(nonce_account_with(State::Uninitialized)),
+            Err(NonceRejection::Uninitialized)
+        );
+
+        let State::Initialized(data) = bincode::deserialize::<Versions>(&valid.data)
+            .unwrap()
+            .state()
+            .clone()
+        else {
+            unreachable!()
+        };
+        let legacy = Versions::Legacy(Box::new(State::Initialized(data.clone())));
+        assert_eq!(
+            fixture.verify_account(nonce_account_with_versions(legacy)),
+            Err(NonceRejection::Uninitialized)
+        );
+
+        let account = Account {
+            lamports: valid.lamports - 1,
+            ..valid.clone()
+        };
+        assert_eq!(
+            fixture.verify_account(account),
+            Err(NonceRejection::NotRentExempt)
+        );
+
+        let other = Pubkey::new_unique();
+        let state = State::Initialized(Data::new(other, data.durable_nonce, 5_000));
+        assert_eq!(
+            fixture.verify_account(nonce_account_with(state)),
+            Err(NonceRejection::AuthorityMismatch(other))
+        );
+
+        let advanced = DurableNonce::from_blockhash(&Hash::new_unique());
+        let state = State::Initialized(Data::new(fixture.authority, advanced, 5_000));
+        assert_eq!(
+            fixture.verify_account(nonce_account_with(state)),
+            Err(NonceRejection::ValueMismatch {
+                expected: fixture.value,
+                actual: *advanced.as_hash(),
+            })
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! Payment payload, as sent by a client in the `X-PAYMENT` header
+
+use crate::{
+    error::X402Error,
+    requirements::{Network, Scheme},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Supported x402 protocol version
+pub const X402_VERSION: u8 = 1;
+
+/// A signed payment
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PaymentPayload {
+    /// x402 protocol version, [`X402_VERSION`]
+    pub x402_version: u8,
+    /// Payment scheme
+    pub scheme: Scheme,
+    /// Cluster the payment settles on
+    pub network: Network,
+    /// Scheme-specific payload
+    pub payload: ExactSvmPayload,
+}
+
+/// `payload` of the exact SVM scheme
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ExactSvmPayload {
+    /// Base64 wire-format transaction, signed by the client and awaiting
+    /// the fee payer's signature
+    #[schemars(regex(pattern = r"^[A-Za-z0-9+/]+={0,2}$"))]
+    pub transaction: String,
+}
+
+impl PaymentPayload {
+    /// Decodes and validates an `X-PAYMENT` header, base64 of the JSON
+    /// payload
+    pub fn from_header(header: &str) -> Result<Self, X402Error> {
+        let json = STANDARD
+            .decode(header.trim())
+            .map_err(|_| X402Error::InvalidBase64)?;
+        let payload: Self = serde_json::from_slice(&json)?;
+        payload.validate()?;
+        Ok(payload)
+    }
+
+    /// Encodes the payload as an `X-PAYMENT` header
+    pub fn to_header(&self) -> String {
+        // Serializing plain structs to JSON cannot fail
+        STANDARD.encode(serde_json::to_vec(self).unwrap())
+    }
+
+    /// Checks the version and that the transaction decodes
+    pub fn validate(&self) -> Result<(), X402Error> {
+        if self.x402_version != X402_VERSION {
+            return Err(X402Error::UnsupportedVersion(self.x402_version));
+        }
+        self.payload.transaction_bytes().map(|_| ())
+    }
+}
+
+impl ExactSvmPayload {
+    /// Creates a payload from a serialized transaction
+    pub fn new(transaction: &[u8]) -> Self {
+        Self {
+            transaction: STANDARD.encode(transaction),
+        }
+    }
+
+    /// Returns the serialized transaction
+    pub fn transaction_bytes(&self) -> Result<Vec<u8>, X402Error> {
+        let transaction = STANDARD
+            .decode(&self.transaction)
+            .map_err(|_| X402Error::InvalidBase64)?;
+        if transaction.is_empty() {
+            return Err(X402Error::EmptyTransaction);
+        }
+        Ok(transaction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn payload() -> PaymentPayload {
+        PaymentPayload {
+            x402_version: X402_VERSION,
+            scheme: Scheme::Exact,
+            network: Network::SolanaDevnet,
+            payload: ExactSvmPayload::new(&[1, 2, 3]),
+        }
+    }
+
+    #[test]
+    fn test_header_round_trip() {
+        let payload = payload();
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            json!({
+                "x402Version": 1,
+                "scheme": "exact",
+                "network": "solana-devnet",
+                "payload": { "transaction": "AQID" },
+            })
+        );
+        let header = payload.to_header();
+        assert_eq!(PaymentPayload::from_header(&header), Ok(payload.clone()));
+        assert_eq!(
+            PaymentPayload::from_header(&format!(" {header}\r\n")),
+            Ok(payload.clone())
+        );
+        assert_eq!(payload.payload.transaction_bytes(), Ok(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        assert_eq!(
+            PaymentPayload::from_header("not base64!"),
+            Err(X402Error::InvalidBase64)
+        );
+        assert!(matches!(
+            PaymentPayload::from_header(&STANDARD.encode("{}")),
+            Err(X402Error::InvalidJson(_))
+        ));
+
+        let mut payload = payload();
+        payload.x402_version = 2;
+        assert_eq!(
+            PaymentPayload::from_header(&payload.to_header()),
+            Err(X402Error::UnsupportedVersion(2))
+        );
+
+        let mut payload = self::payload();
+        payload.payload.transaction = "AQID=".to_string();
+        assert_eq!(payload.validate(), Err(X402Error::InvalidBase64));
+        payload.payload.transaction = String::new();
+        assert_eq!(payload.validate(), Err(X402Error::EmptyTransaction));
+
+        let mut value = serde_json::to_value(self::payload()).unwrap();
+        value["payload"]["signature"] = json!("extra");
+        assert!(serde_json::from_value::<PaymentPayload>(value).is_err());
+    }
+}
@@ -0,0 +1,287 @@
+//! Payment requirements, as announced by a resource server in its
+//! `402 Payment Required` response
+
+use crate::{error::X402Error, serde_string};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use solana_hash::Hash;
+use solana_pubkey::Pubkey;
+
+/// Latest accepted `extra.deadline`, JavaScript's `Number.MAX_SAFE_INTEGER`
+///
+/// Deadlines are JSON numbers. Larger values do not survive a round trip
+/// through JavaScript clients, and no real deadline comes close.
+pub const MAX_DEADLINE: i64 = (1 << 53) - 1;
+
+/// Payment scheme
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum Scheme {
+    /// Transfer of exactly `maxAmountRequired`
+    Exact,
+}
+
+/// Solana cluster
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum Network {
+    /// Mainnet beta
+    Solana,
+    /// Devnet
+    SolanaDevnet,
+}
+
+/// Requirements a payment must meet
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PaymentRequirements {
+    /// Payment scheme
+    pub scheme: Scheme,
+    /// Cluster the payment settles on
+    pub network: Network,
+    /// Amount to transfer, in base units of `asset`
+    #[serde(with = "serde_string::amount")]
+    #[schemars(with = "String", regex(pattern = r"^(0|[1-9][0-9]*)$"))]
+    pub max_amount_required: u64,
+    /// URL of the paid resource
+    pub resource: String,
+    /// Description of the paid resource
+    pub description: String,
+    /// MIME type of the paid resource
+    pub mime_type: String,
+    /// JSON Schema of the resource's response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Value>,
+    /// Wallet that receives the payment; the transfer destination is its
+    /// associated token account for `asset`
+    #[serde(with = "serde_string::display")]
+    #[schemars(with = "String", regex(pattern = BASE58_PATTERN))]
+    pub pay_to: Pubkey,
+    /// Time the client has to submit the payment
+    pub max_timeout_seconds: u64,
+    /// Mint of the token to pay with
+    #[serde(with = "serde_string::display")]
+    #[schemars(with = "String", regex(pattern = BASE58_PATTERN))]
+    pub asset: Pubkey,
+    /// Scheme-specific fields
+    pub extra: ExactSvmExtra,
+}
+
+/// `extra` of the exact SVM scheme
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ExactSvmExtra {
+    /// Facilitator account that pays the transaction fees
+    #[serde(with = "serde_string::display")]
+    #[schemars(with = "String", regex(pattern = BASE58_PATTERN))]
+    pub fee_payer: Pubkey,
+    /// Deadline validator program, set together with `deadline`
+    #[serde(
+        default,
+        with = "serde_string::option_display",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<String>", regex(pattern = BASE58_PATTERN))]
+    pub validator_program: Option<Pubkey>,
+    /// Unix timestamp after which the payment must fail, set together with
+    /// `validatorProgram`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 1, max = MAX_DEADLINE))]
+    pub deadline: Option<i64>,
+    /// Durable nonce the transaction must use instead of a recent blockhash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<NonceInfo>,
+}
+
+/// Durable nonce of `extra.nonce`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct NonceInfo {
+    /// Nonce account
+    #[serde(with = "serde_string::display")]
+    #[schemars(with = "String", regex(pattern = BASE58_PATTERN))]
+    pub account: Pubkey,
+    /// Nonce authority, the client, never the fee payer
+    #[serde(with = "serde_string::display")]
+    #[schemars(with = "String", regex(pattern = BASE58_PATTERN))]
+    pub authority: Pubkey,
+    /// Current nonce value, used as the transaction's blockhash
+    #[serde(with = "serde_string::display")]
+    #[schemars(with = "String", regex(pattern = BASE58_PATTERN))]
+    pub value: Hash,
+}
+
+/// Base58 string of 32 to 44 characters, the length range of 32-byte
+/// values; exact validation happens on decoding
+const BASE58_PATTERN: &str = "^[1-9A-HJ-NP-Za-km-z]{32,44}$";
+
+impl PaymentRequirements {
+    /// Deserializes and validates requirements
+    pub fn from_json(json: &str) -> Result<Self, X402Error> {
+        let requirements: Self = serde_json::from_str(json)?;
+        requirements.validate()?;
+        Ok(requirements)
+    }
+
+    /// Checks the rules that span fields
+    ///
+    /// Field formats, such as public keys, are checked on deserialization.
+    pub fn validate(&self) -> Result<(), X402Error> {
+        if self.max_amount_required == 0 {
+            return Err(X402Error::ZeroAmount);
+        }
+        if self.max_timeout_seconds == 0 {
+            return Err(X402Error::ZeroTimeout);
+        }
+        self.extra.validate()
+    }
+}
+
+impl ExactSvmExtra {
+    /// Checks the deadline range and that deadline and validator program
+    /// are set together
+    pub fn validate(&self) -> Result<(), X402Error> {
+        match (self.validator_program, self.deadline) {
+            (None, None) => Ok(()),
+            (None, Some(_)) => Err(X402Error::MissingValidatorProgram),
+            (Some(_), None) => Err(X402Error::MissingDeadline),
+            // Deadline 0 would never expire
+            (Some(_), Some(deadline)) if !(1..=MAX_DEADLINE).contains(&deadline) => {
+                Err(X402Error::DeadlineOutOfRange(deadline))
+            }
+            (Some(_), Some(_)) => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const FEE_PAYER: &str = "2wmVCSfPxGPjrnMMn7rchp4uaeoTqN39mXFC2zhPdri9";
+    const VALIDATOR: &str = "DEADaT1auZ8JjUMWUhhPWjQqFk9HSgHBkt5KaGMVnp1H";
+    const NONCE_ACCOUNT: &str = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
+    const NONCE_AUTHORITY: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+    const NONCE_VALUE: &str = "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi";
+
+    fn sample() -> Value {
+        json!({
+            "scheme": "exact",
+            "network": "solana",
+            "maxAmountRequired": "1000000",
+            "resource": "https://api.example.com/report",
+            "description": "Report",
+            "mimeType": "application/json",
+            "payTo": "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin",
+            "maxTimeoutSeconds": 300,
+            "asset": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "extra": {
+                "feePayer": FEE_PAYER,
+                "validatorProgram": VALIDATOR,
+                "deadline": 1800000000,
+                "nonce": {
+                    "account": NONCE_ACCOUNT,
+                    "authority": NONCE_AUTHORITY,
+                    "value": NONCE_VALUE,
+                },
+            },
+        })
+    }
+
+    fn parse(value: Value) -> Result<PaymentRequirements, X402Error> {
+        let requirements: PaymentRequirements = serde_json::from_value(value)?;
+        requirements.validate()?;
+        Ok(requirements)
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let requirements = parse(sample()).unwrap();
+        assert_eq!(requirements.max_amount_required, 1_000_000);
+        assert_eq!(requirements.network, Network::Solana);
+        assert_eq!(
+            requirements.extra.validator_program,
+            Some(VALIDATOR.parse().unwrap())
+        );
+        assert_eq!(requirements.extra.deadline, Some(1800000000));
+        let nonce = requirements.extra.nonce.as_ref().unwrap();
+        assert_eq!(nonce.authority, NONCE_AUTHORITY.parse().unwrap());
+        assert_eq!(nonce.value, NONCE_VALUE.parse().unwrap());
+        assert_eq!(serde_json::to_value(&requirements).unwrap(), sample());
+    }
+
+    #[test]
+    fn test_without_extensions() {
+        let mut value = sample();
+        value["extra"] = json!({ "feePayer": FEE_PAYER });
+        value["network"] = json!("solana-devnet");
+        let requirements = parse(value.clone()).unwrap();
+        assert_eq!(requirements.extra.validator_program, None);
+        assert_eq!(requirements.extra.deadline, None);
+        assert_eq!(requirements.extra.nonce, None);
+        assert_eq!(serde_json::to_value(&requirements).unwrap(), value);
+    }
+
+    #[test]
+    fn test_invalid_fields() {
+        let cases = [
+            ("/payTo", json!("not a key")),
+            ("/asset", json!("")),
+            ("/extra/feePayer", json!(VALIDATOR.to_string() + "1")),
+            ("/extra/validatorProgram", json!(42)),
+            ("/extra/nonce/value", json!("0OIl")),
+            ("/maxAmountRequired", json!(1000000)),
+            ("/maxAmountRequired", json!("+1000000")),
+            ("/maxAmountRequired", json!("01000000")),
+            ("/maxAmountRequired", json!("1e6")),
+            ("/maxAmountRequired", json!("18446744073709551616")),
+            ("/extra/deadline", json!("1800000000")),
+            ("/scheme", json!("upto")),
+            ("/network", json!("solana-mainnet")),
+            ("/extra/unknown", json!(1)),
+            ("/extra/nonce/unknown", json!(1)),
+        ];
+        for (pointer, invalid) in cases {
+            let mut value = sample();
+            let (parent, key) = pointer.rsplit_once('/').unwrap();
+            value.pointer_mut(parent).unwrap()[key] = invalid;
+            assert!(
+                matches!(parse(value), Err(X402Error::InvalidJson(_))),
+                "{pointer}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut value = sample();
+        value["maxAmountRequired"] = json!("0");
+        assert_eq!(parse(value), Err(X402Error::ZeroAmount));
+
+        let mut value = sample();
+        value["maxTimeoutSeconds"] = json!(0);
+        assert_eq!(parse(value), Err(X402Error::ZeroTimeout));
+
+        for deadline in [0, -1, MAX_DEADLINE + 1, i64::MAX] {
+            let mut value = sample();
+            value["extra"]["deadline"] = json!(deadline);
+            assert_eq!(parse(value), Err(X402Error::DeadlineOutOfRange(deadline)));
+        }
+        let mut value = sample();
+        value["extra"]["deadline"] = json!(MAX_DEADLINE);
+        assert!(parse(value).is_ok());
+
+        let mut value = sample();
+        value["extra"]
+            .as_object_mut()
+            .unwrap()
+            .remove("validatorProgram");
+        assert_eq!(parse(value), Err(X402Error::MissingValidatorProgram));
+
+        let mut value = sample();
+        value["extra"].as_object_mut().unwrap().remove("deadline");
+        assert_eq!(parse(value), Err(X402Error::MissingDeadline));
+    }
+}
@@ -0,0 +1,40 @@
+//! Payment requirements shared by the unit and integration tests
+//!
+//! The integration tests include this file with `#[path]`, so it names the
+//! crate's types through its parent module, which imports them in both.
+
+use super::{ExactSvmExtra, Network, PaymentRequirements, Scheme};
+use cascade_protocol_deadline_validator_client::ID as VALIDATOR_ID;
+use solana_pubkey::Pubkey;
+
+/// Deadline of the requirements, Jan 15, 2027
+pub const DEADLINE: i64 = 1800000000;
+
+/// Returns validated requirements to pay `max_amount_required` of `asset`
+/// to `pay_to` within 60 seconds, with `DEADLINE` enforced by the deadline
+/// validator
+pub fn requirements(
+    fee_payer: Pubkey,
+    pay_to: Pubkey,
+    asset: Pubkey,
+    max_amount_required: u64,
+) -> PaymentRequirements {
+    PaymentRequirements {
+        scheme: Scheme::Exact,
+        network: Network::Solana,
+        max_amount_required,
+        resource: "https://api.example.com/report".to_string(),
+        description: "Report".to_string(),
+        mime_type: "application/json".to_string(),
+        output_schema: None,
+        pay_to,
+        max_timeout_seconds: 60,
+        asset,
+        extra: ExactSvmExtra {
+            fee_payer,
+            validator_program: Some(VALIDATOR_ID),
+            deadline: Some(DEADLINE),
+            nonce: None,
+        },
+    }
+}
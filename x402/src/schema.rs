@@ -0,0 +1,64 @@
+//! JSON Schemas of the x402 types
+//!
+//! The schemas describe the wire format, including the base58 and amount
+//! string formats and the deadline range. Rules that span fields, such as
+//! `deadline` requiring `validatorProgram`, are only checked by `validate`.
+//! The committed `x402/schema/*.schema.json` files are checked against
+//! these functions.
+//!
+//! Regenerate the committed schemas with `make schema`.
+
+use crate::{PaymentPayload, PaymentRequirements};
+use serde_json::Value;
+
+/// Returns the JSON Schema of [`PaymentRequirements`]
+pub fn payment_requirements() -> Value {
+    schemars::schema_for!(PaymentRequirements).to_value()
+}
+
+/// Returns the JSON Schema of [`PaymentPayload`]
+pub fn payment_payload() -> Value {
+    schemars::schema_for!(PaymentPayload).to_value()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_schema_describes_extensions() {
+        let schema = payment_requirements();
+        let extra = &schema["$defs"]["ExactSvmExtra"];
+        assert_eq!(extra["required"], json!(["feePayer"]));
+        assert_eq!(extra["properties"]["deadline"]["minimum"], 1);
+        assert!(extra["properties"]["validatorProgram"]["pattern"].is_string());
+        assert_eq!(
+            schema["$defs"]["NonceInfo"]["required"],
+            json!(["account", "authority", "value"])
+        );
+        assert_eq!(schema["additionalProperties"], false);
+    }
+
+    #[test]
+    fn test_committed_schemas_are_up_to_date() {
+        for (committed, generated, name) in [
+            (
+                include_str!("../schema/payment-requirements.schema.json"),
+                payment_requirements(),
+                "payment-requirements",
+            ),
+            (
+                include_str!("../schema/payment-payload.schema.json"),
+                payment_payload(),
+                "payment-payload",
+            ),
+        ] {
+            let generated = serde_json::to_string_pretty(&generated).unwrap() + "\n";
+            assert!(
+                committed == generated,
+                "x402/schema/{name}.schema.json is out of date, regenerate it with `make schema`"
+            );
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! Serde helpers for values encoded as JSON strings
+
+use serde::{de, Deserialize, Deserializer, Serializer};
+use std::{borrow::Cow, fmt::Display, str::FromStr};
+
+/// Serializes with `Display` and deserializes with `FromStr`, for base58
+/// public keys and hashes
+pub mod display {
+    use super::*;
+
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        let value = Cow::<str>::deserialize(deserializer)?;
+        value.parse().map_err(de::Error::custom)
+    }
+}
+
+/// [`display`] for optional values, absent when `None`
+pub mod option_display {
+    use super::*;
+
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &Option<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => serializer.collect_str(value),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        Option::<Cow<str>>::deserialize(deserializer)?
+            .map(|value| value.parse().map_err(de::Error::custom))
+            .transpose()
+    }
+}
+
+/// Amounts in base units as decimal strings, which survive JavaScript's
+/// 53-bit numbers. Only plain digits are accepted, without sign, spaces or
+/// leading zeros.
+pub mod amount {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(amount: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(amount)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        let value = Cow::<str>::deserialize(deserializer)?;
+        let canonical = value.bytes().all(|byte| byte.is_ascii_digit())
+            && (value.len() == 1 || !value.starts_with('0'));
+        if !canonical {
+            return Err(de::Error::custom(format!("invalid amount {value:?}")));
+        }
+        value.parse().map_err(de::Error::custom)
+    }
+}
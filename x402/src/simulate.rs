@@ -0,0 +1,833 @@
+//! Simulation-based verification of smart-wallet transfers
+//!
+//! When the last instruction is not a token program, [`verify_layout`]
+//! returns [`Transfer::Cpi`] and the RFC's "Simulation Verification"
+//! applies: the transaction must make exactly one `TransferChecked` CPI,
+//! of `maxAmountRequired` of `asset` to the `payTo` associated token
+//! account, through SPL Token or Token-2022.
+//!
+//! [`Simulator`] runs the transaction in an embedded SVM, Mollusk, against
+//! account snapshots from an [`AccountFetcher`], so it works offline. It
+//! records every invocation, including CPIs, and the token balances before
+//! and after. [`verify_simulation`] then applies the RFC's rules, and
+//! rejects the payment when any instruction fails, including the deadline
+//! validator's.
+//!
+//! SPL Token, Token-2022 and the Associated Token Account program are
+//! built in. Add the deadline validator and the smart-wallet programs with
+//! [`Simulator::add_program`], e.g. from `solana program dump`.
+//!
+//! Like the runtime, the simulator does not execute compute budget
+//! instructions, and it skips `AdvanceNonceAccount`, which `verify_nonce`
+//! checks. Signatures are not verified. A simulation only shows what the
+//! transaction does against the snapshots: accounts can change before it
+//! lands.
+//!
+//! [`verify_layout`]: crate::verify::verify_layout
+//! [`Transfer::Cpi`]: crate::verify::Transfer::Cpi
+
+use crate::{
+    nonce::AccountFetcher,
+    requirements::PaymentRequirements,
+    verify::{
+        associated_token_address, Layout, ADVANCE_NONCE_ACCOUNT, COMPUTE_BUDGET_PROGRAM_ID,
+        SYSTEM_PROGRAM_ID, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID, TRANSFER_CHECKED,
+    },
+};
+use mollusk_svm::{
+    program::{create_program_account_loader_v3, keyed_account_for_system_program, loader_keys},
+    InvocationInspectCallback, Mollusk,
+};
+use mollusk_svm_programs_token::{associated_token, token, token2022};
+use solana_account::Account;
+use solana_instruction::{error::InstructionError, AccountMeta, Instruction};
+use solana_message::{v0::LoadedAddresses, AccountKeys, VersionedMessage};
+use solana_program_runtime::invoke_context::InvokeContext;
+use solana_pubkey::Pubkey;
+use solana_transaction_context::InstructionAccount;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use thiserror::Error;
+
+/// Token `Transfer` discriminator, which skips the mint and decimals checks
+const TRANSFER: u8 = 3;
+
+/// Size of a token account without extensions
+const TOKEN_ACCOUNT_LEN: usize = 165;
+
+/// Token-2022 account type of token accounts with extensions
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
+/// Reasons a simulated transaction does not make the required payment
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum SimulationRejection {
+    /// The deadline validator's instruction failed, e.g. with
+    /// `DeadlineExpired`
+    #[error("Deadline validation failed: {0}")]
+    DeadlineFailed(InstructionError),
+
+    /// Another instruction failed
+    #[error("Instruction {index} failed: {error}")]
+    InstructionFailed {
+        /// Index of the instruction
+        index: usize,
+        /// Error of the instruction
+        error: InstructionError,
+    },
+
+    /// The transaction used more compute units than it requested
+    #[error("Simulation consumed {0} compute units, more than the limit")]
+    ComputeUnitLimitExceeded(u64),
+
+    /// The transaction does not make exactly one token transfer CPI
+    #[error("Expected exactly one TransferChecked CPI, found {0} transfers")]
+    TransferCount(usize),
+
+    /// The transfer CPI is not a well-formed `TransferChecked`
+    #[error("Transfer CPI is not a TransferChecked")]
+    InvalidTransfer,
+
+    /// The transfer amount differs from `maxAmountRequired`
+    #[error("Amount {actual} does not match the required amount {expected}")]
+    AmountMismatch {
+        /// `maxAmountRequired`
+        expected: u64,
+        /// Amount of the transfer
+        actual: u64,
+    },
+
+    /// The transfer mint is not `asset`
+    #[error("Mint {0} is not the required asset")]
+    MintMismatch(Pubkey),
+
+    /// The transfer destination is not the `payTo` associated token account
+    #[error("Destination {0} is not the payTo token account")]
+    DestinationMismatch(Pubkey),
+
+    /// The destination balance did not grow by `maxAmountRequired`, e.g.
+    /// because of a Token-2022 transfer fee
+    #[error("Destination balance changed by {actual}, expected {expected}")]
+    BalanceMismatch {
+        /// `maxAmountRequired`
+        expected: u64,
+        /// Change of the destination balance
+        actual: i128,
+    },
+}
+
+/// An instruction executed during the simulation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Invocation {
+    /// Index of the top-level instruction
+    pub index: usize,
+    /// 1 for the top-level instruction, 2 and above for CPIs
+    pub stack_height: usize,
+    /// Invoked program
+    pub program_id: Pubkey,
+    /// Account keys, in instruction order
+    pub accounts: Vec<Pubkey>,
+    /// Instruction data
+    pub data: Vec<u8>,
+}
+
+/// A token account's balance before and after the simulation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenBalance {
+    /// Token account
+    pub address: Pubkey,
+    /// SPL Token or Token-2022
+    pub program_id: Pubkey,
+    /// Mint of the account
+    pub mint: Pubkey,
+    /// Owner of the account
+    pub owner: Pubkey,
+    /// Balance before, 0 for accounts created by the transaction
+    pub pre_amount: u64,
+    /// Balance after, 0 for accounts closed by the transaction
+    pub post_amount: u64,
+}
+
+impl TokenBalance {
+    /// Returns the change of the balance
+    pub fn delta(&self) -> i128 {
+        i128::from(self.post_amount) - i128::from(self.pre_amount)
+    }
+}
+
+/// The first failed instruction of a simulation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Failure {
+    /// Index of the instruction
+    pub index: usize,
+    /// Program of the instruction
+    pub program_id: Pubkey,
+    /// Error of the instruction
+    pub error: InstructionError,
+}
+
+/// The outcome of a simulated transaction
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Simulation {
+    /// The failed instruction, if any; later instructions are not run
+    pub failure: Option<Failure>,
+    /// Compute units consumed by all instructions
+    pub compute_units_consumed: u64,
+    /// Instructions executed, in order, including CPIs
+    pub invocations: Vec<Invocation>,
+    /// Token accounts of the transaction
+    pub token_balances: Vec<TokenBalance>,
+}
+
+impl Simulation {
+    /// Returns the instructions invoked through CPI
+    pub fn inner_instructions(&self) -> impl Iterator<Item = &Invocation> {
+        self.invocations
+            .iter()
+            .filter(|invocation| invocation.stack_height > 1)
+    }
+}
+
+/// Embedded SVM for payment simulations
+pub struct Simulator {
+    mollusk: Mollusk,
+    /// Executable accounts of the loaded programs
+    programs: HashMap<Pubkey, Account>,
+    /// Invocations of the last processed instruction
+    trace: Rc<RefCell<Vec<Invocation>>>,
+}
+
+impl Default for Simulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Simulator {
+    /// Creates a simulator with SPL Token, Token-2022 and the Associated
+    /// Token Account program
+    pub fn new() -> Self {
+        let trace = Rc::default();
+        let mut mollusk = Mollusk {
+            invocation_inspect_callback: Box::new(TraceRecorder(Rc::clone(&trace))),
+            ..Mollusk::default()
+        };
+        token::add_program(&mut mollusk);
+        token2022::add_program(&mut mollusk);
+        associated_token::add_program(&mut mollusk);
+        let programs = HashMap::from([
+            keyed_account_for_system_program(),
+            token::keyed_account(),
+            token2022::keyed_account(),
+            associated_token::keyed_account(),
+        ]);
+        Self {
+            mollusk,
+            programs,
+            trace,
+        }
+    }
+
+    /// Adds a program, such as the deadline validator or a smart wallet,
+    /// from its ELF
+    pub fn add_program(&mut self, program_id: &Pubkey, elf: &[u8]) {
+        self.mollusk
+            .add_program_with_elf_and_loader(program_id, elf, &loader_keys::LOADER_V3);
+        self.programs
+            .insert(*program_id, create_program_account_loader_v3(program_id));
+    }
+
+    /// Sets the slot and Unix timestamp of the Clock sysvar
+    pub fn set_clock(&mut self, slot: u64, unix_timestamp: i64) {
+        self.mollusk.warp_to_slot(slot);
+        self.mollusk.sysvars.clock.unix_timestamp = unix_timestamp;
+    }
+
+    /// Simulates a transaction against the accounts of `accounts`
+    ///
+    /// `loaded_addresses` are the keys a v0 message loads from address
+    /// lookup tables, if any. Accounts that `accounts` does not return are
+    /// simulated as empty system accounts.
+    pub fn simulate(
+        &self,
+        message: &VersionedMessage,
+        loaded_addresses: Option<&LoadedAddresses>,
+        accounts: &impl AccountFetcher,
+    ) -> Simulation {
+        let keys = AccountKeys::new(message.static_account_keys(), loaded_addresses);
+        let clock = self.mollusk.sysvars.keyed_account_for_clock_sysvar();
+        let snapshot: Vec<_> = keys
+            .iter()
+            .map(|key| {
+                let account = if *key == clock.0 {
+                    clock.1.clone()
+                } else if let Some(program) = self.programs.get(key) {
+                    program.clone()
+                } else {
+                    accounts.get_account(key).unwrap_or_default()
+                };
+                (*key, account)
+            })
+            .collect();
+
+        let mut simulation = Simulation::default();
+        let mut resulting_accounts = snapshot.clone();
+        for (index, compiled) in message.instructions().iter().enumerate() {
+            let program_id = keys.get(usize::from(compiled.program_id_index)).copied();
+            let skipped = program_id == Some(COMPUTE_BUDGET_PROGRAM_ID)
+                || (program_id == Some(SYSTEM_PROGRAM_ID)
+                    && compiled.data == ADVANCE_NONCE_ACCOUNT);
+            if skipped {
+                continue;
+            }
+            let metas: Option<Vec<_>> = compiled
+                .accounts
+                .iter()
+                .map(|&account| {
+                    let account = usize::from(account);
+                    Some(AccountMeta {
+                        pubkey: *keys.get(account)?,
+                        is_signer: message.is_signer(account),
+                        is_writable: message.is_maybe_writable(account, None),
+                    })
+                })
+                .collect();
+            let (Some(program_id), Some(metas)) = (program_id, metas) else {
+                simulation.failure = Some(Failure {
+                    index,
+                    program_id: program_id.unwrap_or_default(),
+                    error: InstructionError::MissingAccount,
+                });
+                break;
+            };
+            // Mollusk panics on programs it has not loaded
+            if self
+                .mollusk
+                .program_cache
+                .load_program(&program_id)
+                .is_none()
+            {
+                simulation.failure = Some(Failure {
+                    index,
+                    program_id,
+                    error: InstructionError::UnsupportedProgramId,
+                });
+                break;
+            }
+
+            let instruction = Instruction {
+                program_id,
+                accounts: metas,
+                data: compiled.data.clone(),
+            };
+            let result = self
+                .mollusk
+                .process_instruction(&instruction, &resulting_accounts);
+            simulation.compute_units_consumed += result.compute_units_consumed;
+            simulation
+                .invocations
+                .extend(
+                    self.trace
+                        .borrow_mut()
+                        .drain(..)
+                        .map(|invocation| Invocation {
+                            index,
+                            ..invocation
+                        }),
+                );
+            if let Err(error) = result.raw_result {
+                simulation.failure = Some(Failure {
+                    index,
+                    program_id,
+                    error,
+                });
+                break;
+            }
+            resulting_accounts = result.resulting_accounts;
+        }
+
+        simulation.token_balances = snapshot
+            .iter()
+            .zip(&resulting_accounts)
+            .filter_map(|((address, pre), (_, post))| {
+                let pre = token_account(pre);
+                let post = token_account(post);
+                let (program_id, mint, owner, _) = post.or(pre)?;
+                Some(TokenBalance {
+                    address: *address,
+                    program_id,
+                    mint,
+                    owner,
+                    pre_amount: pre.map_or(0, |(.., amount)| amount),
+                    post_amount: post.map_or(0, |(.., amount)| amount),
+                })
+            })
+            .collect();
+        simulation
+    }
+}
+
+/// Records the instruction trace of each instruction Mollusk processes
+struct TraceRecorder(Rc<RefCell<Vec<Invocation>>>);
+
+impl InvocationInspectCallback for TraceRecorder {
+    fn before_invocation(&self, _: &Pubkey, _: &[u8], _: &[InstructionAccount], _: &InvokeContext) {
+    }
+
+    fn after_invocation(&self, invoke_context: &InvokeContext) {
+        let transaction_context = &invoke_context.transaction_context;
+        let mut trace = self.0.borrow_mut();
+        for index in 0..transaction_context.get_instruction_trace_length() {
+            let Ok(instruction) =
+                transaction_context.get_instruction_context_at_index_in_trace(index)
+            else {
+                continue;
+            };
+            let Ok(program_id) = instruction.get_program_key() else {
+                continue;
+            };
+            trace.push(Invocation {
+                index: 0,
+                stack_height: instruction.get_stack_height(),
+                program_id: *program_id,
+                accounts: (0..instruction.get_number_of_instruction_accounts())
+                    .filter_map(|account| instruction.get_key_of_instruction_account(account).ok())
+                    .copied()
+                    .collect(),
+                data: instruction.get_instruction_data().to_vec(),
+            });
+        }
+    }
+}
+
+/// Returns the token program, mint, owner and amount of an initialized
+/// token account
+fn token_account(account: &Account) -> Option<(Pubkey, Pubkey, Pubkey, u64)> {
+    if account.owner != TOKEN_PROGRAM_ID && account.owner != TOKEN_2022_PROGRAM_ID {
+        return None;
+    }
+    // Mint (32), owner (32), amount (u64), delegate (36), state (u8), ...
+    let data = &account.data;
+    let is_account = data.len() == TOKEN_ACCOUNT_LEN
+        || data.get(TOKEN_ACCOUNT_LEN) == Some(&ACCOUNT_TYPE_ACCOUNT);
+    if !is_account || data[108] == 0 {
+        return None;
+    }
+    let mint = Pubkey::try_from(&data[0..32]).ok()?;
+    let owner = Pubkey::try_from(&data[32..64]).ok()?;
+    let amount = u64::from_le_bytes(data[64..72].try_into().ok()?);
+    Some((account.owner, mint, owner, amount))
+}
+
+/// Verifies that a simulated smart-wallet transaction makes the required
+/// payment
+///
+/// `layout` is the result of `verify_layout` for the same transaction.
+pub fn verify_simulation(
+    simulation: &Simulation,
+    layout: &Layout,
+    requirements: &PaymentRequirements,
+) -> Result<(), SimulationRejection> {
+    if let Some(failure) = &simulation.failure {
+        let error = failure.error.clone();
+        if Some(failure.program_id) == requirements.extra.validator_program {
+            return Err(SimulationRejection::DeadlineFailed(error));
+        }
+        return Err(SimulationRejection::InstructionFailed {
+            index: failure.index,
+            error,
+        });
+    }
+    if simulation.compute_units_consumed > u64::from(layout.compute_unit_limit) {
+        return Err(SimulationRejection::ComputeUnitLimitExceeded(
+            simulation.compute_units_consumed,
+        ));
+    }
+
+    let transfers: Vec<_> = simulation
+        .inner_instructions()
+        .filter(|ix| ix.program_id == TOKEN_PROGRAM_ID || ix.program_id == TOKEN_2022_PROGRAM_ID)
+        .filter(|ix| matches!(ix.data.first(), Some(&(TRANSFER | TRANSFER_CHECKED))))
+        .collect();
+    let [transfer] = transfers[..] else {
+        return Err(SimulationRejection::TransferCount(transfers.len()));
+    };
+
+    // TransferChecked: discriminator, amount (u64), decimals (u8)
+    let data = &transfer.data;
+    if data.len() != 10 || data[0] != TRANSFER_CHECKED {
+        return Err(SimulationRejection::InvalidTransfer);
+    }
+    let amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
+    let [Some(_), Some(&mint), Some(&destination)] =
+        [0, 1, 2].map(|position| transfer.accounts.get(position))
+    else {
+        return Err(SimulationRejection::InvalidTransfer);
+    };
+
+    let expected = requirements.max_amount_required;
+    if amount != expected {
+        return Err(SimulationRejection::AmountMismatch {
+            expected,
+            actual: amount,
+        });
+    }
+    if mint != requirements.asset {
+        return Err(SimulationRejection::MintMismatch(mint));
+    }
+    if destination != associated_token_address(&requirements.pay_to, &mint, &transfer.program_id) {
+        return Err(SimulationRejection::DestinationMismatch(destination));
+    }
+    let actual = simulation
+        .token_balances
+        .iter()
+        .find(|balance| balance.address == destination)
+        .map_or(0, TokenBalance::delta);
+    if actual != i128::from(expected) {
+        return Err(SimulationRejection::BalanceMismatch { expected, actual });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        requirements_fixture::{requirements, DEADLINE},
+        verify::{Transfer, ASSOCIATED_TOKEN_PROGRAM_ID},
+    };
+    use cascade_protocol_deadline_validator_client::ID as VALIDATOR_ID;
+    use solana_message::Message;
+    use solana_program_option::COption;
+    use spl_token_interface::state::{Account as TokenAccount, AccountState, Mint};
+
+    const AMOUNT: u64 = 1_000_000;
+
+    /// Keys and accounts of a payment
+    struct Fixture {
+        fee_payer: Pubkey,
+        client: Pubkey,
+        pay_to: Pubkey,
+        mint: Pubkey,
+        requirements: PaymentRequirements,
+        accounts: HashMap<Pubkey, Account>,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let fee_payer = Pubkey::new_unique();
+            let client = Pubkey::new_unique();
+            let pay_to = Pubkey::new_unique();
+            let mint = Pubkey::new_unique();
+            let source = associated_token_address(&client, &mint, &TOKEN_PROGRAM_ID);
+            let accounts = HashMap::from([
+                (
+                    fee_payer,
+                    Account::new(1_000_000_000, 0, &SYSTEM_PROGRAM_ID),
+                ),
+                (
+                    mint,
+                    token::create_account_for_mint(Mint {
+                        mint_authority: COption::None,
+                        supply: 10 * AMOUNT,
+                        decimals: 6,
+                        is_initialized: true,
+                        freeze_authority: COption::None,
+                    }),
+                ),
+                (
+                    source,
+                    token::create_account_for_token_account(TokenAccount {
+                        mint,
+                        owner: client,
+                        amount: 10 * AMOUNT,
+                        delegate: COption::None,
+                        state: AccountState::Initialized,
+                        is_native: COption::None,
+                        delegated_amount: 0,
+                        close_authority: COption::None,
+                    }),
+                ),
+            ]);
+            Self {
+                fee_payer,
+                client,
+                pay_to,
+                mint,
+                requirements: requirements(fee_payer, pay_to, mint, AMOUNT),
+                accounts,
+            }
+        }
+
+        fn source(&self) -> Pubkey {
+            associated_token_address(&self.client, &self.mint, &TOKEN_PROGRAM_ID)
+        }
+
+        fn destination(&self) -> Pubkey {
+            associated_token_address(&self.pay_to, &self.mint, &TOKEN_PROGRAM_ID)
+        }
+
+        fn create_ata(&self) -> Instruction {
+            Instruction::new_with_bytes(
+                ASSOCIATED_TOKEN_PROGRAM_ID,
+                &[1],
+                vec![
+                    AccountMeta::new(self.fee_payer, true),
+                    AccountMeta::new(self.destination(), false),
+                    AccountMeta::new_readonly(self.pay_to, false),
+                    AccountMeta::new_readonly(self.mint, false),
+                    AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                    AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                ],
+            )
+        }
+
+        fn transfer_checked(&self) -> Instruction {
+            Instruction::new_with_bytes(
+                TOKEN_PROGRAM_ID,
+                &transfer_checked_data(AMOUNT),
+                vec![
+                    AccountMeta::new(self.source(), false),
+                    AccountMeta::new_readonly(self.mint, false),
+                    AccountMeta::new(self.destination(), false),
+                    AccountMeta::new_readonly(self.client, true),
+                ],
+            )
+        }
+
+        fn simulate(&self, instructions: &[Instruction]) -> Simulation {
+            let message = Message::new(instructions, Some(&self.fee_payer));
+            Simulator::new().simulate(&VersionedMessage::Legacy(message), None, &self.accounts)
+        }
+
+        /// A simulation in which a smart wallet made the transfer through
+        /// CPI
+        fn cpi_simulation(&self, data: Vec<u8>) -> Simulation {
+            Simulation {
+                failure: None,
+                compute_units_consumed: 20_000,
+                invocations: vec![
+                    Invocation {
+                        index: 2,
+                        stack_height: 1,
+                        program_id: WALLET_ID,
+                        accounts: vec![],
+                        data: vec![],
+                    },
+                    Invocation {
+                        index: 2,
+                        stack_height: 2,
+                        program_id: TOKEN_PROGRAM_ID,
+                        accounts: vec![
+                            self.source(),
+                            self.mint,
+                            self.destination(),
+                            Pubkey::new_unique(),
+                        ],
+                        data,
+                    },
+                ],
+                token_balances: vec![TokenBalance {
+                    address: self.destination(),
+                    program_id: TOKEN_PROGRAM_ID,
+                    mint: self.mint,
+                    owner: self.pay_to,
+                    pre_amount: 5,
+                    post_amount: 5 + AMOUNT,
+                }],
+            }
+        }
+    }
+
+    const WALLET_ID: Pubkey = solana_pubkey::pubkey!("SMRTzfY6DfH5ik3TKiyLFfXexV8uSG3d2UksSCYdunG");
+
+    fn layout() -> Layout {
+        Layout {
+            advance_nonce: false,
+            compute_unit_limit: 200_000,
+            compute_unit_price: 1,
+            deadline: Some(DEADLINE),
+            create_ata: false,
+            transfer: Transfer::Cpi {
+                program_id: WALLET_ID,
+            },
+        }
+    }
+
+    fn transfer_checked_data(amount: u64) -> Vec<u8> {
+        let mut data = vec![TRANSFER_CHECKED];
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.push(6);
+        data
+    }
+
+    #[test]
+    fn test_records_cpis_and_balances() {
+        let fixture = Fixture::new();
+        let simulation = fixture.simulate(&[fixture.create_ata(), fixture.transfer_checked()]);
+        assert_eq!(simulation.failure, None);
+        assert!(simulation.compute_units_consumed > 0);
+
+        // The ATA program initializes the account through CPI
+        let inner: Vec<_> = simulation.inner_instructions().collect();
+        assert!(inner.iter().all(|ix| ix.index == 0));
+        assert!(inner
+            .iter()
+            .any(|ix| ix.program_id == SYSTEM_PROGRAM_ID && ix.stack_height == 2));
+        assert!(
+            inner
+                .iter()
+                .any(|ix| ix.program_id == TOKEN_PROGRAM_ID
+                    && ix.accounts[0] == fixture.destination())
+        );
+        let top_level: Vec<_> = simulation
+            .invocations
+            .iter()
+            .filter(|ix| ix.stack_height == 1)
+            .map(|ix| (ix.index, ix.program_id))
+            .collect();
+        assert_eq!(
+            top_level,
+            [(0, ASSOCIATED_TOKEN_PROGRAM_ID), (1, TOKEN_PROGRAM_ID)]
+        );
+
+        let delta = |address| {
+            simulation
+                .token_balances
+                .iter()
+                .find(|balance| balance.address == address)
+                .map(TokenBalance::delta)
+        };
+        assert_eq!(delta(fixture.source()), Some(-i128::from(AMOUNT)));
+        assert_eq!(delta(fixture.destination()), Some(i128::from(AMOUNT)));
+
+        // A top-level TransferChecked is not a CPI
+        assert_eq!(
+            verify_simulation(&simulation, &layout(), &fixture.requirements),
+            Err(SimulationRejection::TransferCount(0))
+        );
+    }
+
+    #[test]
+    fn test_failures() {
+        let fixture = Fixture::new();
+
+        // Unknown programs fail instead of being skipped
+        let wallet = Instruction::new_with_bytes(WALLET_ID, &[0], vec![]);
+        let simulation = fixture.simulate(&[fixture.create_ata(), wallet]);
+        assert_eq!(
+            simulation.failure,
+            Some(Failure {
+                index: 1,
+                program_id: WALLET_ID,
+                error: InstructionError::UnsupportedProgramId,
+            })
+        );
+
+        // Later instructions are not run
+        let mut transfer = fixture.transfer_checked();
+        transfer.data = transfer_checked_data(100 * AMOUNT);
+        let simulation = fixture.simulate(&[transfer, fixture.create_ata()]);
+        assert_eq!(
+            simulation.failure.as_ref().map(|failure| failure.index),
+            Some(0)
+        );
+        assert!(simulation.invocations.iter().all(|ix| ix.index == 0));
+        assert!(matches!(
+            verify_simulation(&simulation, &layout(), &fixture.requirements),
+            Err(SimulationRejection::InstructionFailed { index: 0, .. })
+        ));
+
+        // Failures of the deadline validator, e.g. DeadlineExpired
+        let simulation = Simulation {
+            failure: Some(Failure {
+                index: 2,
+                program_id: VALIDATOR_ID,
+                error: InstructionError::Custom(0xDEAD_0000),
+            }),
+            ..Simulation::default()
+        };
+        assert_eq!(
+            verify_simulation(&simulation, &layout(), &fixture.requirements),
+            Err(SimulationRejection::DeadlineFailed(
+                InstructionError::Custom(0xDEAD_0000)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_verify_transfer_cpi() {
+        let fixture = Fixture::new();
+        let simulation = fixture.cpi_simulation(transfer_checked_data(AMOUNT));
+        assert_eq!(
+            verify_simulation(&simulation, &layout(), &fixture.requirements),
+            Ok(())
+        );
+
+        let mut layout = layout();
+        layout.compute_unit_limit = 10_000;
+        assert_eq!(
+            verify_simulation(&simulation, &layout, &fixture.requirements),
+            Err(SimulationRejection::ComputeUnitLimitExceeded(20_000))
+        );
+    }
+
+    #[test]
+    fn test_reject_transfer_cpi() {
+        let fixture = Fixture::new();
+        let verify = |simulation: &Simulation| {
+            verify_simulation(simulation, &layout(), &fixture.requirements)
+        };
+
+        let mut simulation = fixture.cpi_simulation(transfer_checked_data(AMOUNT));
+        simulation
+            .invocations
+            .push(simulation.invocations[1].clone());
+        assert_eq!(
+            verify(&simulation),
+            Err(SimulationRejection::TransferCount(2))
+        );
+
+        // Plain Transfer skips the mint and decimals checks
+        let mut data = vec![TRANSFER];
+        data.extend_from_slice(&AMOUNT.to_le_bytes());
+        let simulation = fixture.cpi_simulation(data);
+        assert_eq!(
+            verify(&simulation),
+            Err(SimulationRejection::InvalidTransfer)
+        );
+
+        let simulation = fixture.cpi_simulation(transfer_checked_data(AMOUNT - 1));
+        assert_eq!(
+            verify(&simulation),
+            Err(SimulationRejection::AmountMismatch {
+                expected: AMOUNT,
+                actual: AMOUNT - 1,
+            })
+        );
+
+        let mut simulation = fixture.cpi_simulation(transfer_checked_data(AMOUNT));
+        let other = Pubkey::new_unique();
+        simulation.invocations[1].accounts[1] = other;
+        assert_eq!(
+            verify(&simulation),
+            Err(SimulationRejection::MintMismatch(other))
+        );
+
+        let mut simulation = fixture.cpi_simulation(transfer_checked_data(AMOUNT));
+        simulation.invocations[1].accounts[2] = other;
+        assert_eq!(
+            verify(&simulation),
+            Err(SimulationRejection::DestinationMismatch(other))
+        );
+
+        // E.g. a Token-2022 transfer fee
+        let mut simulation = fixture.cpi_simulation(transfer_checked_data(AMOUNT));
+        simulation.token_balances[0].post_amount -= 100;
+        assert_eq!(
+            verify(&simulation),
+            Err(SimulationRejection::BalanceMismatch {
+                expected: AMOUNT,
+                actual: i128::from(AMOUNT - 100),
+            })
+        );
+    }
+}
@@ -0,0 +1,1126 @@
+//! Static verification of the RFC's unified instruction layout
+//!
+//! Section "1. Instruction Layout" of the RFC allows 3 to 6 top-level
+//! instructions, in this order:
+//!
+//! 1. Optional System Program `AdvanceNonceAccount`
+//! 2. Compute Budget `SetComputeUnitLimit`
+//! 3. Compute Budget `SetComputeUnitPrice`, at most
+//!    [`MAX_COMPUTE_UNIT_PRICE`]
+//! 4. Optional `ValidateDeadline` of `extra.validatorProgram`, required
+//!    when `extra.deadline` is set
+//! 5. Optional Associated Token Account `Create` or `CreateIdempotent` for
+//!    (`payTo`, `asset`)
+//! 6. `TransferChecked` of exactly `maxAmountRequired` with the decimals of
+//!    `asset` to the `payTo` associated token account, or a smart-wallet instruction that
+//!    transfers through CPI
+//!
+//! The fee payer must be `extra.feePayer` and must not appear in any
+//! instruction's accounts, except as the funding account of the ATA
+//! creation.
+//!
+//! [`verify_layout`] checks all of this from the message alone. It does
+//! not check account state, such as the source balance or the nonce
+//! account, and a smart-wallet transfer still has to be verified by
+//! simulation, as [`Transfer::Cpi`] reports.
+
+use crate::requirements::PaymentRequirements;
+use cascade_protocol_deadline_codec::{DeadlineInstruction, Envelope};
+use solana_message::{
+    compiled_instruction::CompiledInstruction, v0::LoadedAddresses, AccountKeys, VersionedMessage,
+};
+use solana_pubkey::{pubkey, Pubkey};
+use thiserror::Error;
+
+/// System program address
+pub const SYSTEM_PROGRAM_ID: Pubkey = pubkey!("11111111111111111111111111111111");
+
+/// Compute Budget program address
+pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey =
+    pubkey!("ComputeBudget111111111111111111111111111111");
+
+/// SPL Token program address
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// Token-2022 program address
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// Associated Token Account program address
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// Highest accepted compute unit price, 5 lamports per compute unit, in
+/// micro-lamports
+pub const MAX_COMPUTE_UNIT_PRICE: u64 = 5_000_000;
+
+/// Fewest instructions of a valid layout
+pub const MIN_INSTRUCTIONS: usize = 3;
+
+/// Most instructions of a valid layout
+pub const MAX_INSTRUCTIONS: usize = 6;
+
+/// System Program `AdvanceNonceAccount`, a little-endian u32 tag
+pub(crate) const ADVANCE_NONCE_ACCOUNT: [u8; 4] = [4, 0, 0, 0];
+
+/// Compute Budget `SetComputeUnitLimit` discriminator
+const SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+
+/// Compute Budget `SetComputeUnitPrice` discriminator
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+
+/// Token `TransferChecked` discriminator
+pub(crate) const TRANSFER_CHECKED: u8 = 12;
+
+/// Reasons a transaction does not match the layout
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum Rejection {
+    /// The transaction has fewer than 3 or more than 6 instructions
+    #[error("Expected 3 to 6 instructions, found {0}")]
+    InstructionCount(usize),
+
+    /// An instruction's program ID index is outside the account keys
+    #[error("Instruction {index}: invalid program ID index")]
+    InvalidProgramIdIndex {
+        /// Index of the instruction
+        index: usize,
+    },
+
+    /// The fee payer is not `extra.feePayer`
+    #[error("Fee payer {0} is not the facilitator")]
+    FeePayerMismatch(Pubkey),
+
+    /// The message has no static account keys, so no fee payer
+    #[error("Missing fee payer")]
+    MissingFeePayer,
+
+    /// `AdvanceNonceAccount` is malformed or not the first instruction
+    #[error("Instruction {index}: nonce advance must be the first instruction")]
+    InvalidNonceAdvance {
+        /// Index of the instruction
+        index: usize,
+    },
+
+    /// The compute unit limit instruction is missing or malformed
+    #[error("Instruction {index}: expected SetComputeUnitLimit")]
+    MissingComputeUnitLimit {
+        /// Index of the instruction
+        index: usize,
+    },
+
+    /// The compute unit price instruction is missing or malformed
+    #[error("Instruction {index}: expected SetComputeUnitPrice")]
+    MissingComputeUnitPrice {
+        /// Index of the instruction
+        index: usize,
+    },
+
+    /// The compute unit price is above `MAX_COMPUTE_UNIT_PRICE`
+    #[error("Compute unit price {0} exceeds the maximum")]
+    ComputeUnitPriceTooHigh(u64),
+
+    /// `extra.deadline` is set, but the transaction does not enforce it
+    #[error("Missing deadline instruction")]
+    MissingDeadline,
+
+    /// The deadline program's instruction is not a plain `ValidateDeadline`
+    #[error("Instruction {index}: expected ValidateDeadline")]
+    InvalidDeadlineInstruction {
+        /// Index of the instruction
+        index: usize,
+    },
+
+    /// The enforced deadline differs from `extra.deadline`
+    #[error("Deadline {actual} does not match the required deadline {expected}")]
+    DeadlineMismatch {
+        /// `extra.deadline`
+        expected: i64,
+        /// Deadline of the instruction
+        actual: i64,
+    },
+
+    /// The ATA creation is malformed or creates another account
+    #[error("Instruction {index}: ATA creation must create the payTo account for the asset")]
+    InvalidAtaCreate {
+        /// Index of the instruction
+        index: usize,
+    },
+
+    /// A token program instruction other than a well-formed
+    /// `TransferChecked` is last
+    #[error("Instruction {index}: expected TransferChecked")]
+    InvalidTransfer {
+        /// Index of the instruction
+        index: usize,
+    },
+
+    /// The transfer amount differs from `maxAmountRequired`
+    #[error("Amount {actual} does not match the required amount {expected}")]
+    AmountMismatch {
+        /// `maxAmountRequired`
+        expected: u64,
+        /// Amount of the transfer
+        actual: u64,
+    },
+
+    /// The transfer mint is not `asset`
+    #[error("Mint {0} is not the required asset")]
+    MintMismatch(Pubkey),
+
+    /// The transfer decimals are not those of `asset`
+    #[error("Decimals {actual} do not match the asset's decimals {expected}")]
+    DecimalsMismatch {
+        /// Decimals of `asset`
+        expected: u8,
+        /// Decimals of the transfer
+        actual: u8,
+    },
+
+    /// The transfer destination is not the `payTo` associated token account
+    #[error("Destination {0} is not the payTo token account")]
+    DestinationMismatch(Pubkey),
+
+    /// An instruction is not allowed at its position
+    #[error("Instruction {index}: unexpected instruction")]
+    UnexpectedInstruction {
+        /// Index of the instruction
+        index: usize,
+    },
+
+    /// The fee payer appears in an instruction's accounts
+    #[error("Instruction {index}: fee payer must not be used")]
+    FeePayerInInstruction {
+        /// Index of the instruction
+        index: usize,
+    },
+}
+
+/// The verified layout
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Layout {
+    /// Whether the transaction starts with `AdvanceNonceAccount`
+    pub advance_nonce: bool,
+    /// Requested compute unit limit
+    pub compute_unit_limit: u32,
+    /// Compute unit price, in micro-lamports
+    pub compute_unit_price: u64,
+    /// Deadline enforced by the deadline validator, if any
+    pub deadline: Option<i64>,
+    /// Whether the transaction creates the destination token account
+    pub create_ata: bool,
+    /// How the payment is transferred
+    pub transfer: Transfer,
+}
+
+/// The transfer instruction, the last one
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transfer {
+    /// A top-level `TransferChecked`, fully verified
+    Direct {
+        /// SPL Token or Token-2022
+        token_program: Pubkey,
+        /// Source token account
+        source: Pubkey,
+        /// Owner or delegate signing the transfer
+        authority: Pubkey,
+    },
+    /// Another program, e.g. a smart wallet, that must transfer through
+    /// CPI; verify it by simulation
+    Cpi {
+        /// Program of the last instruction
+        program_id: Pubkey,
+    },
+}
+
+/// Returns the associated token account of a wallet for a mint
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Verifies the instruction layout of a payment transaction
+///
+/// `loaded_addresses` are the keys a v0 message loads from address lookup
+/// tables, if any. `requirements` should already be validated.
+/// `decimals` are the decimals of `asset`, read from its mint account,
+/// which a `TransferChecked` must repeat.
+pub fn verify_layout(
+    message: &VersionedMessage,
+    loaded_addresses: Option<&LoadedAddresses>,
+    requirements: &PaymentRequirements,
+    decimals: u8,
+) -> Result<Layout, Rejection> {
+    let instructions = message.instructions();
+    if !(MIN_INSTRUCTIONS..=MAX_INSTRUCTIONS).contains(&instructions.len()) {
+        return Err(Rejection::InstructionCount(instructions.len()));
+    }
+    let keys = AccountKeys::new(message.static_account_keys(), loaded_addresses);
+    let instructions: Vec<_> = instructions
+        .iter()
+        .enumerate()
+        .map(|(index, instruction)| {
+            let program_id = keys
+                .get(usize::from(instruction.program_id_index))
+                .ok_or(Rejection::InvalidProgramIdIndex { index })?;
+            Ok(Decompiled {
+                index,
+                program_id: *program_id,
+                instruction,
+                keys: &keys,
+            })
+        })
+        .collect::<Result<_, Rejection>>()?;
+    // A v0 message may load every key from lookup tables
+    let fee_payer = *message
+        .static_account_keys()
+        .first()
+        .ok_or(Rejection::MissingFeePayer)?;
+    if fee_payer != requirements.extra.fee_payer {
+        return Err(Rejection::FeePayerMismatch(fee_payer));
+    }
+
+    // The fee payer may only fund the ATA creation, checked in
+    // `verify_ata_create`
+    for instruction in &instructions {
+        let is_ata_create = instruction.program_id == ASSOCIATED_TOKEN_PROGRAM_ID;
+        let accounts = &instruction.instruction.accounts;
+        let used = accounts
+            .iter()
+            .enumerate()
+            .filter(|&(position, _)| !(is_ata_create && position == 0))
+            .any(|(_, &account)| keys.get(usize::from(account)) == Some(&fee_payer));
+        if used {
+            return Err(Rejection::FeePayerInInstruction {
+                index: instruction.index,
+            });
+        }
+    }
+
+    let (transfer, rest) = instructions.split_last().expect("checked length");
+    let mut rest = rest.iter().peekable();
+
+    let advance_nonce = match rest.next_if(|ix| ix.program_id == SYSTEM_PROGRAM_ID) {
+        Some(ix) if ix.instruction.data == ADVANCE_NONCE_ACCOUNT => true,
+        Some(ix) => return Err(Rejection::InvalidNonceAdvance { index: ix.index }),
+        None => false,
+    };
+
+    let compute_unit_limit = rest
+        .next()
+        .filter(|ix| ix.program_id == COMPUTE_BUDGET_PROGRAM_ID)
+        .and_then(|ix| compute_budget_arg::<4>(ix, SET_COMPUTE_UNIT_LIMIT))
+        .map(u32::from_le_bytes)
+        .ok_or(Rejection::MissingComputeUnitLimit {
+            index: usize::from(advance_nonce),
+        })?;
+    let compute_unit_price = rest
+        .next()
+        .filter(|ix| ix.program_id == COMPUTE_BUDGET_PROGRAM_ID)
+        .and_then(|ix| compute_budget_arg::<8>(ix, SET_COMPUTE_UNIT_PRICE))
+        .map(u64::from_le_bytes)
+        .ok_or(Rejection::MissingComputeUnitPrice {
+            index: usize::from(advance_nonce) + 1,
+        })?;
+    if compute_unit_price > MAX_COMPUTE_UNIT_PRICE {
+        return Err(Rejection::ComputeUnitPriceTooHigh(compute_unit_price));
+    }
+
+    let validator_program = requirements.extra.validator_program;
+    let deadline = rest.next_if(|ix| Some(ix.program_id) == validator_program);
+    let ata_create = rest.next_if(|ix| ix.program_id == ASSOCIATED_TOKEN_PROGRAM_ID);
+    if let Some(ix) = rest.next() {
+        return Err(Rejection::UnexpectedInstruction { index: ix.index });
+    }
+
+    let deadline = match deadline {
+        Some(ix) => Some(verify_deadline(ix, requirements)?),
+        None if requirements.extra.deadline.is_some() => return Err(Rejection::MissingDeadline),
+        None => None,
+    };
+
+    let transfer = verify_transfer(transfer, requirements, decimals)?;
+    if let Some(ix) = ata_create {
+        verify_ata_create(ix, &transfer, requirements)?;
+    }
+
+    Ok(Layout {
+        advance_nonce,
+        compute_unit_limit,
+        compute_unit_price,
+        deadline,
+        create_ata: ata_create.is_some(),
+        transfer,
+    })
+}
+
+/// An instruction with its resolved program ID
+struct Decompiled<'a> {
+    index: usize,
+    program_id: Pubkey,
+    instruction: &'a CompiledInstruction,
+    keys: &'a AccountKeys<'a>,
+}
+
+impl Decompiled<'_> {
+    /// Returns the account at `position`, if any
+    fn account(&self, position: usize) -> Option<Pubkey> {
+        let index = *self.instruction.accounts.get(position)?;
+        self.keys.get(usize::from(index)).copied()
+    }
+}
+
+/// Returns the argument of a compute budget instruction with the expected
+/// discriminator
+fn compute_budget_arg<const N: usize>(ix: &Decompiled, discriminator: u8) -> Option<[u8; N]> {
+    match ix.instruction.data.split_first() {
+        Some((&first, arg)) if first == discriminator => arg.try_into().ok(),
+        _ => None,
+    }
+}
+
+/// Returns the deadline of a plain `ValidateDeadline`, checked against
+/// `extra.deadline`
+fn verify_deadline(ix: &Decompiled, requirements: &PaymentRequirements) -> Result<i64, Rejection> {
+    let invalid = Rejection::InvalidDeadlineInstruction { index: ix.index };
+    let envelope = Envelope::unpack(&ix.instruction.data).map_err(|_| invalid.clone())?;
+    // Envelope constraints could make the transaction fail before the
+    // deadline, at the fee payer's expense
+    let DeadlineInstruction::ValidateDeadline { deadline } = envelope.instruction else {
+        return Err(invalid);
+    };
+    if !envelope.constraints.is_empty() {
+        return Err(invalid);
+    }
+    match requirements.extra.deadline {
+        Some(expected) if expected == deadline => Ok(deadline),
+        Some(expected) => Err(Rejection::DeadlineMismatch {
+            expected,
+            actual: deadline,
+        }),
+        None => Err(invalid),
+    }
+}
+
+/// Verifies a top-level `TransferChecked`, or accepts any other program
+/// for simulation
+fn verify_transfer(
+    ix: &Decompiled,
+    requirements: &PaymentRequirements,
+    decimals: u8,
+) -> Result<Transfer, Rejection> {
+    let token_program = ix.program_id;
+    if token_program != TOKEN_PROGRAM_ID && token_program != TOKEN_2022_PROGRAM_ID {
+        if token_program == SYSTEM_PROGRAM_ID
+            || token_program == COMPUTE_BUDGET_PROGRAM_ID
+            || token_program == ASSOCIATED_TOKEN_PROGRAM_ID
+            || Some(token_program) == requirements.extra.validator_program
+        {
+            return Err(Rejection::UnexpectedInstruction { index: ix.index });
+        }
+        return Ok(Transfer::Cpi {
+            program_id: token_program,
+        });
+    }
+
+    let invalid = Rejection::InvalidTransfer { index: ix.index };
+    // TransferChecked: discriminator, amount (u64), decimals (u8)
+    let data = &ix.instruction.data;
+    if data.len() != 10 || data[0] != TRANSFER_CHECKED {
+        return Err(invalid);
+    }
+    let amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
+    let [Some(source), Some(mint), Some(destination), Some(authority)] =
+        [0, 1, 2, 3].map(|position| ix.account(position))
+    else {
+        return Err(invalid);
+    };
+
+    if amount != requirements.max_amount_required {
+        return Err(Rejection::AmountMismatch {
+            expected: requirements.max_amount_required,
+            actual: amount,
+        });
+    }
+    if mint != requirements.asset {
+        return Err(Rejection::MintMismatch(mint));
+    }
+    if data[9] != decimals {
+        return Err(Rejection::DecimalsMismatch {
+            expected: decimals,
+            actual: data[9],
+        });
+    }
+    if destination != associated_token_address(&requirements.pay_to, &mint, &token_program) {
+        return Err(Rejection::DestinationMismatch(destination));
+    }
+    Ok(Transfer::Direct {
+        token_program,
+        source,
+        authority,
+    })
+}
+
+/// Verifies that the ATA creation creates the `payTo` account for `asset`
+/// under the transfer's token program
+fn verify_ata_create(
+    ix: &Decompiled,
+    transfer: &Transfer,
+    requirements: &PaymentRequirements,
+) -> Result<(), Rejection> {
+    let invalid = Rejection::InvalidAtaCreate { index: ix.index };
+    // Create: no data or 0, CreateIdempotent: 1
+    if !matches!(ix.instruction.data[..], [] | [0] | [1]) {
+        return Err(invalid);
+    }
+    // Funding, associated account, wallet, mint, system program, token program
+    let [Some(ata), Some(wallet), Some(mint), Some(system_program), Some(token_program)] =
+        [1, 2, 3, 4, 5].map(|position| ix.account(position))
+    else {
+        return Err(invalid);
+    };
+    let token_program_matches = match *transfer {
+        Transfer::Direct {
+            token_program: expected,
+            ..
+        } => token_program == expected,
+        Transfer::Cpi { .. } => {
+            token_program == TOKEN_PROGRAM_ID || token_program == TOKEN_2022_PROGRAM_ID
+        }
+    };
+    let valid = token_program_matches
+        && wallet == requirements.pay_to
+        && mint == requirements.asset
+        && system_program == SYSTEM_PROGRAM_ID
+        && ata == associated_token_address(&wallet, &mint, &token_program);
+    if !valid {
+        return Err(invalid);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requirements_fixture::{requirements, DEADLINE};
+    use cascade_protocol_deadline_validator_client::{
+        instruction::{validate_deadline, versioned, Constraints},
+        ID as VALIDATOR_ID,
+    };
+    use solana_instruction::{AccountMeta, Instruction};
+    use solana_message::{v0, Hash, Message};
+
+    const AMOUNT: u64 = 1_000_000;
+    const DECIMALS: u8 = 6;
+
+    struct Fixture {
+        fee_payer: Pubkey,
+        client: Pubkey,
+        pay_to: Pubkey,
+        mint: Pubkey,
+        requirements: PaymentRequirements,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let fee_payer = Pubkey::new_unique();
+            let pay_to = Pubkey::new_unique();
+            let mint = Pubkey::new_unique();
+            Self {
+                fee_payer,
+                client: Pubkey::new_unique(),
+                pay_to,
+                mint,
+                requirements: requirements(fee_payer, pay_to, mint, AMOUNT),
+            }
+        }
+
+        fn without_deadline(mut self) -> Self {
+            self.requirements.extra.validator_program = None;
+            self.requirements.extra.deadline = None;
+            self
+        }
+
+        fn destination(&self, token_program: &Pubkey) -> Pubkey {
+            associated_token_address(&self.pay_to, &self.mint, token_program)
+        }
+
+        fn transfer(&self, token_program: &Pubkey, amount: u64) -> Instruction {
+            let source = associated_token_address(&self.client, &self.mint, token_program);
+            transfer_checked(
+                token_program,
+                &source,
+                &self.mint,
+                &self.destination(token_program),
+                &self.client,
+                amount,
+            )
+        }
+
+        fn create_ata(&self, token_program: &Pubkey) -> Instruction {
+            create_ata(&self.fee_payer, &self.pay_to, &self.mint, token_program)
+        }
+
+        fn verify(&self, instructions: &[Instruction]) -> Result<Layout, Rejection> {
+            let message = Message::new(instructions, Some(&self.fee_payer));
+            verify_layout(
+                &VersionedMessage::Legacy(message),
+                None,
+                &self.requirements,
+                DECIMALS,
+            )
+        }
+    }
+
+    fn set_compute_unit_limit(units: u32) -> Instruction {
+        let mut data = vec![SET_COMPUTE_UNIT_LIMIT];
+        data.extend_from_slice(&units.to_le_bytes());
+        Instruction::new_with_bytes(COMPUTE_BUDGET_PROGRAM_ID, &data, vec![])
+    }
+
+    fn set_compute_unit_price(micro_lamports: u64) -> Instruction {
+        let mut data = vec![SET_COMPUTE_UNIT_PRICE];
+        data.extend_from_slice(&micro_lamports.to_le_bytes());
+        Instruction::new_with_bytes(COMPUTE_BUDGET_PROGRAM_ID, &data, vec![])
+    }
+
+    fn advance_nonce(nonce: &Pubkey, authority: &Pubkey) -> Instruction {
+        Instruction::new_with_bytes(
+            SYSTEM_PROGRAM_ID,
+            &ADVANCE_NONCE_ACCOUNT,
+            vec![
+                AccountMeta::new(*nonce, false),
+                AccountMeta::new_readonly(
+                    pubkey!("SysvarRecentB1ockHashes11111111111111111111"),
+                    false,
+                ),
+                AccountMeta::new_readonly(*authority, true),
+            ],
+        )
+    }
+
+    fn create_ata(
+        funding: &Pubkey,
+        wallet: &Pubkey,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Instruction {
+        Instruction::new_with_bytes(
+            ASSOCIATED_TOKEN_PROGRAM_ID,
+            &[1],
+            vec![
+                AccountMeta::new(*funding, true),
+                AccountMeta::new(associated_token_address(wallet, mint, token_program), false),
+                AccountMeta::new_readonly(*wallet, false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                AccountMeta::new_readonly(*token_program, false),
+            ],
+        )
+    }
+
+    fn transfer_checked(
+        token_program: &Pubkey,
+        source: &Pubkey,
+        mint: &Pubkey,
+        destination: &Pubkey,
+        authority: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        let mut data = vec![TRANSFER_CHECKED];
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.push(DECIMALS);
+        Instruction::new_with_bytes(
+            *token_program,
+            &data,
+            vec![
+                AccountMeta::new(*source, false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new(*destination, false),
+                AccountMeta::new_readonly(*authority, true),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_minimal_layout() {
+        let fixture = Fixture::new().without_deadline();
+        let layout = fixture
+            .verify(&[
+                set_compute_unit_limit(200_000),
+                set_compute_unit_price(1_000),
+                fixture.transfer(&TOKEN_PROGRAM_ID, AMOUNT),
+            ])
+            .unwrap();
+        assert_eq!(
+            layout,
+            Layout {
+                advance_nonce: false,
+                compute_unit_limit: 200_000,
+                compute_unit_price: 1_000,
+                deadline: None,
+                create_ata: false,
+                transfer: Transfer::Direct {
+                    token_program: TOKEN_PROGRAM_ID,
+                    source: associated_token_address(
+                        &fixture.client,
+                        &fixture.mint,
+                        &TOKEN_PROGRAM_ID
+                    ),
+                    authority: fixture.client,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn test_full_layout() {
+        let fixture = Fixture::new();
+        let nonce = Pubkey::new_unique();
+        let layout = fixture
+            .verify(&[
+                advance_nonce(&nonce, &fixture.client),
+                set_compute_unit_limit(200_000),
+                set_compute_unit_price(MAX_COMPUTE_UNIT_PRICE),
+                validate_deadline(DEADLINE),
+                fixture.create_ata(&TOKEN_2022_PROGRAM_ID),
+                fixture.transfer(&TOKEN_2022_PROGRAM_ID, AMOUNT),
+            ])
+            .unwrap();
+        assert!(layout.advance_nonce);
+        assert_eq!(layout.deadline, Some(DEADLINE));
+        assert!(layout.create_ata);
+        assert!(matches!(
+            layout.transfer,
+            Transfer::Direct { token_program, .. } if token_program == TOKEN_2022_PROGRAM_ID
+        ));
+    }
+
+    #[test]
+    fn test_smart_wallet_layout() {
+        let fixture = Fixture::new();
+        let smart_wallet = Pubkey::new_unique();
+        let execute = Instruction::new_with_bytes(
+            smart_wallet,
+            &[7, 7],
+            vec![AccountMeta::new_readonly(fixture.client, true)],
+        );
+        let layout = fixture
+            .verify(&[
+                set_compute_unit_limit(400_000),
+                set_compute_unit_price(0),
+                validate_deadline(DEADLINE),
+                fixture.create_ata(&TOKEN_PROGRAM_ID),
+                execute,
+            ])
+            .unwrap();
+        assert_eq!(
+            layout.transfer,
+            Transfer::Cpi {
+                program_id: smart_wallet
+            }
+        );
+    }
+
+    #[test]
+    fn test_v0_with_loaded_addresses() {
+        let fixture = Fixture::new().without_deadline();
+        let mut message = v0::Message::try_compile(
+            &fixture.fee_payer,
+            &[
+                set_compute_unit_limit(200_000),
+                set_compute_unit_price(1_000),
+                fixture.transfer(&TOKEN_PROGRAM_ID, AMOUNT),
+            ],
+            &[],
+            Hash::default(),
+        )
+        .unwrap();
+        // Move the destination into a lookup table
+        let destination = fixture.destination(&TOKEN_PROGRAM_ID);
+        let position = message
+            .account_keys
+            .iter()
+            .position(|key| *key == destination)
+            .unwrap();
+        message.account_keys[position] = Pubkey::new_unique();
+        let loaded_index = message.account_keys.len() as u8;
+        message.instructions[2].accounts[2] = loaded_index;
+        let loaded = LoadedAddresses {
+            writable: vec![destination],
+            readonly: vec![],
+        };
+        let message = VersionedMessage::V0(message);
+
+        assert!(verify_layout(&message, Some(&loaded), &fixture.requirements, DECIMALS).is_ok());
+        assert_eq!(
+            verify_layout(&message, None, &fixture.requirements, DECIMALS),
+            Err(Rejection::InvalidTransfer { index: 2 })
+        );
+    }
+
+    #[test]
+    fn test_reject_message_without_static_keys() {
+        let fixture = Fixture::new().without_deadline();
+        let mut message = v0::Message::try_compile(
+            &fixture.fee_payer,
+            &[
+                set_compute_unit_limit(200_000),
+                set_compute_unit_price(1_000),
+                fixture.transfer(&TOKEN_PROGRAM_ID, AMOUNT),
+            ],
+            &[],
+            Hash::default(),
+        )
+        .unwrap();
+        // Every key, program IDs included, resolves through lookup tables
+        let loaded = LoadedAddresses {
+            writable: std::mem::take(&mut message.account_keys),
+            readonly: vec![],
+        };
+        let message = VersionedMessage::V0(message);
+
+        assert_eq!(
+            verify_layout(&message, Some(&loaded), &fixture.requirements, DECIMALS),
+            Err(Rejection::MissingFeePayer)
+        );
+    }
+
+    #[test]
+    fn test_reject_structure() {
+        let fixture = Fixture::new();
+        let limit = set_compute_unit_limit(200_000);
+        let price = set_compute_unit_price(1_000);
+        let deadline = validate_deadline(DEADLINE);
+        let transfer = fixture.transfer(&TOKEN_PROGRAM_ID, AMOUNT);
+        let nonce = advance_nonce(&Pubkey::new_unique(), &fixture.client);
+        let other = Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![]);
+
+        let cases = [
+            (
+                vec![limit.clone(), transfer.clone()],
+                Rejection::InstructionCount(2),
+            ),
+            (
+                vec![
+                    nonce.clone(),
+                    limit.clone(),
+                    price.clone(),
+                    deadline.clone(),
+                    deadline.clone(),
+                    fixture.create_ata(&TOKEN_PROGRAM_ID),
+                    transfer.clone(),
+                ],
+                Rejection::InstructionCount(7),
+            ),
+            (
+                vec![
+                    price.clone(),
+                    limit.clone(),
+                    deadline.clone(),
+                    transfer.clone(),
+                ],
+                Rejection::MissingComputeUnitLimit { index: 0 },
+            ),
+            (
+                vec![limit.clone(), deadline.clone(), transfer.clone()],
+                Rejection::MissingComputeUnitPrice { index: 1 },
+            ),
+            (
+                vec![limit.clone(), price.clone(), transfer.clone()],
+                Rejection::MissingDeadline,
+            ),
+            (
+                vec![
+                    limit.clone(),
+                    price.clone(),
+                    nonce.clone(),
+                    deadline.clone(),
+                    transfer.clone(),
+                ],
+                Rejection::UnexpectedInstruction { index: 2 },
+            ),
+            (
+                vec![
+                    Instruction::new_with_bytes(SYSTEM_PROGRAM_ID, &[2, 0, 0, 0], vec![]),
+                    limit.clone(),
+                    price.clone(),
+                    deadline.clone(),
+                    transfer.clone(),
+                ],
+                Rejection::InvalidNonceAdvance { index: 0 },
+            ),
+            (
+                vec![
+                    limit.clone(),
+                    price.clone(),
+                    deadline.clone(),
+                    other.clone(),
+                    transfer.clone(),
+                ],
+                Rejection::UnexpectedInstruction { index: 3 },
+            ),
+            (
+                vec![
+                    limit.clone(),
+                    price.clone(),
+                    fixture.create_ata(&TOKEN_PROGRAM_ID),
+                    deadline.clone(),
+                    transfer.clone(),
+                ],
+                Rejection::UnexpectedInstruction { index: 3 },
+            ),
+            (
+                vec![
+                    limit.clone(),
+                    price.clone(),
+                    deadline.clone(),
+                    limit.clone(),
+                ],
+                Rejection::UnexpectedInstruction { index: 3 },
+            ),
+        ];
+        for (instructions, expected) in cases {
+            assert_eq!(fixture.verify(&instructions), Err(expected));
+        }
+    }
+
+    #[test]
+    fn test_reject_compute_unit_price() {
+        let fixture = Fixture::new().without_deadline();
+        assert_eq!(
+            fixture.verify(&[
+                set_compute_unit_limit(200_000),
+                set_compute_unit_price(MAX_COMPUTE_UNIT_PRICE + 1),
+                fixture.transfer(&TOKEN_PROGRAM_ID, AMOUNT),
+            ]),
+            Err(Rejection::ComputeUnitPriceTooHigh(
+                MAX_COMPUTE_UNIT_PRICE + 1
+            ))
+        );
+    }
+
+    #[test]
+    fn test_reject_deadline() {
+        let fixture = Fixture::new();
+        let verify = |deadline: Instruction| {
+            fixture.verify(&[
+                set_compute_unit_limit(200_000),
+                set_compute_unit_price(1_000),
+                deadline,
+                fixture.transfer(&TOKEN_PROGRAM_ID, AMOUNT),
+            ])
+        };
+        assert_eq!(
+            verify(validate_deadline(DEADLINE + 60)),
+            Err(Rejection::DeadlineMismatch {
+                expected: DEADLINE,
+                actual: DEADLINE + 60
+            })
+        );
+        let constrained = versioned(
+            validate_deadline(DEADLINE),
+            &Constraints {
+                min_slot: Some(1),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            verify(constrained),
+            Err(Rejection::InvalidDeadlineInstruction { index: 2 })
+        );
+        let mut malformed = validate_deadline(DEADLINE);
+        malformed.data.push(0);
+        assert_eq!(
+            verify(malformed),
+            Err(Rejection::InvalidDeadlineInstruction { index: 2 })
+        );
+        let clock_sanity = Instruction::new_with_bytes(VALIDATOR_ID, &[2; 9], vec![]);
+        assert_eq!(
+            verify(clock_sanity),
+            Err(Rejection::InvalidDeadlineInstruction { index: 2 })
+        );
+
+        // A deadline instruction the requirements do not ask for
+        let fixture = Fixture::new().without_deadline();
+        assert_eq!(
+            fixture.verify(&[
+                set_compute_unit_limit(200_000),
+                set_compute_unit_price(1_000),
+                validate_deadline(DEADLINE),
+                fixture.transfer(&TOKEN_PROGRAM_ID, AMOUNT),
+            ]),
+            Err(Rejection::UnexpectedInstruction { index: 2 })
+        );
+    }
+
+    #[test]
+    fn test_reject_transfer() {
+        let fixture = Fixture::new().without_deadline();
+        let verify = |transfer: Instruction| {
+            fixture.verify(&[
+                set_compute_unit_limit(200_000),
+                set_compute_unit_price(1_000),
+                transfer,
+            ])
+        };
+        assert_eq!(
+            verify(fixture.transfer(&TOKEN_PROGRAM_ID, AMOUNT - 1)),
+            Err(Rejection::AmountMismatch {
+                expected: AMOUNT,
+                actual: AMOUNT - 1
+            })
+        );
+
+        let source = Pubkey::new_unique();
+        let other_mint = Pubkey::new_unique();
+        assert_eq!(
+            verify(transfer_checked(
+                &TOKEN_PROGRAM_ID,
+                &source,
+                &other_mint,
+                &fixture.destination(&TOKEN_PROGRAM_ID),
+                &fixture.client,
+                AMOUNT
+            )),
+            Err(Rejection::MintMismatch(other_mint))
+        );
+
+        // The destination under the other token program
+        let destination = fixture.destination(&TOKEN_2022_PROGRAM_ID);
+        assert_eq!(
+            verify(transfer_checked(
+                &TOKEN_PROGRAM_ID,
+                &source,
+                &fixture.mint,
+                &destination,
+                &fixture.client,
+                AMOUNT
+            )),
+            Err(Rejection::DestinationMismatch(destination))
+        );
+
+        let mut transfer = fixture.transfer(&TOKEN_PROGRAM_ID, AMOUNT);
+        transfer.data[9] = DECIMALS + 3;
+        assert_eq!(
+            verify(transfer),
+            Err(Rejection::DecimalsMismatch {
+                expected: DECIMALS,
+                actual: DECIMALS + 3
+            })
+        );
+
+        // Transfer without decimals check
+        let mut transfer = fixture.transfer(&TOKEN_PROGRAM_ID, AMOUNT);
+        transfer.data = vec![3];
+        transfer.data.extend_from_slice(&AMOUNT.to_le_bytes());
+        assert_eq!(
+            verify(transfer),
+            Err(Rejection::InvalidTransfer { index: 2 })
+        );
+
+        let mut transfer = fixture.transfer(&TOKEN_PROGRAM_ID, AMOUNT);
+        transfer.accounts.pop();
+        assert_eq!(
+            verify(transfer),
+            Err(Rejection::InvalidTransfer { index: 2 })
+        );
+    }
+
+    #[test]
+    fn test_reject_ata_create() {
+        let fixture = Fixture::new().without_deadline();
+        let verify = |create: Instruction| {
+            fixture.verify(&[
+                set_compute_unit_limit(200_000),
+                set_compute_unit_price(1_000),
+                create,
+                fixture.transfer(&TOKEN_PROGRAM_ID, AMOUNT),
+            ])
+        };
+        assert!(verify(fixture.create_ata(&TOKEN_PROGRAM_ID)).is_ok());
+
+        let cases = [
+            create_ata(
+                &fixture.fee_payer,
+                &fixture.client,
+                &fixture.mint,
+                &TOKEN_PROGRAM_ID,
+            ),
+            create_ata(
+                &fixture.fee_payer,
+                &fixture.pay_to,
+                &Pubkey::new_unique(),
+                &TOKEN_PROGRAM_ID,
+            ),
+            fixture.create_ata(&TOKEN_2022_PROGRAM_ID),
+            {
+                let mut recover_nested = fixture.create_ata(&TOKEN_PROGRAM_ID);
+                recover_nested.data = vec![2];
+                recover_nested
+            },
+        ];
+        for create in cases {
+            assert_eq!(
+                verify(create),
+                Err(Rejection::InvalidAtaCreate { index: 2 })
+            );
+        }
+    }
+
+    #[test]
+    fn test_reject_fee_payer() {
+        let fixture = Fixture::new().without_deadline();
+        let limit = set_compute_unit_limit(200_000);
+        let price = set_compute_unit_price(1_000);
+
+        let mut requirements = fixture.requirements.clone();
+        requirements.extra.fee_payer = Pubkey::new_unique();
+        let message = Message::new(
+            &[
+                limit.clone(),
+                price.clone(),
+                fixture.transfer(&TOKEN_PROGRAM_ID, AMOUNT),
+            ],
+            Some(&fixture.fee_payer),
+        );
+        assert_eq!(
+            verify_layout(
+                &VersionedMessage::Legacy(message),
+                None,
+                &requirements,
+                DECIMALS
+            ),
+            Err(Rejection::FeePayerMismatch(fixture.fee_payer))
+        );
+
+        // Fee payer as the transfer authority
+        let source = associated_token_address(&fixture.fee_payer, &fixture.mint, &TOKEN_PROGRAM_ID);
+        let transfer = transfer_checked(
+            &TOKEN_PROGRAM_ID,
+            &source,
+            &fixture.mint,
+            &fixture.destination(&TOKEN_PROGRAM_ID),
+            &fixture.fee_payer,
+            AMOUNT,
+        );
+        assert_eq!(
+            fixture.verify(&[limit.clone(), price.clone(), transfer]),
+            Err(Rejection::FeePayerInInstruction { index: 2 })
+        );
+
+        // Fee payer as the ATA wallet rather than the funding account
+        let mut create = fixture.create_ata(&TOKEN_PROGRAM_ID);
+        create.accounts[2].pubkey = fixture.fee_payer;
+        assert_eq!(
+            fixture.verify(&[
+                limit,
+                price,
+                create,
+                fixture.transfer(&TOKEN_PROGRAM_ID, AMOUNT)
+            ]),
+            Err(Rejection::FeePayerInInstruction { index: 2 })
+        );
+    }
+}
@@ -0,0 +1,284 @@
+//! Integration tests for simulation-based verification
+//!
+//! These tests pay through the Squads-style vault fixture, which transfers
+//! with a `TransferChecked` CPI signed by its vault PDA, and enforce the
+//! deadline with the deadline validator. Both programs are loaded from
+//! `target/deploy`, so run `cargo build-sbf` first.
+//!
+//! ## Running Tests
+//! For clean output without verbose DEBUG logs, use:
+//! ```bash
+//! RUST_LOG=off cargo test --test tests
+//! ```
+
+use {
+    cascade_protocol_deadline_validator_client::{self as client, instruction::validate_deadline},
+    cascade_protocol_deadline_vault_fixture::{self as vault_fixture, execute},
+    cascade_protocol_deadline_x402::{
+        simulate::{SimulationRejection, Simulator},
+        verify::{
+            associated_token_address, verify_layout, Transfer, ASSOCIATED_TOKEN_PROGRAM_ID,
+            COMPUTE_BUDGET_PROGRAM_ID, SYSTEM_PROGRAM_ID, TOKEN_PROGRAM_ID,
+        },
+        verify_simulation, ExactSvmExtra, Network, PaymentRequirements, Scheme,
+    },
+    mollusk_svm::file::load_program_elf,
+    mollusk_svm_programs_token::token,
+    solana_account::Account,
+    solana_instruction::{error::InstructionError, AccountMeta, Instruction},
+    solana_message::{Message, VersionedMessage},
+    solana_program_option::COption,
+    solana_pubkey::Pubkey,
+    spl_token_interface::state::{Account as TokenAccount, AccountState, Mint},
+    std::collections::HashMap,
+};
+
+#[path = "../src/requirements_fixture.rs"]
+mod requirements_fixture;
+
+use requirements_fixture::{requirements, DEADLINE};
+
+const AMOUNT: u64 = 1_000_000;
+const DECIMALS: u8 = 6;
+const VAULT_INDEX: u8 = 0;
+
+/// Helper function to create a simulator with the deadline validator and
+/// the vault fixture at `unix_timestamp`
+fn setup_simulator(unix_timestamp: i64) -> Simulator {
+    std::env::set_var("SBF_OUT_DIR", "../target/deploy");
+    let mut simulator = Simulator::new();
+    simulator.add_program(
+        &client::id(),
+        &load_program_elf("cascade_protocol_deadline_validator"),
+    );
+    simulator.add_program(
+        &vault_fixture::id(),
+        &load_program_elf("cascade_protocol_deadline_vault_fixture"),
+    );
+    simulator.set_clock(1_000, unix_timestamp);
+    simulator
+}
+
+/// A multisig whose vault pays `pay_to`, with the facilitator paying fees
+struct Fixture {
+    fee_payer: Pubkey,
+    multisig: Pubkey,
+    member: Pubkey,
+    vault: Pubkey,
+    pay_to: Pubkey,
+    mint: Pubkey,
+    requirements: PaymentRequirements,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let fee_payer = Pubkey::new_unique();
+        let multisig = Pubkey::new_unique();
+        let pay_to = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        Self {
+            fee_payer,
+            multisig,
+            member: Pubkey::new_unique(),
+            vault: vault_fixture::get_vault_address_and_bump_seed(
+                &multisig,
+                VAULT_INDEX,
+                &vault_fixture::id(),
+            )
+            .0,
+            pay_to,
+            mint,
+            requirements: requirements(fee_payer, pay_to, mint, AMOUNT),
+        }
+    }
+
+    fn source(&self) -> Pubkey {
+        associated_token_address(&self.vault, &self.mint, &TOKEN_PROGRAM_ID)
+    }
+
+    fn destination(&self) -> Pubkey {
+        associated_token_address(&self.pay_to, &self.mint, &TOKEN_PROGRAM_ID)
+    }
+
+    /// Account snapshots, as a facilitator would fetch them
+    fn accounts(&self) -> HashMap<Pubkey, Account> {
+        HashMap::from([
+            (
+                self.fee_payer,
+                Account::new(1_000_000_000, 0, &SYSTEM_PROGRAM_ID),
+            ),
+            (self.member, Account::new(1_000_000, 0, &SYSTEM_PROGRAM_ID)),
+            (
+                self.multisig,
+                Account {
+                    lamports: 1_000_000,
+                    data: self.member.to_bytes().to_vec(),
+                    owner: vault_fixture::id(),
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            ),
+            (
+                self.mint,
+                token::create_account_for_mint(Mint {
+                    mint_authority: COption::None,
+                    supply: 10 * AMOUNT,
+                    decimals: DECIMALS,
+                    is_initialized: true,
+                    freeze_authority: COption::None,
+                }),
+            ),
+            (
+                self.source(),
+                token::create_account_for_token_account(TokenAccount {
+                    mint: self.mint,
+                    owner: self.vault,
+                    amount: 10 * AMOUNT,
+                    delegate: COption::None,
+                    state: AccountState::Initialized,
+                    is_native: COption::None,
+                    delegated_amount: 0,
+                    close_authority: COption::None,
+                }),
+            ),
+        ])
+    }
+
+    /// `TransferChecked` from the vault, for the vault to run
+    fn transfer_checked(&self, amount: u64) -> Instruction {
+        let mut data = vec![12];
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.push(DECIMALS);
+        Instruction::new_with_bytes(
+            TOKEN_PROGRAM_ID,
+            &data,
+            vec![
+                AccountMeta::new(self.source(), false),
+                AccountMeta::new_readonly(self.mint, false),
+                AccountMeta::new(self.destination(), false),
+                AccountMeta::new_readonly(self.vault, true),
+            ],
+        )
+    }
+
+    fn create_ata(&self) -> Instruction {
+        Instruction::new_with_bytes(
+            ASSOCIATED_TOKEN_PROGRAM_ID,
+            &[1],
+            vec![
+                AccountMeta::new(self.fee_payer, true),
+                AccountMeta::new(self.destination(), false),
+                AccountMeta::new_readonly(self.pay_to, false),
+                AccountMeta::new_readonly(self.mint, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            ],
+        )
+    }
+
+    /// The RFC's layout, with the vault's `Execute` last
+    fn message(&self, member: &Pubkey, transfer: &Instruction) -> VersionedMessage {
+        let mut set_compute_unit_limit = vec![2];
+        set_compute_unit_limit.extend_from_slice(&200_000u32.to_le_bytes());
+        let mut set_compute_unit_price = vec![3];
+        set_compute_unit_price.extend_from_slice(&1_000u64.to_le_bytes());
+        let instructions = [
+            Instruction::new_with_bytes(COMPUTE_BUDGET_PROGRAM_ID, &set_compute_unit_limit, vec![]),
+            Instruction::new_with_bytes(COMPUTE_BUDGET_PROGRAM_ID, &set_compute_unit_price, vec![]),
+            validate_deadline(DEADLINE),
+            self.create_ata(),
+            execute(&self.multisig, member, VAULT_INDEX, transfer),
+        ];
+        VersionedMessage::Legacy(Message::new(&instructions, Some(&self.fee_payer)))
+    }
+
+    /// Verifies the layout, simulates and verifies the simulation
+    fn verify(
+        &self,
+        simulator: &Simulator,
+        message: &VersionedMessage,
+    ) -> Result<(), SimulationRejection> {
+        let layout = verify_layout(message, None, &self.requirements, DECIMALS).unwrap();
+        assert_eq!(
+            layout.transfer,
+            Transfer::Cpi {
+                program_id: vault_fixture::id()
+            }
+        );
+        let simulation = simulator.simulate(message, None, &self.accounts());
+        verify_simulation(&simulation, &layout, &self.requirements)
+    }
+}
+
+#[test]
+fn test_vault_payment() {
+    let fixture = Fixture::new();
+    let simulator = setup_simulator(DEADLINE - 60);
+    let message = fixture.message(&fixture.member, &fixture.transfer_checked(AMOUNT));
+    assert_eq!(fixture.verify(&simulator, &message), Ok(()));
+
+    // The transfer is a CPI of the vault's Execute, signed by the vault
+    let simulation = simulator.simulate(&message, None, &fixture.accounts());
+    let transfer = simulation
+        .inner_instructions()
+        .find(|ix| ix.program_id == TOKEN_PROGRAM_ID && ix.data.first() == Some(&12))
+        .unwrap();
+    assert_eq!(transfer.index, 4);
+    assert_eq!(transfer.stack_height, 2);
+    assert_eq!(transfer.accounts[3], fixture.vault);
+
+    let delta = |address| {
+        simulation
+            .token_balances
+            .iter()
+            .find(|balance| balance.address == address)
+            .map(|balance| balance.delta())
+    };
+    assert_eq!(delta(fixture.source()), Some(-i128::from(AMOUNT)));
+    assert_eq!(delta(fixture.destination()), Some(i128::from(AMOUNT)));
+}
+
+#[test]
+fn test_vault_payment_at_deadline() {
+    let fixture = Fixture::new();
+    let message = fixture.message(&fixture.member, &fixture.transfer_checked(AMOUNT));
+    assert_eq!(fixture.verify(&setup_simulator(DEADLINE), &message), Ok(()));
+}
+
+#[test]
+fn test_vault_payment_after_deadline() {
+    let fixture = Fixture::new();
+    let message = fixture.message(&fixture.member, &fixture.transfer_checked(AMOUNT));
+    assert!(matches!(
+        fixture.verify(&setup_simulator(DEADLINE + 1), &message),
+        Err(SimulationRejection::DeadlineFailed(
+            InstructionError::Custom(_)
+        ))
+    ));
+}
+
+#[test]
+fn test_vault_payment_wrong_amount() {
+    let fixture = Fixture::new();
+    let message = fixture.message(&fixture.member, &fixture.transfer_checked(AMOUNT - 1));
+    assert_eq!(
+        fixture.verify(&setup_simulator(DEADLINE - 60), &message),
+        Err(SimulationRejection::AmountMismatch {
+            expected: AMOUNT,
+            actual: AMOUNT - 1,
+        })
+    );
+}
+
+#[test]
+fn test_vault_payment_by_non_member() {
+    let fixture = Fixture::new();
+    let message = fixture.message(&Pubkey::new_unique(), &fixture.transfer_checked(AMOUNT));
+    assert_eq!(
+        fixture.verify(&setup_simulator(DEADLINE - 60), &message),
+        Err(SimulationRejection::InstructionFailed {
+            index: 4,
+            error: InstructionError::InvalidAccountData,
+        })
+    );
+}